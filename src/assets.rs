@@ -0,0 +1,269 @@
+//! Canonical asset identifiers.
+//!
+//! Venues name the same coin differently (`XBT` vs `BTC`, `BCHABC` for
+//! BCH, `MATIC` after its rename to `POL`, network markers such as
+//! `USDT-ERC20`). Every fetched pair is mapped onto one canonical name per
+//! asset before scanning, so graphs and cross-venue comparisons line up,
+//! while the venue's own spelling is kept for display. Bridged tokens such
+//! as `USDC.E` trade at their own price and stay separate unless config
+//! aliases them.
+
+use std::collections::HashMap;
+use tracing::debug;
+
+use crate::config::AssetsConfig;
+use crate::models::{PairPrice, PerpQuote};
+
+/// Renames that hold on every venue; config entries override these
+const BUILTIN_ALIASES: &[(&str, &str)] = &[
+    ("XBT", "BTC"),
+    ("XDG", "DOGE"),
+    ("BCHABC", "BCH"),
+    ("BCHSV", "BSV"),
+    ("MATIC", "POL"),
+    ("RNDR", "RENDER"),
+];
+
+/// Venue-specific codes; Kraken prefixes its oldest assets with X (crypto)
+/// or Z (fiat) in REST responses
+const BUILTIN_VENUE_ALIASES: &[(&str, &[(&str, &str)])] = &[(
+    "kraken",
+    &[
+        ("XXBT", "BTC"),
+        ("XETH", "ETH"),
+        ("XETC", "ETC"),
+        ("XLTC", "LTC"),
+        ("XXRP", "XRP"),
+        ("XXLM", "XLM"),
+        ("XXMR", "XMR"),
+        ("XZEC", "ZEC"),
+        ("XMLN", "MLN"),
+        ("XREP", "REP"),
+        ("XXDG", "DOGE"),
+        ("ZUSD", "USD"),
+        ("ZEUR", "EUR"),
+        ("ZGBP", "GBP"),
+        ("ZCAD", "CAD"),
+        ("ZJPY", "JPY"),
+        ("ZAUD", "AUD"),
+    ],
+)];
+
+/// Chain markers stripped from network-specific tickers of one token
+pub const DEFAULT_CHAIN_SUFFIXES: &[&str] = &["-ERC20", "-TRC20", "-BEP20", "-SPL"];
+
+#[derive(Debug, Clone)]
+pub struct AssetRegistry {
+    aliases: HashMap<String, String>,
+    /// Per-exchange aliases, consulted before `aliases`
+    venues: HashMap<String, HashMap<String, String>>,
+    chain_suffixes: Vec<String>,
+}
+
+fn upper_map(map: &HashMap<String, String>) -> impl Iterator<Item = (String, String)> + '_ {
+    map.iter()
+        .map(|(k, v)| (k.trim().to_uppercase(), v.trim().to_uppercase()))
+}
+
+impl AssetRegistry {
+    pub fn new(cfg: &AssetsConfig) -> Self {
+        let mut aliases: HashMap<String, String> = BUILTIN_ALIASES
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        aliases.extend(upper_map(&cfg.aliases));
+        let mut venues: HashMap<String, HashMap<String, String>> = BUILTIN_VENUE_ALIASES
+            .iter()
+            .map(|(ex, list)| {
+                let map = list.iter().map(|(k, v)| (k.to_string(), v.to_string()));
+                (ex.to_string(), map.collect())
+            })
+            .collect();
+        for (ex, map) in &cfg.venues {
+            venues.entry(ex.clone()).or_default().extend(upper_map(map));
+        }
+        Self {
+            aliases,
+            venues,
+            chain_suffixes: cfg
+                .chain_suffixes
+                .iter()
+                .map(|s| s.trim().to_uppercase())
+                .filter(|s| !s.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Canonical name of `symbol` as listed on `exchange`
+    pub fn canonical(&self, exchange: &str, symbol: &str) -> String {
+        let upper = symbol.trim().to_uppercase();
+        if let Some(hit) = self.lookup(exchange, &upper) {
+            return hit.to_string();
+        }
+        let bare = self
+            .chain_suffixes
+            .iter()
+            .find_map(|s| upper.strip_suffix(s.as_str()).filter(|b| !b.is_empty()))
+            .unwrap_or(&upper);
+        self.lookup(exchange, bare).unwrap_or(bare).to_string()
+    }
+
+    fn lookup(&self, exchange: &str, symbol: &str) -> Option<&str> {
+        self.venues
+            .get(exchange)
+            .and_then(|m| m.get(symbol))
+            .or_else(|| self.aliases.get(symbol))
+            .map(String::as_str)
+    }
+
+    /// Rewrite `base`/`quote` to canonical names. Pairs that collapse onto
+    /// one asset (`USDT-ERC20/USDT`) are dropped, and when two listings end up
+    /// as the same market (`XBT/USDT` next to `BTC/USDT`) the more liquid
+    /// one is kept.
+    pub fn normalize(&self, exchange: &str, pairs: Vec<PairPrice>) -> Vec<PairPrice> {
+        let mut out: Vec<PairPrice> = Vec::with_capacity(pairs.len());
+        let mut index: HashMap<(String, String), usize> = HashMap::new();
+
+        for mut p in pairs {
+            p.base = self.canonical(exchange, &p.native_base);
+            p.quote = self.canonical(exchange, &p.native_quote);
+            if p.base == p.quote {
+                debug!(
+                    "{}: dropping {}/{}, both legs are {}",
+                    exchange, p.native_base, p.native_quote, p.base
+                );
+                continue;
+            }
+            // a market and its inverse share one edge in the scanner graph
+            let key = if p.base < p.quote {
+                (p.base.clone(), p.quote.clone())
+            } else {
+                (p.quote.clone(), p.base.clone())
+            };
+            match index.get(&key) {
+                Some(&i) => {
+                    debug!(
+                        "{}: {}/{} and {}/{} are the same market, keeping the more liquid",
+                        exchange,
+                        out[i].native_base,
+                        out[i].native_quote,
+                        p.native_base,
+                        p.native_quote
+                    );
+                    if p.liquidity > out[i].liquidity {
+                        out[i] = p;
+                    }
+                }
+                None => {
+                    index.insert(key, out.len());
+                    out.push(p);
+                }
+            }
+        }
+        out
+    }
+
+    /// Rewrite perp `base`/`quote` to canonical names so they line up with
+    /// the spot pairs of the same venue
+    pub fn normalize_perps(&self, exchange: &str, perps: Vec<PerpQuote>) -> Vec<PerpQuote> {
+        perps
+            .into_iter()
+            .map(|p| PerpQuote {
+                base: self.canonical(exchange, &p.base),
+                quote: self.canonical(exchange, &p.quote),
+                ..p
+            })
+            .filter(|p| p.base != p.quote)
+            .collect()
+    }
+}
+
+/// Built-in aliases only
+impl Default for AssetRegistry {
+    fn default() -> Self {
+        Self::new(&AssetsConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair(base: &str, quote: &str, liquidity: f64) -> PairPrice {
+        PairPrice {
+            symbol: format!("{}{}", base, quote),
+            base: base.to_string(),
+            quote: quote.to_string(),
+            native_base: base.to_string(),
+            native_quote: quote.to_string(),
+            price: 1.0,
+            bid: None,
+            ask: None,
+            is_spot: true,
+            liquidity,
+        }
+    }
+
+    fn registry() -> AssetRegistry {
+        AssetRegistry::default()
+    }
+
+    #[test]
+    fn builtin_aliases_and_chain_suffixes() {
+        let assets = registry();
+        assert_eq!(assets.canonical("binance", "xbt"), "BTC");
+        assert_eq!(assets.canonical("gateio", "MATIC"), "POL");
+        assert_eq!(assets.canonical("kucoin", "BCHABC"), "BCH");
+        // bridged tokens are not the same asset
+        assert_eq!(assets.canonical("kucoin", "USDC.e"), "USDC.E");
+        assert_eq!(assets.canonical("gateio", "USDT-ERC20"), "USDT");
+        assert_eq!(assets.canonical("bybit", "ETH"), "ETH");
+        assert_eq!(assets.canonical("kraken", "XXBT"), "BTC");
+        assert_eq!(assets.canonical("kraken", "ZUSD"), "USD");
+        assert_eq!(assets.canonical("binance", "ZUSD"), "ZUSD");
+    }
+
+    #[test]
+    fn config_overrides_builtins_per_venue() {
+        let cfg = AssetsConfig {
+            aliases: HashMap::from([("MATIC".to_string(), "MATIC".to_string())]),
+            venues: HashMap::from([(
+                "kucoin".to_string(),
+                HashMap::from([
+                    ("WAXP".to_string(), "WAX".to_string()),
+                    ("USDC.E".to_string(), "USDC".to_string()),
+                ]),
+            )]),
+            ..AssetsConfig::default()
+        };
+        let assets = AssetRegistry::new(&cfg);
+        assert_eq!(assets.canonical("binance", "MATIC"), "MATIC");
+        assert_eq!(assets.canonical("kucoin", "WAXP"), "WAX");
+        assert_eq!(assets.canonical("binance", "WAXP"), "WAXP");
+        assert_eq!(assets.canonical("kucoin", "USDC.e"), "USDC");
+        assert_eq!(assets.canonical("binance", "USDC.e"), "USDC.E");
+    }
+
+    #[test]
+    fn normalize_keeps_native_symbols_and_merges_duplicates() {
+        let pairs = vec![
+            pair("XBT", "USDT", 5.0),
+            pair("BTC", "USDT", 9.0),
+            pair("USDT", "XBT", 1.0),
+            pair("USDT-ERC20", "USDT", 3.0),
+            pair("ETH", "XBT", 2.0),
+        ];
+        let out = registry().normalize("binance", pairs);
+
+        assert_eq!(out.len(), 2);
+        assert_eq!(
+            (out[0].base.as_str(), out[0].native_base.as_str()),
+            ("BTC", "BTC")
+        );
+        assert_eq!(out[0].liquidity, 9.0);
+        assert_eq!(
+            (out[1].quote.as_str(), out[1].native_quote.as_str()),
+            ("BTC", "XBT")
+        );
+    }
+}
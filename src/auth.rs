@@ -0,0 +1,282 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::config::ApiKeyConfig;
+use crate::error::ApiError;
+use crate::models::AppState;
+
+/// What an API key is allowed to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// Read stored scan results
+    Read,
+    /// Trigger scans (hits exchange APIs)
+    Scan,
+    /// Place or manage orders
+    Trade,
+    /// Operational endpoints (metadata refresh, kill switch, ...)
+    Admin,
+}
+
+/// Caller identity attached to authenticated requests
+#[derive(Debug, Clone)]
+pub struct Principal {
+    /// Key name from the config (never the token itself)
+    pub name: String,
+    pub client: String,
+}
+
+/// A resolved key: the token is read from the config or its env var at load time
+#[derive(Clone)]
+pub struct ApiKey {
+    pub name: String,
+    token: String,
+    pub scopes: Vec<Scope>,
+}
+
+impl std::fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiKey")
+            .field("name", &self.name)
+            .field("scopes", &self.scopes)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ApiKey {
+    pub fn resolve(cfg: &ApiKeyConfig) -> Result<Self, String> {
+        let token = match (&cfg.token, &cfg.token_env) {
+            (Some(t), None) => t.clone(),
+            (None, Some(var)) => std::env::var(var)
+                .map_err(|_| format!("auth key `{}`: env var {} is not set", cfg.name, var))?,
+            (Some(_), Some(_)) => {
+                return Err(format!(
+                    "auth key `{}`: set either token or token_env, not both",
+                    cfg.name
+                ))
+            }
+            (None, None) => {
+                return Err(format!(
+                    "auth key `{}`: token or token_env is required",
+                    cfg.name
+                ))
+            }
+        };
+        if token.len() < 16 {
+            return Err(format!(
+                "auth key `{}`: token must be at least 16 characters",
+                cfg.name
+            ));
+        }
+        Ok(Self {
+            name: cfg.name.clone(),
+            token,
+            scopes: cfg.scopes.clone(),
+        })
+    }
+}
+
+/// Constant-time comparison so response timing doesn't leak token prefixes
+fn token_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// `Authorization: Bearer <token>` or `X-API-Key: <token>`
+fn presented_token(req: &Request) -> Option<&str> {
+    let headers = req.headers();
+    if let Some(v) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+    {
+        if let Some(token) = v.strip_prefix("Bearer ") {
+            return Some(token.trim());
+        }
+    }
+    headers
+        .get("x-api-key")
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+}
+
+fn client_addr(req: &Request) -> String {
+    req.extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Route middleware: require a key holding `scope`, then write an audit line.
+/// With no keys configured auth is off and every caller is `anonymous`.
+pub async fn require_scope(
+    State((state, scope)): State<(Arc<Mutex<AppState>>, Scope)>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let client = client_addr(&req);
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+
+    let principal = {
+        let shared_state = state.lock().await;
+        let keys = &shared_state.api_keys;
+        if keys.is_empty() {
+            Principal {
+                name: "anonymous".to_string(),
+                client: client.clone(),
+            }
+        } else {
+            let Some(token) = presented_token(&req) else {
+                tracing::warn!(
+                    target: "audit",
+                    %client, %method, %path,
+                    "denied: missing credentials"
+                );
+                return ApiError::MissingCredentials.into_response();
+            };
+            let Some(key) = keys
+                .iter()
+                .find(|k| token_eq(k.token.as_bytes(), token.as_bytes()))
+            else {
+                tracing::warn!(target: "audit", %client, %method, %path, "denied: unknown key");
+                return ApiError::InvalidCredentials.into_response();
+            };
+            if !key.scopes.contains(&scope) {
+                tracing::warn!(
+                    target: "audit",
+                    key = %key.name, %client, %method, %path, ?scope,
+                    "denied: missing scope"
+                );
+                return ApiError::MissingScope.into_response();
+            }
+            Principal {
+                name: key.name.clone(),
+                client: client.clone(),
+            }
+        }
+    };
+
+    let key = principal.name.clone();
+    req.extensions_mut().insert(principal);
+    let resp = next.run(req).await;
+
+    tracing::info!(
+        target: "audit",
+        %key, %client, %method, %path, ?scope,
+        status = resp.status().as_u16(),
+        "request"
+    );
+    resp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::get, Extension, Router};
+    use reqwest::StatusCode;
+    use tokio::net::TcpListener;
+
+    use crate::config::Config;
+
+    const TRADER: &str = "trader-token-0123456789";
+    const READER: &str = "reader-token-0123456789";
+
+    /// Serve `/orders` behind the trade scope; returns its URL
+    async fn serve(keys: Vec<ApiKey>) -> String {
+        let mut state = AppState::new(Config::default()).unwrap();
+        state.api_keys = keys;
+        let state = Arc::new(Mutex::new(state));
+        let whoami = |Extension(principal): Extension<Principal>| async move { principal.name };
+        let app = Router::new().route(
+            "/orders",
+            get(whoami).route_layer(middleware::from_fn_with_state(
+                (state, Scope::Trade),
+                require_scope,
+            )),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/orders", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+        });
+        url
+    }
+
+    fn key(name: &str, token: &str, scopes: &[Scope]) -> ApiKey {
+        ApiKey {
+            name: name.to_string(),
+            token: token.to_string(),
+            scopes: scopes.to_vec(),
+        }
+    }
+
+    async fn call(url: &str, token: Option<&str>) -> (StatusCode, String) {
+        let mut request = reqwest::Client::new().get(url);
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await.unwrap();
+        (response.status(), response.text().await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn keys_need_the_route_scope() {
+        let url = serve(vec![
+            key("trader", TRADER, &[Scope::Read, Scope::Trade]),
+            key("reader", READER, &[Scope::Read]),
+        ])
+        .await;
+
+        let (status, body) = call(&url, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert!(body.contains("missing_credentials"), "{}", body);
+
+        let (status, body) = call(&url, Some("not-a-key-0123456789")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert!(body.contains("invalid_credentials"), "{}", body);
+
+        let (status, body) = call(&url, Some(READER)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert!(body.contains("missing_scope"), "{}", body);
+
+        assert_eq!(
+            call(&url, Some(TRADER)).await,
+            (StatusCode::OK, "trader".to_string())
+        );
+        let response = reqwest::Client::new()
+            .get(&url)
+            .header("X-API-Key", TRADER)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn without_keys_every_caller_is_anonymous() {
+        let url = serve(Vec::new()).await;
+        assert_eq!(
+            call(&url, None).await,
+            (StatusCode::OK, "anonymous".to_string())
+        );
+        assert_eq!(
+            call(&url, Some("anything-at-all-0123")).await,
+            (StatusCode::OK, "anonymous".to_string())
+        );
+    }
+}
@@ -0,0 +1,209 @@
+//! Spot-perpetual basis and funding scanner.
+//!
+//! Each linear perpetual is paired with the same venue's spot market and
+//! priced as two trades. Cash and carry buys spot and shorts the perp: it
+//! earns the perp's premium as the two converge and collects funding while
+//! the rate is positive. The reverse shorts spot and buys the perp, earning
+//! a discount and negative funding, but needs the spot asset borrowed.
+//! Funding is projected over a holding horizon at the current rate.
+
+use crate::models::{BasisResult, PairPrice, PerpQuote};
+use crate::utils::round4;
+use std::collections::HashMap;
+
+/// Inputs of one venue's basis scan
+#[derive(Debug, Clone, Copy)]
+pub struct BasisParams {
+    /// Minimum profit after fees (%) to report
+    pub min_profit: f64,
+    pub horizon_hours: f64,
+    /// Taker fee per spot trade (%)
+    pub spot_fee: f64,
+    /// Taker fee per perp trade (%)
+    pub perp_fee: f64,
+    pub allow_spot_short: bool,
+}
+
+/// Counters from one scanner pass, exported as metrics by the caller
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BasisStats {
+    /// Perps with a matching spot market
+    pub evaluated: usize,
+    /// Pairs above `min_profit` after fees
+    pub passing: usize,
+}
+
+/// One way of putting on the trade
+struct Leg {
+    direction: &'static str,
+    spot_price: f64,
+    /// Premium captured at convergence, in percent
+    basis_gain: f64,
+    expected_funding: f64,
+}
+
+pub fn scan_basis(
+    exchange: &str,
+    spot: &[PairPrice],
+    perps: &[PerpQuote],
+    params: &BasisParams,
+) -> (Vec<BasisResult>, BasisStats) {
+    let mut stats = BasisStats::default();
+    let markets: HashMap<(&str, &str), &PairPrice> = spot
+        .iter()
+        .filter(|p| p.is_spot && p.price > 0.0)
+        .map(|p| ((p.base.as_str(), p.quote.as_str()), p))
+        .collect();
+    // open and close on both sides
+    let trade_fees = 2.0 * params.spot_fee + 2.0 * params.perp_fee;
+
+    let mut results = Vec::new();
+    for perp in perps {
+        let Some(&market) = markets.get(&(perp.base.as_str(), perp.quote.as_str())) else {
+            continue;
+        };
+        if perp.mark_price <= 0.0 || perp.funding_interval_hours <= 0.0 {
+            continue;
+        }
+        stats.evaluated += 1;
+
+        let settlements = params.horizon_hours / perp.funding_interval_hours;
+        let funding = perp.funding_rate * 100.0 * settlements;
+        let buy = market.ask.unwrap_or(market.price);
+        let sell = market.bid.unwrap_or(market.price);
+        let mut legs = vec![Leg {
+            direction: "long_spot_short_perp",
+            spot_price: buy,
+            basis_gain: (perp.mark_price - buy) / buy * 100.0,
+            expected_funding: funding,
+        }];
+        if params.allow_spot_short {
+            legs.push(Leg {
+                direction: "short_spot_long_perp",
+                spot_price: sell,
+                basis_gain: (sell - perp.mark_price) / sell * 100.0,
+                expected_funding: -funding,
+            });
+        }
+        let Some(best) = legs.into_iter().max_by(|a, b| {
+            (a.basis_gain + a.expected_funding).total_cmp(&(b.basis_gain + b.expected_funding))
+        }) else {
+            continue;
+        };
+
+        let profit = best.basis_gain + best.expected_funding - trade_fees;
+        if !profit.is_finite() || profit < params.min_profit {
+            continue;
+        }
+        stats.passing += 1;
+        results.push(BasisResult {
+            exchange: exchange.to_string(),
+            spot_pair: format!("{}/{}", market.native_base, market.native_quote),
+            perp_symbol: perp.symbol.clone(),
+            direction: best.direction,
+            spot_price: best.spot_price,
+            mark_price: perp.mark_price,
+            index_price: perp.index_price,
+            basis: round4((perp.mark_price - best.spot_price) / best.spot_price * 100.0),
+            funding_rate: round4(perp.funding_rate * 100.0),
+            funding_annualized: round4(
+                perp.funding_rate * 100.0 * 24.0 / perp.funding_interval_hours * 365.0,
+            ),
+            next_funding_time: perp.next_funding_time,
+            expected_funding: round4(best.expected_funding),
+            trade_fees: round4(trade_fees),
+            profit_after_fees: round4(profit),
+            horizon_hours: params.horizon_hours,
+            min_liquidity: market.liquidity.min(perp.liquidity),
+        });
+    }
+
+    results.sort_by(|a, b| b.profit_after_fees.total_cmp(&a.profit_after_fees));
+    (results, stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spot(base: &str, price: f64) -> PairPrice {
+        PairPrice {
+            symbol: format!("{}USDT", base),
+            base: base.to_string(),
+            quote: "USDT".to_string(),
+            native_base: base.to_string(),
+            native_quote: "USDT".to_string(),
+            price,
+            bid: None,
+            ask: None,
+            is_spot: true,
+            liquidity: 5e8,
+        }
+    }
+
+    fn perp(base: &str, mark_price: f64, funding_rate: f64) -> PerpQuote {
+        PerpQuote {
+            base: base.to_string(),
+            quote: "USDT".to_string(),
+            symbol: format!("{}USDT", base),
+            mark_price,
+            index_price: None,
+            funding_rate,
+            next_funding_time: None,
+            funding_interval_hours: 8.0,
+            liquidity: 1e9,
+        }
+    }
+
+    fn params(allow_spot_short: bool) -> BasisParams {
+        BasisParams {
+            min_profit: 0.0,
+            horizon_hours: 24.0,
+            spot_fee: 0.1,
+            perp_fee: 0.05,
+            allow_spot_short,
+        }
+    }
+
+    #[test]
+    fn premium_and_positive_funding_pay_cash_and_carry() {
+        // 1% premium, 0.05% per 8h => 0.15% over a day, minus 0.3% fees
+        let (results, stats) = scan_basis(
+            "binance",
+            &[spot("BTC", 100_000.0)],
+            &[perp("BTC", 101_000.0, 0.0005)],
+            &params(false),
+        );
+        assert_eq!(stats.evaluated, 1);
+        let r = &results[0];
+        assert_eq!(r.direction, "long_spot_short_perp");
+        assert_eq!(r.basis, 1.0);
+        assert_eq!(r.expected_funding, 0.15);
+        assert_eq!(r.profit_after_fees, 0.85);
+        assert_eq!(r.funding_annualized, 54.75);
+    }
+
+    #[test]
+    fn discount_needs_spot_short_to_be_reported() {
+        let spot = [spot("ETH", 6_000.0)];
+        let perps = [perp("ETH", 5_940.0, -0.0002)];
+        let (results, _) = scan_basis("bybit", &spot, &perps, &params(false));
+        assert!(results.is_empty());
+
+        let (results, _) = scan_basis("bybit", &spot, &perps, &params(true));
+        assert_eq!(results[0].direction, "short_spot_long_perp");
+        assert!(results[0].profit_after_fees > 0.0);
+    }
+
+    #[test]
+    fn perps_without_a_spot_market_are_skipped() {
+        let (results, stats) = scan_basis(
+            "okx",
+            &[spot("BTC", 100_000.0)],
+            &[perp("1000PEPE", 0.01, 0.001)],
+            &params(true),
+        );
+        assert!(results.is_empty());
+        assert_eq!(stats.evaluated, 0);
+    }
+}
@@ -0,0 +1,1423 @@
+use clap::Parser;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::assets::DEFAULT_CHAIN_SUFFIXES;
+use crate::auth::{ApiKey, Scope};
+use crate::exchanges::{
+    canonical_exchange, perp_venue_fee, venue_fee, FetchSettings, GenericVenues, VenueSpec,
+    PERP_EXCHANGES, SUPPORTED_EXCHANGES,
+};
+use crate::execution::{
+    default_base_url, load_credentials, seal_file, Credentials, ExecutionMode, OrderType,
+    RecoveryPolicy, EXECUTION_EXCHANGES,
+};
+use crate::models::BasisRequest;
+
+/// Command line flags; every flag can also be set through the listed env var.
+/// Precedence: built-in defaults < config file < env var < flag.
+#[derive(Debug, Clone, Parser)]
+#[command(version, about = "Triangular arbitrage scanner")]
+pub struct Cli {
+    /// Path to a TOML config file
+    #[arg(long, short, env = "ARB_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// Full bind address, e.g. 127.0.0.1:8080
+    #[arg(long, env = "ARB_BIND")]
+    pub bind: Option<SocketAddr>,
+
+    /// Port to listen on (keeps the old `PORT` env var working)
+    #[arg(long, env = "PORT")]
+    pub port: Option<u16>,
+
+    /// Directory served at `/`
+    #[arg(long, env = "ARB_STATIC_DIR")]
+    pub static_dir: Option<PathBuf>,
+
+    /// Comma-separated list of exchanges that may be scanned
+    #[arg(long, env = "ARB_EXCHANGES", value_delimiter = ',')]
+    pub exchanges: Option<Vec<String>>,
+
+    /// Comma-separated exchanges that must be up for `/readyz`
+    #[arg(long, env = "REQUIRED_FEEDS", value_delimiter = ',')]
+    pub required_feeds: Option<Vec<String>>,
+
+    /// Minimum post-fee profit (%) used when a scan request omits it
+    #[arg(long, env = "ARB_MIN_PROFIT")]
+    pub min_profit: Option<f64>,
+
+    /// Comma-separated CORS origins (`*` allows any)
+    #[arg(long, env = "ARB_CORS_ORIGINS", value_delimiter = ',')]
+    pub cors_origins: Option<Vec<String>>,
+
+    /// Seconds between background scans (0 disables them)
+    #[arg(long, env = "ARB_SCAN_INTERVAL")]
+    pub scan_interval: Option<u64>,
+
+    /// Encrypt this plaintext JSON key file into `execution.credentials_file`
+    /// with the passphrase from `execution.credentials_passphrase_env`, then exit
+    #[arg(long, value_name = "PLAINTEXT")]
+    pub seal_credentials: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub exchanges: ExchangesConfig,
+    pub scan: ScanConfig,
+    pub http: HttpConfig,
+    pub auth: AuthConfig,
+    pub rate_limit: RateLimitConfig,
+    pub metadata: MetadataConfig,
+    pub assets: AssetsConfig,
+    pub generic: GenericConfig,
+    pub basis: BasisConfig,
+    pub paper: PaperConfig,
+    pub execution: ExecutionConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub bind: SocketAddr,
+    pub static_dir: PathBuf,
+    /// Origins allowed to call the API cross-origin; empty allows none,
+    /// `["*"]` allows any
+    pub cors_origins: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExchangesConfig {
+    pub enabled: Vec<String>,
+    /// Exchanges whose feeds gate `/readyz`
+    pub required: Vec<String>,
+    /// Fee per leg (%) for venues without an entry in `fees` or a known
+    /// published default
+    pub default_fee: f64,
+    /// Fee per leg (%) keyed by exchange
+    pub fees: HashMap<String, f64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScanConfig {
+    /// Minimum post-fee profit (%) when a request omits `min_profit`
+    pub default_min_profit: f64,
+    /// How long the ticker WebSockets (Binance, OKX, Kraken, Coinbase) are
+    /// sampled per scan
+    pub ws_window_secs: u64,
+    /// Seconds between background scans of the enabled exchanges (0 = off)
+    pub interval_secs: u64,
+    /// Pair snapshots younger than this are reused instead of refetched
+    pub cache_ttl_secs: u64,
+}
+
+/// Cached symbol listings (exchangeInfo, /symbols, ...)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetadataConfig {
+    /// Listings older than this are downloaded again
+    pub default_ttl_secs: u64,
+    /// TTL override keyed by exchange
+    pub ttl_secs: HashMap<String, u64>,
+    /// Keep listings on disk so a restart doesn't refetch them
+    pub persist: bool,
+    pub dir: PathBuf,
+}
+
+impl MetadataConfig {
+    pub fn cache_dir(&self) -> Option<PathBuf> {
+        self.persist.then(|| self.dir.clone())
+    }
+}
+
+/// Asset renames applied to fetched pairs (`XBT` -> `BTC`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AssetsConfig {
+    /// `native = canonical` for every exchange; overrides the built-ins
+    pub aliases: HashMap<String, String>,
+    /// Aliases for one exchange only, keyed by exchange
+    pub venues: HashMap<String, HashMap<String, String>>,
+    /// Chain markers stripped from tickers (`USDT-ERC20` -> `USDT`)
+    pub chain_suffixes: Vec<String>,
+}
+
+/// REST venues described in config rather than code (see `exchanges::generic`)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GenericConfig {
+    /// One venue description per file, `.toml` or `.json`; re-read on reload
+    pub files: Vec<PathBuf>,
+    pub venues: Vec<VenueSpec>,
+}
+
+/// Spot-perp basis and funding scanner (`POST /scan/basis`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BasisConfig {
+    /// Holding period funding is projected over when a request omits it
+    pub horizon_hours: f64,
+    /// Also report trades that short spot (needs the asset borrowed on margin)
+    pub allow_spot_short: bool,
+    /// Perp taker fee per trade (%) keyed by exchange; venues without an
+    /// entry use their published default
+    pub perp_fees: HashMap<String, f64>,
+}
+
+/// Paper trading of detected triangles (`/paper/*`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PaperConfig {
+    /// Simulate qualifying triangles from every scan
+    pub enabled: bool,
+    /// Post-fee profit (%) a triangle needs to be simulated
+    pub min_profit: f64,
+    /// Delay before each order meets the book, standing in for the round
+    /// trip a live order would take
+    pub latency_ms: u64,
+    /// Share of the starting asset's balance committed per triangle
+    pub trade_fraction: f64,
+    /// Trades kept for `/paper/trades`; older ones are dropped
+    pub max_trades: usize,
+    /// Starting balance per canonical asset, seeded on every exchange the
+    /// first time it is traded
+    pub initial_balances: HashMap<String, f64>,
+}
+
+/// Live order execution (`POST /execute`, `GET /executions`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExecutionConfig {
+    /// Accept `POST /execute`; off, the endpoint refuses every triangle
+    pub enabled: bool,
+    /// Also execute qualifying triangles from every scan
+    pub auto: bool,
+    /// Post-fee profit (%) a scanned triangle needs to be auto-executed
+    pub min_profit: f64,
+    /// `sequential` chains the legs; `inventory` fires all three at once
+    /// from balances already held
+    pub mode: ExecutionMode,
+    pub order_type: OrderType,
+    /// How far past the scanned price an IOC order may fill, in basis points
+    pub ioc_slippage_bps: f64,
+    /// Amount committed per triangle, keyed by canonical asset; a triangle
+    /// starts at the first of its assets listed here
+    pub start_amounts: HashMap<String, f64>,
+    /// Executions kept for `/executions`; older ones are dropped
+    pub max_executions: usize,
+    /// How long a signed request stays valid (Binance, Bybit)
+    pub recv_window_ms: u64,
+    /// Seconds between account balance fetches on venues with trading keys;
+    /// 0 only refreshes them after executions
+    pub balance_refresh_secs: u64,
+    /// REST endpoint override keyed by exchange, e.g. a testnet
+    pub base_urls: HashMap<String, String>,
+    /// Env vars holding trading keys, keyed by exchange; they override keys
+    /// of the same account in `credentials_file`
+    pub credentials: HashMap<String, CredentialsConfig>,
+    /// Trading keys sealed with `--seal-credentials`
+    pub credentials_file: Option<PathBuf>,
+    /// Env var holding the passphrase `credentials_file` is sealed with
+    pub credentials_passphrase_env: String,
+    /// Account traded per exchange, by subaccount name; `main` when unlisted.
+    /// Venues without a key for their account can't be traded.
+    pub accounts: HashMap<String, String>,
+    /// Only trade with keys their venue reports as able to trade and unable
+    /// to withdraw; turn off for testnets without the key info endpoint
+    pub verify_keys: bool,
+    /// Venues whose keys may trade although the venue doesn't report their
+    /// permissions (Gate); their keys are refused unless listed
+    pub unverifiable_keys: Vec<String>,
+    pub recovery: RecoveryConfig,
+    pub risk: RiskConfig,
+}
+
+/// What happens when a leg doesn't fill completely
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RecoveryConfig {
+    pub policy: RecoveryPolicy,
+    /// Orders resent for a leg's remainder under `retry`
+    pub retries: u32,
+    /// Each retry's limit price moves this much further past the scanned
+    /// price, in basis points
+    pub retry_step_bps: f64,
+    /// Policy for what is still stranded once the retries run out
+    pub fallback: RecoveryPolicy,
+}
+
+/// Guardrails checked before every triangle; amounts are keyed by canonical
+/// asset, and assets without an entry are not limited
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RiskConfig {
+    /// Most of its start asset one triangle may commit; larger start
+    /// amounts are scaled down
+    pub max_notional: HashMap<String, f64>,
+    /// Triangles executing at once across all venues
+    pub max_open_attempts: usize,
+    /// Realized loss in a start asset after which nothing is executed until
+    /// the next UTC day
+    pub max_daily_loss: HashMap<String, f64>,
+    /// Most of an asset the triangles in flight may trade at once, counting
+    /// leftovers held by recovery until they are released
+    pub max_exposure: HashMap<String, f64>,
+    /// Where the kill switch, today's PnL and held leftovers are saved; read
+    /// at startup only
+    pub state_file: PathBuf,
+}
+
+impl ExecutionConfig {
+    pub fn base_url(&self, exchange: &str) -> &str {
+        self.base_urls
+            .get(exchange)
+            .map(String::as_str)
+            .unwrap_or_else(|| default_base_url(exchange))
+    }
+}
+
+/// Env vars holding a venue's trading keys; secrets never go in the file
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CredentialsConfig {
+    /// Main account key; set with `secret_env`
+    pub api_key_env: Option<String>,
+    pub secret_env: Option<String>,
+    /// KuCoin's API passphrase
+    pub passphrase_env: Option<String>,
+    /// Subaccount keys keyed by subaccount name
+    pub subaccounts: HashMap<String, KeyEnvConfig>,
+}
+
+/// Env vars holding one subaccount's trading key
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KeyEnvConfig {
+    pub api_key_env: String,
+    pub secret_env: String,
+    pub passphrase_env: Option<String>,
+}
+
+/// Per-client limits on `POST /scan`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// Sustained scans per minute per client (0 disables limiting)
+    pub scan_per_minute: u32,
+    /// Scans a client may fire back-to-back before being throttled
+    pub scan_burst: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpConfig {
+    pub connect_timeout_secs: u64,
+    /// Whole request, including reading the body
+    pub request_timeout_secs: u64,
+    /// Idle pooled connections are closed after this long
+    pub pool_idle_timeout_secs: u64,
+    pub tcp_keepalive_secs: u64,
+    /// Retries for timeouts, 5xx and rate limits (0 disables retrying)
+    pub max_retries: u32,
+    /// First backoff delay; doubles each retry, half of it jittered
+    pub backoff_base_ms: u64,
+    /// Backoff cap; a longer `Retry-After` fails the request instead
+    pub backoff_max_ms: u64,
+    /// Percent of each venue's rate limit kept in reserve
+    pub budget_headroom_percent: u32,
+    /// Longest a request waits for the venue's rate-limit window to reset
+    pub budget_max_wait_secs: u64,
+    /// Extra PEM root certificates trusted for every exchange
+    pub ca_bundle: Option<PathBuf>,
+    /// Per-exchange transport overrides, keyed by exchange name
+    pub venues: HashMap<String, VenueHttpConfig>,
+}
+
+/// Transport settings for a single exchange
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VenueHttpConfig {
+    /// `http://`, `https://`, `socks5://` or `socks5h://` proxy URL
+    pub proxy: Option<String>,
+    /// PEM root certificates trusted for this exchange only
+    pub ca_bundle: Option<PathBuf>,
+}
+
+// proxy URLs may carry credentials
+impl std::fmt::Debug for VenueHttpConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VenueHttpConfig")
+            .field("proxy", &self.proxy.as_ref().map(|_| "<redacted>"))
+            .field("ca_bundle", &self.ca_bundle)
+            .finish()
+    }
+}
+
+/// API keys; with none configured the API is open (a warning is logged)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    pub keys: Vec<ApiKeyConfig>,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKeyConfig {
+    /// Shown in audit logs instead of the token
+    pub name: String,
+    pub token: Option<String>,
+    /// Read the token from this env var instead of the file
+    pub token_env: Option<String>,
+    pub scopes: Vec<Scope>,
+}
+
+// manual impl so the token can never end up in logs via `{:?}`
+impl std::fmt::Debug for ApiKeyConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiKeyConfig")
+            .field("name", &self.name)
+            .field("token", &self.token.as_ref().map(|_| "<redacted>"))
+            .field("token_env", &self.token_env)
+            .field("scopes", &self.scopes)
+            .finish()
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind: SocketAddr::from(([0, 0, 0, 0], 8080)),
+            static_dir: PathBuf::from("static"),
+            cors_origins: Vec::new(),
+        }
+    }
+}
+
+impl Default for ExchangesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: SUPPORTED_EXCHANGES.iter().map(|s| s.to_string()).collect(),
+            required: Vec::new(),
+            default_fee: 0.10,
+            fees: HashMap::new(),
+        }
+    }
+}
+
+impl Default for ScanConfig {
+    fn default() -> Self {
+        Self {
+            default_min_profit: 0.0,
+            ws_window_secs: 20,
+            interval_secs: 0,
+            cache_ttl_secs: 15,
+        }
+    }
+}
+
+impl Default for MetadataConfig {
+    fn default() -> Self {
+        Self {
+            default_ttl_secs: 3600,
+            ttl_secs: HashMap::new(),
+            persist: true,
+            dir: PathBuf::from("cache/metadata"),
+        }
+    }
+}
+
+impl Default for AssetsConfig {
+    fn default() -> Self {
+        Self {
+            aliases: HashMap::new(),
+            venues: HashMap::new(),
+            chain_suffixes: DEFAULT_CHAIN_SUFFIXES
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl Default for BasisConfig {
+    fn default() -> Self {
+        Self {
+            horizon_hours: 24.0,
+            allow_spot_short: false,
+            perp_fees: HashMap::new(),
+        }
+    }
+}
+
+impl Default for PaperConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_profit: 0.1,
+            latency_ms: 150,
+            trade_fraction: 0.1,
+            max_trades: 500,
+            initial_balances: [
+                ("USDT", 10_000.0),
+                ("USD", 10_000.0),
+                ("BTC", 0.1),
+                ("ETH", 2.0),
+            ]
+            .into_iter()
+            .map(|(asset, amount)| (asset.to_string(), amount))
+            .collect(),
+        }
+    }
+}
+
+impl Default for ExecutionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            auto: false,
+            min_profit: 0.2,
+            mode: ExecutionMode::Sequential,
+            order_type: OrderType::Ioc,
+            ioc_slippage_bps: 5.0,
+            start_amounts: HashMap::new(),
+            max_executions: 500,
+            recv_window_ms: 5_000,
+            balance_refresh_secs: 30,
+            base_urls: HashMap::new(),
+            credentials: HashMap::new(),
+            credentials_file: None,
+            credentials_passphrase_env: "ARB_CREDENTIALS_PASSPHRASE".to_string(),
+            accounts: HashMap::new(),
+            verify_keys: true,
+            unverifiable_keys: Vec::new(),
+            recovery: RecoveryConfig::default(),
+            risk: RiskConfig::default(),
+        }
+    }
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            max_notional: HashMap::new(),
+            max_open_attempts: 4,
+            max_daily_loss: HashMap::new(),
+            max_exposure: HashMap::new(),
+            state_file: PathBuf::from("cache/kill-switch.json"),
+        }
+    }
+}
+
+impl Default for RecoveryConfig {
+    fn default() -> Self {
+        Self {
+            policy: RecoveryPolicy::Unwind,
+            retries: 2,
+            retry_step_bps: 10.0,
+            fallback: RecoveryPolicy::Unwind,
+        }
+    }
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            scan_per_minute: 6,
+            scan_burst: 3,
+        }
+    }
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_secs: 5,
+            request_timeout_secs: 30,
+            pool_idle_timeout_secs: 90,
+            tcp_keepalive_secs: 60,
+            max_retries: 2,
+            backoff_base_ms: 250,
+            backoff_max_ms: 5_000,
+            budget_headroom_percent: 10,
+            budget_max_wait_secs: 30,
+            ca_bundle: None,
+            venues: HashMap::new(),
+        }
+    }
+}
+
+/// Startup / reload failure, printed as-is to the operator
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Venue(PathBuf, String),
+    Invalid(Vec<String>),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, e) => write!(f, "cannot read {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "invalid TOML in {}: {}", path.display(), e),
+            ConfigError::Venue(path, e) => {
+                write!(f, "invalid venue description in {}: {}", path.display(), e)
+            }
+            ConfigError::Invalid(problems) => {
+                writeln!(f, "invalid configuration:")?;
+                for p in problems {
+                    writeln!(f, "  - {}", p)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Load defaults, then the config file, then env/CLI overrides, and validate.
+    pub fn load(cli: &Cli) -> Result<Self, ConfigError> {
+        let mut cfg = match &cli.config {
+            Some(path) => Self::from_file(path)?,
+            None => Self::default(),
+        };
+        cfg.load_generic_files()?;
+        cfg.apply_overrides(cli);
+        cfg.normalize();
+        cfg.validate()?;
+        Ok(cfg)
+    }
+
+    /// `--seal-credentials`: encrypt the plaintext key file `plain` into
+    /// `execution.credentials_file`. The config isn't validated, as that
+    /// would need the sealed file.
+    pub fn seal_credentials(cli: &Cli, plain: &Path) -> Result<String, String> {
+        let mut cfg = match &cli.config {
+            Some(path) => Self::from_file(path).map_err(|e| e.to_string())?,
+            None => Self::default(),
+        };
+        cfg.apply_overrides(cli);
+        seal_file(plain, &cfg.execution)
+    }
+
+    fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let raw =
+            std::fs::read_to_string(path).map_err(|e| ConfigError::Read(path.to_path_buf(), e))?;
+        toml::from_str(&raw).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))
+    }
+
+    /// Append the venues described in `generic.files`
+    fn load_generic_files(&mut self) -> Result<(), ConfigError> {
+        for path in &self.generic.files {
+            let raw = std::fs::read_to_string(path)
+                .map_err(|e| ConfigError::Read(path.to_path_buf(), e))?;
+            let spec = match path.extension().and_then(|e| e.to_str()) {
+                Some("json") => serde_json::from_str(&raw).map_err(|e| e.to_string()),
+                _ => toml::from_str(&raw).map_err(|e| e.to_string()),
+            }
+            .map_err(|e| ConfigError::Venue(path.to_path_buf(), e))?;
+            self.generic.venues.push(spec);
+        }
+        Ok(())
+    }
+
+    fn apply_overrides(&mut self, cli: &Cli) {
+        if let Some(bind) = cli.bind {
+            self.server.bind = bind;
+        }
+        if let Some(port) = cli.port {
+            self.server.bind.set_port(port);
+        }
+        if let Some(dir) = &cli.static_dir {
+            self.server.static_dir = dir.clone();
+        }
+        if let Some(list) = &cli.exchanges {
+            self.exchanges.enabled = list.clone();
+        }
+        if let Some(list) = &cli.required_feeds {
+            self.exchanges.required = list.clone();
+        }
+        if let Some(min_profit) = cli.min_profit {
+            self.scan.default_min_profit = min_profit;
+        }
+        if let Some(origins) = &cli.cors_origins {
+            self.server.cors_origins = origins.clone();
+        }
+        if let Some(secs) = cli.scan_interval {
+            self.scan.interval_secs = secs;
+        }
+    }
+
+    /// Trim list entries and map keys, map exchange aliases (`gate`) to
+    /// canonical names and upper-case asset keys.
+    fn normalize(&mut self) {
+        for spec in &mut self.generic.venues {
+            spec.name = spec.name.trim().to_lowercase();
+        }
+        fn exchange(name: &str) -> String {
+            let trimmed = name.trim();
+            canonical_exchange(trimmed)
+                .map(str::to_string)
+                .unwrap_or_else(|| trimmed.to_lowercase())
+        }
+        fn canon(list: &mut Vec<String>) {
+            for ex in list.iter_mut() {
+                *ex = exchange(ex);
+            }
+            let mut seen = HashSet::new();
+            list.retain(|ex| !ex.is_empty() && seen.insert(ex.clone()));
+        }
+        fn canon_keys<V>(map: &mut HashMap<String, V>) {
+            *map = map.drain().map(|(ex, v)| (exchange(&ex), v)).collect();
+        }
+        fn canon_assets<V>(map: &mut HashMap<String, V>) {
+            *map = map
+                .drain()
+                .map(|(asset, v)| (asset.trim().to_uppercase(), v))
+                .collect();
+        }
+        canon(&mut self.exchanges.enabled);
+        canon(&mut self.exchanges.required);
+        canon(&mut self.execution.unverifiable_keys);
+        canon_keys(&mut self.exchanges.fees);
+        canon_keys(&mut self.metadata.ttl_secs);
+        canon_keys(&mut self.basis.perp_fees);
+        canon_keys(&mut self.assets.venues);
+        canon_keys(&mut self.http.venues);
+        canon_keys(&mut self.execution.base_urls);
+        canon_keys(&mut self.execution.credentials);
+        canon_keys(&mut self.execution.accounts);
+        canon_assets(&mut self.paper.initial_balances);
+        canon_assets(&mut self.execution.start_amounts);
+        let risk = &mut self.execution.risk;
+        canon_assets(&mut risk.max_notional);
+        canon_assets(&mut risk.max_daily_loss);
+        canon_assets(&mut risk.max_exposure);
+        self.server.cors_origins = self
+            .server
+            .cors_origins
+            .iter()
+            .map(|o| o.trim().trim_end_matches('/').to_string())
+            .filter(|o| !o.is_empty())
+            .collect();
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+        let names = self.exchange_names();
+        let known = |ex: &str| names.iter().any(|n| n == ex);
+
+        let mut generic_names = HashSet::new();
+        for spec in &self.generic.venues {
+            let name = &spec.name;
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                problems.push(format!(
+                    "generic venue `{}`: name must be letters, digits, `-` or `_`",
+                    name
+                ));
+            }
+            if canonical_exchange(name).is_some() {
+                problems.push(format!(
+                    "generic venue `{}`: name is taken by a built-in exchange",
+                    name
+                ));
+            }
+            if !generic_names.insert(name) {
+                problems.push(format!("generic venue `{}`: described twice", name));
+            }
+            problems.extend(spec.problems());
+        }
+
+        if self.exchanges.enabled.is_empty() {
+            problems.push("exchanges.enabled must list at least one exchange".to_string());
+        }
+        for ex in &self.exchanges.enabled {
+            if !known(ex) {
+                problems.push(format!(
+                    "exchanges.enabled: unknown exchange `{}` (supported: {})",
+                    ex,
+                    names.join(", ")
+                ));
+            }
+        }
+        for ex in &self.exchanges.required {
+            if !self.exchanges.enabled.contains(ex) {
+                problems.push(format!(
+                    "exchanges.required: `{}` is not in exchanges.enabled",
+                    ex
+                ));
+            }
+        }
+        let fee_ok = |fee: f64| fee.is_finite() && (0.0..5.0).contains(&fee);
+        if !fee_ok(self.exchanges.default_fee) {
+            problems.push(format!(
+                "exchanges.default_fee must be in [0, 5) percent, got {}",
+                self.exchanges.default_fee
+            ));
+        }
+        for (ex, fee) in &self.exchanges.fees {
+            if !known(ex) {
+                problems.push(format!("exchanges.fees: unknown exchange `{}`", ex));
+            }
+            if !fee_ok(*fee) {
+                problems.push(format!(
+                    "exchanges.fees.{} must be in [0, 5) percent, got {}",
+                    ex, fee
+                ));
+            }
+        }
+        let (min, max) = BasisRequest::HORIZON_RANGE;
+        if !(min..=max).contains(&self.basis.horizon_hours) {
+            problems.push(format!(
+                "basis.horizon_hours must be between {} and {}, got {}",
+                min, max, self.basis.horizon_hours
+            ));
+        }
+        for (ex, fee) in &self.basis.perp_fees {
+            if !PERP_EXCHANGES.contains(&ex.as_str()) {
+                problems.push(format!(
+                    "basis.perp_fees: `{}` has no perpetuals (supported: {})",
+                    ex,
+                    PERP_EXCHANGES.join(", ")
+                ));
+            }
+            if !fee_ok(*fee) {
+                problems.push(format!(
+                    "basis.perp_fees.{} must be in [0, 5) percent, got {}",
+                    ex, fee
+                ));
+            }
+        }
+        if !self.paper.min_profit.is_finite() {
+            problems.push("paper.min_profit must be a finite number".to_string());
+        }
+        if self.paper.latency_ms > 10_000 {
+            problems.push(format!(
+                "paper.latency_ms must be at most 10000, got {}",
+                self.paper.latency_ms
+            ));
+        }
+        if !(self.paper.trade_fraction > 0.0 && self.paper.trade_fraction <= 1.0) {
+            problems.push(format!(
+                "paper.trade_fraction must be in (0, 1], got {}",
+                self.paper.trade_fraction
+            ));
+        }
+        if !(1..=100_000).contains(&self.paper.max_trades) {
+            problems.push(format!(
+                "paper.max_trades must be between 1 and 100000, got {}",
+                self.paper.max_trades
+            ));
+        }
+        for (asset, amount) in &self.paper.initial_balances {
+            if !amount.is_finite() || *amount < 0.0 {
+                problems.push(format!(
+                    "paper.initial_balances.{} must be a non-negative number, got {}",
+                    asset, amount
+                ));
+            }
+        }
+        if !self.execution.min_profit.is_finite() {
+            problems.push("execution.min_profit must be a finite number".to_string());
+        }
+        if !(0.0..=500.0).contains(&self.execution.ioc_slippage_bps) {
+            problems.push(format!(
+                "execution.ioc_slippage_bps must be in [0, 500], got {}",
+                self.execution.ioc_slippage_bps
+            ));
+        }
+        for (asset, amount) in &self.execution.start_amounts {
+            if !(amount.is_finite() && *amount > 0.0) {
+                problems.push(format!(
+                    "execution.start_amounts.{} must be a positive number, got {}",
+                    asset, amount
+                ));
+            }
+        }
+        if !(1..=100_000).contains(&self.execution.max_executions) {
+            problems.push(format!(
+                "execution.max_executions must be between 1 and 100000, got {}",
+                self.execution.max_executions
+            ));
+        }
+        if !(1..=60_000).contains(&self.execution.recv_window_ms) {
+            problems.push(format!(
+                "execution.recv_window_ms must be between 1 and 60000, got {}",
+                self.execution.recv_window_ms
+            ));
+        }
+        if self.execution.balance_refresh_secs > 86_400 {
+            problems.push(format!(
+                "execution.balance_refresh_secs must be at most 86400, got {}",
+                self.execution.balance_refresh_secs
+            ));
+        }
+        let recovery = &self.execution.recovery;
+        if recovery.retries > 10 {
+            problems.push(format!(
+                "execution.recovery.retries must be at most 10, got {}",
+                recovery.retries
+            ));
+        }
+        if !(0.0..=500.0).contains(&recovery.retry_step_bps) {
+            problems.push(format!(
+                "execution.recovery.retry_step_bps must be in [0, 500], got {}",
+                recovery.retry_step_bps
+            ));
+        }
+        if recovery.fallback == RecoveryPolicy::Retry {
+            problems.push("execution.recovery.fallback must be \"unwind\" or \"hold\"".to_string());
+        }
+        let risk = &self.execution.risk;
+        for (field, limits) in [
+            ("max_notional", &risk.max_notional),
+            ("max_daily_loss", &risk.max_daily_loss),
+            ("max_exposure", &risk.max_exposure),
+        ] {
+            for (asset, amount) in limits {
+                if !(amount.is_finite() && *amount > 0.0) {
+                    problems.push(format!(
+                        "execution.risk.{}.{} must be a positive number, got {}",
+                        field, asset, amount
+                    ));
+                }
+            }
+        }
+        if !(1..=100).contains(&risk.max_open_attempts) {
+            problems.push(format!(
+                "execution.risk.max_open_attempts must be between 1 and 100, got {}",
+                risk.max_open_attempts
+            ));
+        }
+        if risk.state_file.as_os_str().is_empty() {
+            problems.push("execution.risk.state_file must not be empty".to_string());
+        }
+        for (ex, url) in &self.execution.base_urls {
+            if !EXECUTION_EXCHANGES.contains(&ex.as_str()) {
+                problems.push(format!(
+                    "execution.base_urls: orders can't be sent to `{}` (supported: {})",
+                    ex,
+                    EXECUTION_EXCHANGES.join(", ")
+                ));
+            }
+            if url::Url::parse(url).is_err() {
+                problems.push(format!(
+                    "execution.base_urls.{}: `{}` is not a URL",
+                    ex, url
+                ));
+            }
+        }
+        for (section, names) in [
+            (
+                "credentials",
+                self.execution.credentials.keys().collect::<Vec<_>>(),
+            ),
+            ("accounts", self.execution.accounts.keys().collect()),
+            (
+                "unverifiable_keys",
+                self.execution.unverifiable_keys.iter().collect(),
+            ),
+        ] {
+            for ex in names {
+                if !EXECUTION_EXCHANGES.contains(&ex.as_str()) {
+                    problems.push(format!(
+                        "execution.{}: orders can't be sent to `{}` (supported: {})",
+                        section,
+                        ex,
+                        EXECUTION_EXCHANGES.join(", ")
+                    ));
+                }
+            }
+        }
+        if self.execution.enabled {
+            if let Err(e) = load_credentials(&self.execution) {
+                problems.extend(e);
+            }
+            // without keys every caller is anonymous with every scope
+            if !self
+                .auth
+                .keys
+                .iter()
+                .any(|k| k.scopes.contains(&Scope::Trade))
+            {
+                problems.push(
+                    "execution.enabled needs an auth.keys entry with the `trade` scope".to_string(),
+                );
+            }
+        }
+        if !self.scan.default_min_profit.is_finite() {
+            problems.push("scan.default_min_profit must be a finite number".to_string());
+        }
+        if !(1..=300).contains(&self.scan.ws_window_secs) {
+            problems.push(format!(
+                "scan.ws_window_secs must be between 1 and 300, got {}",
+                self.scan.ws_window_secs
+            ));
+        }
+        if self.http.connect_timeout_secs == 0 || self.http.request_timeout_secs == 0 {
+            problems.push("http timeouts must be greater than zero".to_string());
+        }
+        if self.http.max_retries > 10 {
+            problems.push(format!(
+                "http.max_retries must be at most 10, got {}",
+                self.http.max_retries
+            ));
+        }
+        if self.metadata.default_ttl_secs == 0 {
+            problems.push("metadata.default_ttl_secs must be greater than zero".to_string());
+        }
+        for (ex, ttl) in &self.metadata.ttl_secs {
+            if !known(ex) {
+                problems.push(format!("metadata.ttl_secs: unknown exchange `{}`", ex));
+            }
+            if *ttl == 0 {
+                problems.push(format!(
+                    "metadata.ttl_secs.{} must be greater than zero",
+                    ex
+                ));
+            }
+        }
+        let asset_ok =
+            |s: &str| !s.trim().is_empty() && s.trim().chars().all(|c| c.is_ascii_alphanumeric());
+        let mut aliases: Vec<(String, &HashMap<String, String>)> =
+            vec![("assets.aliases".to_string(), &self.assets.aliases)];
+        for (ex, map) in &self.assets.venues {
+            if !known(ex) {
+                problems.push(format!("assets.venues: unknown exchange `{}`", ex));
+            }
+            aliases.push((format!("assets.venues.{}", ex), map));
+        }
+        for (section, map) in aliases {
+            for (native, canonical) in map {
+                if native.trim().is_empty() || !asset_ok(canonical) {
+                    problems.push(format!(
+                        "{}: `{}` = `{}` must map a symbol to an alphanumeric name",
+                        section, native, canonical
+                    ));
+                }
+            }
+        }
+
+        if let Some(path) = &self.http.ca_bundle {
+            if let Err(e) = load_ca_bundle(path) {
+                problems.push(format!("http.ca_bundle: {}", e));
+            }
+        }
+        for (ex, venue) in &self.http.venues {
+            if !known(ex) {
+                problems.push(format!("http.venues: unknown exchange `{}`", ex));
+            }
+            if let Some(proxy) = &venue.proxy {
+                if let Err(e) = check_proxy_url(proxy) {
+                    problems.push(format!("http.venues.{}.proxy: {}", ex, e));
+                }
+            }
+            if let Some(path) = &venue.ca_bundle {
+                if let Err(e) = load_ca_bundle(path) {
+                    problems.push(format!("http.venues.{}.ca_bundle: {}", ex, e));
+                }
+            }
+        }
+        if self.http.budget_headroom_percent >= 100 {
+            problems.push(format!(
+                "http.budget_headroom_percent must be below 100, got {}",
+                self.http.budget_headroom_percent
+            ));
+        }
+        if self.http.backoff_base_ms == 0 || self.http.backoff_base_ms > self.http.backoff_max_ms {
+            problems.push(format!(
+                "http.backoff_base_ms must be greater than zero and at most \
+                 http.backoff_max_ms ({}), got {}",
+                self.http.backoff_max_ms, self.http.backoff_base_ms
+            ));
+        }
+        if !self.server.static_dir.is_dir() {
+            problems.push(format!(
+                "server.static_dir `{}` is not a directory",
+                self.server.static_dir.display()
+            ));
+        }
+        let mut key_names = HashSet::new();
+        for key in &self.auth.keys {
+            if !key_names.insert(&key.name) {
+                problems.push(format!("auth.keys: duplicate key name `{}`", key.name));
+            }
+            if key.scopes.is_empty() {
+                problems.push(format!("auth.keys: key `{}` has no scopes", key.name));
+            }
+            if let Err(e) = ApiKey::resolve(key) {
+                problems.push(e);
+            }
+        }
+        for origin in &self.server.cors_origins {
+            if origin != "*" && url::Url::parse(origin).is_err() {
+                problems.push(format!(
+                    "server.cors_origins: `{}` is not a valid origin URL",
+                    origin
+                ));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(problems))
+        }
+    }
+
+    /// Fee per leg (%) for an exchange: configured, else the venue's
+    /// published default or its description's `fee`, else
+    /// `exchanges.default_fee`
+    pub fn fee_for(&self, exchange: &str) -> f64 {
+        self.exchanges
+            .fees
+            .get(exchange)
+            .copied()
+            .or_else(|| venue_fee(exchange))
+            .or_else(|| self.generic_venue(exchange).and_then(|v| v.fee))
+            .unwrap_or(self.exchanges.default_fee)
+    }
+
+    /// Perp taker fee per trade (%): configured, else the venue's published
+    /// default, else `exchanges.default_fee`
+    pub fn perp_fee_for(&self, exchange: &str) -> f64 {
+        self.basis
+            .perp_fees
+            .get(exchange)
+            .copied()
+            .or_else(|| perp_venue_fee(exchange))
+            .unwrap_or(self.exchanges.default_fee)
+    }
+
+    /// Built-in exchanges followed by the generic venues
+    pub fn exchange_names(&self) -> Vec<String> {
+        SUPPORTED_EXCHANGES
+            .iter()
+            .map(|ex| ex.to_string())
+            .chain(self.generic.venues.iter().map(|v| v.name.clone()))
+            .collect()
+    }
+
+    /// Canonical name of a built-in exchange, alias or generic venue
+    pub fn canonical_exchange(&self, name: &str) -> Option<String> {
+        if let Some(ex) = canonical_exchange(name) {
+            return Some(ex.to_string());
+        }
+        let name = name.to_lowercase();
+        self.generic_venue(&name).map(|v| v.name.clone())
+    }
+
+    fn generic_venue(&self, name: &str) -> Option<&VenueSpec> {
+        self.generic.venues.iter().find(|v| v.name == name)
+    }
+
+    /// Generic venue descriptions keyed by name, for the fetch dispatcher
+    pub fn generic_venues(&self) -> GenericVenues {
+        self.generic
+            .venues
+            .iter()
+            .map(|v| (v.name.clone(), v.clone()))
+            .collect()
+    }
+
+    /// Keys of the traded accounts from the sealed file and env vars, keyed
+    /// by exchange; empty while execution is disabled. Only call after
+    /// `load` validated them.
+    pub fn credentials(&self) -> HashMap<String, Credentials> {
+        if !self.execution.enabled {
+            return HashMap::new();
+        }
+        load_credentials(&self.execution).unwrap_or_default()
+    }
+
+    /// Keys with their tokens resolved; only call after `load` validated them
+    pub fn api_keys(&self) -> Vec<ApiKey> {
+        self.auth
+            .keys
+            .iter()
+            .filter_map(|k| ApiKey::resolve(k).ok())
+            .collect()
+    }
+
+    pub fn cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.scan.cache_ttl_secs)
+    }
+
+    /// Symbol listing TTL for an exchange
+    pub fn metadata_ttl(&self, exchange: &str) -> Duration {
+        let secs = self
+            .metadata
+            .ttl_secs
+            .get(exchange)
+            .copied()
+            .unwrap_or(self.metadata.default_ttl_secs);
+        Duration::from_secs(secs)
+    }
+
+    /// Per-fetch knobs handed to the exchange dispatcher
+    pub fn fetch_settings(&self, exchange: &str) -> FetchSettings {
+        FetchSettings {
+            ws_window: Duration::from_secs(self.scan.ws_window_secs),
+            metadata_ttl: self.metadata_ttl(exchange),
+        }
+    }
+
+    /// Copy the settings that can change without a restart, API keys
+    /// included. Bind address, static dir, CORS origins and the metadata
+    /// cache location are wired in at startup and are kept from `self`.
+    pub fn reload_from(&mut self, fresh: Config) {
+        for (what, changed) in [
+            ("server.bind", self.server.bind != fresh.server.bind),
+            (
+                "server.static_dir",
+                self.server.static_dir != fresh.server.static_dir,
+            ),
+            (
+                "server.cors_origins",
+                self.server.cors_origins != fresh.server.cors_origins,
+            ),
+            (
+                "metadata.persist",
+                self.metadata.persist != fresh.metadata.persist,
+            ),
+            ("metadata.dir", self.metadata.dir != fresh.metadata.dir),
+            (
+                "execution.risk.state_file",
+                self.execution.risk.state_file != fresh.execution.risk.state_file,
+            ),
+        ] {
+            if changed {
+                tracing::warn!("config reload: {} changed, restart required to apply", what);
+            }
+        }
+        self.exchanges = fresh.exchanges;
+        self.scan = fresh.scan;
+        self.http = fresh.http;
+        self.auth = fresh.auth;
+        self.rate_limit = fresh.rate_limit;
+        self.metadata.default_ttl_secs = fresh.metadata.default_ttl_secs;
+        self.metadata.ttl_secs = fresh.metadata.ttl_secs;
+        self.assets = fresh.assets;
+        self.generic = fresh.generic;
+        self.basis = fresh.basis;
+        self.paper = fresh.paper;
+        self.execution = fresh.execution;
+    }
+}
+
+/// Read a PEM file holding one or more root certificates
+pub fn load_ca_bundle(path: &Path) -> Result<Vec<reqwest::Certificate>, String> {
+    let pem = std::fs::read(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    let certs = reqwest::Certificate::from_pem_bundle(&pem)
+        .map_err(|e| format!("{} is not a PEM bundle: {}", path.display(), e))?;
+    if certs.is_empty() {
+        return Err(format!("{} contains no certificates", path.display()));
+    }
+    Ok(certs)
+}
+
+/// Proxies the HTTP client can tunnel through; credentials may be embedded
+fn check_proxy_url(raw: &str) -> Result<(), String> {
+    let url = url::Url::parse(raw).map_err(|e| format!("invalid URL: {}", e))?;
+    match url.scheme() {
+        "http" | "https" | "socks5" | "socks5h" => {}
+        other => return Err(format!("unsupported proxy scheme `{}`", other)),
+    }
+    if url.host_str().is_none() {
+        return Err("proxy URL has no host".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_file(name: &str, toml: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("arb-config-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        std::fs::write(&path, toml).unwrap();
+        path
+    }
+
+    /// Parse `args` as `main` does, with `env` standing in for the process
+    /// environment, which tests running in parallel must not change
+    fn parse(args: &[&str], env: &[(&str, &'static str)]) -> Cli {
+        use clap::{CommandFactory, FromArgMatches};
+
+        let mut cmd = Cli::command();
+        let ids: Vec<_> = cmd.get_arguments().map(|a| a.get_id().clone()).collect();
+        for id in ids {
+            cmd = cmd.mut_arg(id, |arg| {
+                let value = arg
+                    .get_env()
+                    .and_then(|var| env.iter().find(|(name, _)| var == *name))
+                    .map(|(_, value)| *value);
+                // a default ranks below flags, just as an env var does
+                match value {
+                    Some(value) => arg.env(None).default_value(value),
+                    None => arg.env(None),
+                }
+            });
+        }
+        let matches = cmd
+            .try_get_matches_from(["arb"].iter().chain(args))
+            .unwrap();
+        Cli::from_arg_matches(&matches).unwrap()
+    }
+
+    fn problems(cfg: &Config) -> Vec<String> {
+        match cfg.validate() {
+            Err(ConfigError::Invalid(problems)) => problems,
+            other => panic!("expected validation problems, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        assert!(Config::default().validate().is_ok());
+    }
+
+    #[test]
+    fn every_invalid_setting_is_reported() {
+        let mut cfg = Config::default();
+        cfg.exchanges.enabled = vec!["binance".to_string(), "nowhere".to_string()];
+        cfg.exchanges.required = vec!["kraken".to_string()];
+        cfg.exchanges.default_fee = 7.0;
+        cfg.scan.ws_window_secs = 0;
+        cfg.http.connect_timeout_secs = 0;
+        cfg.paper.trade_fraction = 1.5;
+
+        let problems = problems(&cfg);
+        assert_eq!(problems.len(), 6, "{:#?}", problems);
+        for expected in [
+            "exchanges.enabled: unknown exchange `nowhere`",
+            "exchanges.required: `kraken` is not in exchanges.enabled",
+            "exchanges.default_fee must be in [0, 5) percent, got 7",
+            "scan.ws_window_secs must be between 1 and 300, got 0",
+            "http timeouts must be greater than zero",
+            "paper.trade_fraction must be in (0, 1], got 1.5",
+        ] {
+            assert!(
+                problems.iter().any(|p| p.starts_with(expected)),
+                "missing `{}` in {:#?}",
+                expected,
+                problems
+            );
+        }
+    }
+
+    #[test]
+    fn execution_needs_a_trade_key() {
+        const NEEDS_KEY: &str = "execution.enabled needs an auth.keys entry";
+        let key = |scope| ApiKeyConfig {
+            name: "bot".to_string(),
+            token: Some("bot-token-0123456789abcdef".to_string()),
+            token_env: None,
+            scopes: vec![scope],
+        };
+        let mut cfg = Config::default();
+        cfg.execution.enabled = true;
+        assert!(problems(&cfg).iter().any(|p| p.starts_with(NEEDS_KEY)));
+
+        cfg.auth.keys = vec![key(Scope::Read)];
+        assert!(problems(&cfg).iter().any(|p| p.starts_with(NEEDS_KEY)));
+
+        cfg.auth.keys = vec![key(Scope::Trade)];
+        assert!(cfg.validate().is_ok());
+    }
+
+    #[test]
+    fn map_keys_are_cleaned_up_like_lists() {
+        let mut cfg = Config::default();
+        cfg.exchanges.enabled = vec![" Binance ".to_string()];
+        cfg.exchanges.fees = HashMap::from([(" Binance ".to_string(), 0.1)]);
+        cfg.execution.accounts = HashMap::from([("GATE".to_string(), "arb".to_string())]);
+        cfg.execution.start_amounts = HashMap::from([(" usdt".to_string(), 100.0)]);
+        cfg.normalize();
+        assert_eq!(cfg.exchanges.enabled, vec!["binance"]);
+        assert_eq!(cfg.exchanges.fees["binance"], 0.1);
+        assert_eq!(cfg.execution.accounts["gateio"], "arb");
+        assert_eq!(cfg.execution.start_amounts["USDT"], 100.0);
+    }
+
+    #[test]
+    fn unknown_keys_and_bad_toml_are_rejected() {
+        let path = config_file("unknown", "[scan]\ndefault_min_proft = 1.0\n");
+        let cli = parse(&["--config", path.to_str().unwrap()], &[]);
+        let err = Config::load(&cli).unwrap_err();
+        assert!(matches!(err, ConfigError::Parse(..)), "{}", err);
+        assert!(err.to_string().contains("default_min_proft"));
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn flags_override_env_which_overrides_the_file() {
+        let path = config_file(
+            "precedence",
+            r#"
+            [server]
+            bind = "127.0.0.1:9000"
+            [exchanges]
+            enabled = ["binance", "KuCoin"]
+            [scan]
+            default_min_profit = 0.5
+            interval_secs = 10
+            "#,
+        );
+        let config = path.to_str().unwrap();
+        let load = |args: &[&str], env: &[(&str, &'static str)]| {
+            let args: Vec<&str> = ["--config", config].iter().chain(args).copied().collect();
+            Config::load(&parse(&args, env)).unwrap()
+        };
+
+        let cfg = load(&[], &[]);
+        assert_eq!(cfg.server.bind, "127.0.0.1:9000".parse().unwrap());
+        assert_eq!(cfg.exchanges.enabled, vec!["binance", "kucoin"]);
+        assert_eq!(cfg.scan.default_min_profit, 0.5);
+        assert_eq!(cfg.scan.interval_secs, 10);
+        // untouched sections keep their defaults
+        assert_eq!(cfg.scan.cache_ttl_secs, 15);
+
+        let env = [
+            ("ARB_MIN_PROFIT", "0.7"),
+            ("ARB_EXCHANGES", "bybit,gate"),
+            ("PORT", "9100"),
+        ];
+        let cfg = load(&[], &env);
+        assert_eq!(cfg.scan.default_min_profit, 0.7);
+        assert_eq!(cfg.exchanges.enabled, vec!["bybit", "gateio"]);
+        assert_eq!(cfg.server.bind, "127.0.0.1:9100".parse().unwrap());
+        assert_eq!(cfg.scan.interval_secs, 10);
+
+        let flags = [
+            "--min-profit",
+            "0.9",
+            "--scan-interval",
+            "30",
+            "--port",
+            "9200",
+        ];
+        let cfg = load(&flags, &env);
+        assert_eq!(cfg.scan.default_min_profit, 0.9);
+        assert_eq!(cfg.scan.interval_secs, 30);
+        assert_eq!(cfg.server.bind, "127.0.0.1:9200".parse().unwrap());
+        assert_eq!(cfg.exchanges.enabled, vec!["bybit", "gateio"]);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
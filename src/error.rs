@@ -0,0 +1,182 @@
+use axum::{
+    extract::rejection::JsonRejection,
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use serde_json::json;
+use std::fmt;
+
+use crate::exchanges::ExchangeError;
+
+/// Error returned by every handler and middleware. Serialized as
+/// `{ "status": "error", "code": "...", "error": "<message>" }` plus
+/// variant-specific fields.
+#[derive(Debug)]
+pub enum ApiError {
+    /// Body is not valid JSON or doesn't match the request shape
+    MalformedBody(String),
+    EmptyExchangeList,
+    TooManyExchanges {
+        max: usize,
+    },
+    UnknownExchange(String),
+    ExchangeDisabled(String),
+    /// The exchange has no perpetual swap ingestion
+    PerpsUnsupported(String),
+    /// A numeric field is NaN/inf or outside its allowed range
+    OutOfRange {
+        field: &'static str,
+        min: f64,
+        max: f64,
+    },
+    MissingCredentials,
+    InvalidCredentials,
+    MissingScope,
+    RateLimited {
+        retry_after_secs: u64,
+    },
+    /// Every requested exchange failed to respond
+    ExchangesUnavailable(Vec<ExchangeError>),
+    /// A triangle was not sent to the venue (execution disabled, no
+    /// credentials, venue busy, ...)
+    ExecutionRefused(String),
+    /// State that must survive a restart could not be written
+    StateNotSaved(String),
+}
+
+impl ApiError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::MalformedBody(_) => StatusCode::BAD_REQUEST,
+            ApiError::EmptyExchangeList
+            | ApiError::TooManyExchanges { .. }
+            | ApiError::UnknownExchange(_)
+            | ApiError::ExchangeDisabled(_)
+            | ApiError::PerpsUnsupported(_)
+            | ApiError::OutOfRange { .. }
+            | ApiError::ExecutionRefused(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::MissingCredentials | ApiError::InvalidCredentials => StatusCode::UNAUTHORIZED,
+            ApiError::MissingScope => StatusCode::FORBIDDEN,
+            ApiError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            // transient upstream trouble: tell the caller to come back later
+            ApiError::ExchangesUnavailable(failures)
+                if failures.iter().all(ExchangeError::is_retryable) =>
+            {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+            ApiError::ExchangesUnavailable(_) => StatusCode::BAD_GATEWAY,
+            ApiError::StateNotSaved(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Seconds for the `Retry-After` header, if the caller should retry
+    fn retry_after_secs(&self) -> Option<u64> {
+        match self {
+            ApiError::RateLimited { retry_after_secs } => Some(*retry_after_secs),
+            ApiError::ExchangesUnavailable(failures) => failures
+                .iter()
+                .filter_map(ExchangeError::retry_after)
+                .max()
+                .map(|d| d.as_secs()),
+            _ => None,
+        }
+    }
+
+    /// Stable machine-readable identifier
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::MalformedBody(_) => "malformed_body",
+            ApiError::EmptyExchangeList => "empty_exchange_list",
+            ApiError::TooManyExchanges { .. } => "too_many_exchanges",
+            ApiError::UnknownExchange(_) => "unknown_exchange",
+            ApiError::ExchangeDisabled(_) => "exchange_disabled",
+            ApiError::PerpsUnsupported(_) => "perps_unsupported",
+            ApiError::OutOfRange { .. } => "out_of_range",
+            ApiError::MissingCredentials => "missing_credentials",
+            ApiError::InvalidCredentials => "invalid_credentials",
+            ApiError::MissingScope => "missing_scope",
+            ApiError::RateLimited { .. } => "rate_limited",
+            ApiError::ExchangesUnavailable(_) => "exchanges_unavailable",
+            ApiError::ExecutionRefused(_) => "execution_refused",
+            ApiError::StateNotSaved(_) => "state_not_saved",
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::MalformedBody(e) => write!(f, "malformed request body: {}", e),
+            ApiError::EmptyExchangeList => write!(f, "exchanges must list at least one exchange"),
+            ApiError::TooManyExchanges { max } => {
+                write!(f, "at most {} exchanges can be scanned at once", max)
+            }
+            ApiError::UnknownExchange(ex) => write!(f, "unknown exchange `{}`", ex),
+            ApiError::ExchangeDisabled(ex) => write!(f, "exchange `{}` is disabled", ex),
+            ApiError::PerpsUnsupported(ex) => {
+                write!(f, "exchange `{}` has no perpetual swap data", ex)
+            }
+            ApiError::OutOfRange { field, min, max } => {
+                write!(f, "{} must be a number between {} and {}", field, min, max)
+            }
+            ApiError::MissingCredentials => write!(f, "missing API key"),
+            ApiError::InvalidCredentials => write!(f, "invalid API key"),
+            ApiError::MissingScope => write!(f, "API key lacks the required scope"),
+            ApiError::RateLimited { .. } => write!(f, "too many scan requests"),
+            ApiError::ExchangesUnavailable(_) => {
+                write!(f, "none of the requested exchanges returned data")
+            }
+            ApiError::ExecutionRefused(reason) => write!(f, "execution refused: {}", reason),
+            ApiError::StateNotSaved(e) => write!(f, "state not saved: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        ApiError::MalformedBody(rejection.body_text())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let mut body = json!({
+            "status": "error",
+            "code": self.code(),
+            "error": self.to_string(),
+        });
+        match &self {
+            ApiError::UnknownExchange(ex)
+            | ApiError::ExchangeDisabled(ex)
+            | ApiError::PerpsUnsupported(ex) => {
+                body["exchange"] = json!(ex);
+            }
+            ApiError::OutOfRange { field, .. } => body["field"] = json!(field),
+            ApiError::RateLimited { retry_after_secs } => {
+                body["retry_after_secs"] = json!(retry_after_secs);
+            }
+            ApiError::ExchangesUnavailable(failures) => {
+                body["failures"] = failures
+                    .iter()
+                    .map(|e| {
+                        json!({
+                            "exchange": e.exchange,
+                            "endpoint": e.endpoint,
+                            "kind": e.kind,
+                            "message": e.to_string(),
+                        })
+                    })
+                    .collect();
+            }
+            _ => {}
+        }
+
+        let mut resp = (self.status(), Json(body)).into_response();
+        if let Some(secs) = self.retry_after_secs() {
+            resp.headers_mut().insert(header::RETRY_AFTER, secs.into());
+        }
+        resp
+    }
+}
@@ -0,0 +1,497 @@
+use futures_util::StreamExt;
+use serde::Deserialize;
+use std::collections::HashMap;
+use tokio::time::{timeout, Duration};
+use tokio_tungstenite::connect_async;
+use tracing::{info, warn};
+
+use super::book::{Levels, BOOK_DEPTH};
+use super::{
+    de, decimals_step, perp_quote, spot_pair, ExchangeError, HttpClient, Markets, OrderBook,
+    OrderRules,
+};
+use crate::health::FeedRegistry;
+use crate::metrics;
+use crate::models::{PairPrice, PerpQuote};
+
+const INFO_URL: &str = "https://api.binance.com/api/v3/exchangeInfo";
+const TICKER_URL: &str = "https://api.binance.com/api/v3/ticker/24hr";
+const STREAM_URL: &str = "wss://stream.binance.com:9443/ws/!ticker@arr";
+const DEPTH_URL: &str = "https://api.binance.com/api/v3/depth";
+
+const PERP_INFO_URL: &str = "https://fapi.binance.com/fapi/v1/exchangeInfo";
+const PREMIUM_INDEX_URL: &str = "https://fapi.binance.com/fapi/v1/premiumIndex";
+const FUNDING_INFO_URL: &str = "https://fapi.binance.com/fapi/v1/fundingInfo";
+const PERP_TICKER_URL: &str = "https://fapi.binance.com/fapi/v1/ticker/24hr";
+
+/// Regular-user USDⓈ-M futures taker fee, percent per trade
+pub const PERP_TAKER_FEE_PERCENT: f64 = 0.05;
+
+/// `GET /api/v3/exchangeInfo`
+#[derive(Debug, Deserialize)]
+struct ExchangeInfo {
+    symbols: Vec<SymbolInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SymbolInfo {
+    symbol: String,
+    status: String,
+    base_asset: String,
+    quote_asset: String,
+    /// Decimals of a market buy's `quoteOrderQty`
+    #[serde(default)]
+    quote_asset_precision: Option<u32>,
+    #[serde(default)]
+    filters: Vec<SymbolFilter>,
+}
+
+/// One of a symbol's `filters`; only the fields orders are fitted to
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SymbolFilter {
+    filter_type: String,
+    #[serde(default, deserialize_with = "de::opt_f64_str")]
+    step_size: Option<f64>,
+    #[serde(default, deserialize_with = "de::opt_f64_str")]
+    min_qty: Option<f64>,
+    #[serde(default, deserialize_with = "de::opt_f64_str")]
+    tick_size: Option<f64>,
+    #[serde(default, deserialize_with = "de::opt_f64_str")]
+    min_notional: Option<f64>,
+}
+
+/// One entry of `GET /api/v3/ticker/24hr`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Ticker24h {
+    symbol: String,
+    #[serde(deserialize_with = "de::f64_str")]
+    last_price: f64,
+    #[serde(deserialize_with = "de::f64_str")]
+    quote_volume: f64,
+}
+
+/// One entry of a `!ticker@arr` frame (24hrTicker event)
+#[derive(Debug, Deserialize)]
+struct WsTicker {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "c", deserialize_with = "de::f64_str")]
+    last_price: f64,
+    #[serde(rename = "q", deserialize_with = "de::f64_str")]
+    quote_volume: f64,
+}
+
+/// `GET /fapi/v1/exchangeInfo`
+#[derive(Debug, Deserialize)]
+struct PerpExchangeInfo {
+    symbols: Vec<PerpSymbolInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PerpSymbolInfo {
+    symbol: String,
+    /// `PERPETUAL`, `CURRENT_QUARTER`, ...
+    contract_type: String,
+    status: String,
+    base_asset: String,
+    quote_asset: String,
+}
+
+/// One entry of `GET /fapi/v1/premiumIndex`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PremiumIndex {
+    symbol: String,
+    #[serde(deserialize_with = "de::f64_str")]
+    mark_price: f64,
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    index_price: Option<f64>,
+    /// `""` for delivery contracts
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    last_funding_rate: Option<f64>,
+    /// Unix ms; 0 for delivery contracts
+    next_funding_time: u64,
+}
+
+/// One entry of `GET /fapi/v1/fundingInfo`; only contracts whose interval
+/// differs from 8h are listed
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FundingInfo {
+    symbol: String,
+    funding_interval_hours: f64,
+}
+
+/// One entry of `GET /fapi/v1/ticker/24hr`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PerpTicker24h {
+    symbol: String,
+    #[serde(deserialize_with = "de::f64_str")]
+    quote_volume: f64,
+}
+
+/// symbol -> (base, quote) for every TRADING perpetual
+fn perp_symbol_map(info: PerpExchangeInfo) -> HashMap<String, (String, String)> {
+    info.symbols
+        .into_iter()
+        .filter(|s| s.contract_type == "PERPETUAL" && s.status == "TRADING")
+        .map(|s| {
+            (
+                s.symbol.to_uppercase(),
+                (s.base_asset.to_uppercase(), s.quote_asset.to_uppercase()),
+            )
+        })
+        .collect()
+}
+
+/// Join premium index, funding intervals and turnover per contract
+fn perp_quotes(
+    contracts: &HashMap<String, (String, String)>,
+    premium: Vec<PremiumIndex>,
+    intervals: Vec<FundingInfo>,
+    tickers: Vec<PerpTicker24h>,
+) -> Vec<PerpQuote> {
+    let intervals: HashMap<String, f64> = intervals
+        .into_iter()
+        .map(|f| (f.symbol.to_uppercase(), f.funding_interval_hours))
+        .collect();
+    let turnover: HashMap<String, f64> = tickers
+        .into_iter()
+        .map(|t| (t.symbol.to_uppercase(), t.quote_volume))
+        .collect();
+    premium
+        .into_iter()
+        .filter_map(|p| {
+            let symbol = p.symbol.to_uppercase();
+            let (base, quote) = contracts.get(&symbol)?;
+            let liquidity = *turnover.get(&symbol)?;
+            let perp = perp_quote(base, quote, &symbol, p.mark_price, liquidity)?;
+            Some(PerpQuote {
+                index_price: p.index_price.filter(|i| *i > 0.0),
+                funding_rate: p.last_funding_rate?,
+                next_funding_time: (p.next_funding_time > 0).then_some(p.next_funding_time),
+                funding_interval_hours: intervals
+                    .get(&symbol)
+                    .copied()
+                    .unwrap_or(perp.funding_interval_hours),
+                ..perp
+            })
+        })
+        .collect()
+}
+
+/// symbol -> (base, quote) for every TRADING symbol
+fn symbol_map(info: ExchangeInfo) -> HashMap<String, (String, String)> {
+    info.symbols
+        .into_iter()
+        .filter(|s| s.status == "TRADING")
+        .map(|s| {
+            (
+                s.symbol.to_uppercase(),
+                (s.base_asset.to_uppercase(), s.quote_asset.to_uppercase()),
+            )
+        })
+        .collect()
+}
+
+/// Order rules of every TRADING symbol from its LOT_SIZE, PRICE_FILTER and
+/// (MIN_)NOTIONAL filters
+fn order_rules(info: &ExchangeInfo) -> HashMap<String, OrderRules> {
+    info.symbols
+        .iter()
+        .filter(|s| s.status == "TRADING")
+        .map(|s| {
+            let filter = |kind: &str| s.filters.iter().find(|f| f.filter_type == kind);
+            let lot = filter("LOT_SIZE");
+            let rules = OrderRules {
+                qty_step: lot.and_then(|f| f.step_size),
+                quote_step: s.quote_asset_precision.map(decimals_step),
+                tick: filter("PRICE_FILTER").and_then(|f| f.tick_size),
+                min_qty: lot.and_then(|f| f.min_qty),
+                min_notional: filter("NOTIONAL")
+                    .or_else(|| filter("MIN_NOTIONAL"))
+                    .and_then(|f| f.min_notional),
+            };
+            (s.symbol.to_uppercase(), rules.normalized())
+        })
+        .collect()
+}
+
+/// exchangeInfo for base/quote mapping and order rules
+pub async fn load_markets(http: &HttpClient) -> Result<Markets, ExchangeError> {
+    let info: ExchangeInfo = http.get_json("binance", INFO_URL).await?;
+    let listed = info.symbols.len();
+    let rules = order_rules(&info);
+    Ok(Markets::new(listed, symbol_map(info)).with_rules(rules))
+}
+
+/// ---------------- Binance (Hybrid: WS + REST fallback) ----------------
+pub async fn fetch_binance(
+    http: &HttpClient,
+    markets: &Markets,
+    feeds: &FeedRegistry,
+    ws_window: Duration,
+) -> Result<Vec<PairPrice>, ExchangeError> {
+    info!(
+        "fetching binance via websocket ({}s) + REST backfill",
+        ws_window.as_secs()
+    );
+
+    // 1) base/quote mapping from the cached exchangeInfo
+    let symbol_map = &markets.symbols;
+    let total_symbols = symbol_map.len();
+
+    // 2) WS snapshot stream (sampled for `ws_window`). The stream can't go
+    // through a configured proxy / private CA, so those setups are REST-only.
+    let mut pairs: HashMap<String, PairPrice> = HashMap::new();
+    let (ws_messages, ws_decode_errors) = if http.has_custom_transport("binance") {
+        info!("binance: proxy or CA bundle configured, skipping websocket");
+        (0, 0)
+    } else {
+        sample_ws(symbol_map, feeds, ws_window, &mut pairs).await?
+    };
+
+    let ws_pairs = pairs.len();
+
+    // 3) REST fallback for missing pairs
+    let tickers: Vec<Ticker24h> = http.get_json("binance", TICKER_URL).await?;
+
+    let mut rest_added = 0usize;
+    let mut rest_skipped = 0usize;
+    for t in tickers {
+        let symbol = t.symbol.to_uppercase();
+        if pairs.contains_key(&symbol) {
+            continue;
+        }
+        match symbol_map
+            .get(&symbol)
+            .and_then(|(base, quote)| spot_pair(&symbol, base, quote, t.last_price, t.quote_volume))
+        {
+            Some(p) => {
+                pairs.insert(symbol, p);
+                rest_added += 1;
+            }
+            None => rest_skipped += 1,
+        }
+    }
+
+    info!(
+        "binance: total_symbols={} ws_messages={} ws_decode_errors={} ws_pairs={} rest_added={} final_pairs={}",
+        total_symbols,
+        ws_messages,
+        ws_decode_errors,
+        ws_pairs,
+        rest_added,
+        pairs.len()
+    );
+    metrics::WS_MESSAGES
+        .with_label_values(&["binance"])
+        .inc_by(ws_messages as u64);
+    metrics::record_pairs("binance", "ws", ws_pairs);
+    metrics::record_pairs("binance", "rest", rest_added);
+    metrics::record_ingest("binance", markets.listed, markets.skipped(), rest_skipped);
+
+    Ok(pairs.into_values().collect())
+}
+
+/// ---------------- Binance USDⓈ-M perpetuals (REST) ----------------
+pub async fn fetch_binance_perps(http: &HttpClient) -> Result<Vec<PerpQuote>, ExchangeError> {
+    info!("fetching binance perpetuals (REST)");
+
+    let info: PerpExchangeInfo = http.get_json("binance", PERP_INFO_URL).await?;
+    let listed = info.symbols.len();
+    let contracts = perp_symbol_map(info);
+    let premium: Vec<PremiumIndex> = http.get_json("binance", PREMIUM_INDEX_URL).await?;
+    let intervals: Vec<FundingInfo> = http.get_json("binance", FUNDING_INFO_URL).await?;
+    let tickers: Vec<PerpTicker24h> = http.get_json("binance", PERP_TICKER_URL).await?;
+
+    let out = perp_quotes(&contracts, premium, intervals, tickers);
+    info!(
+        "binance: perps_listed={} perpetuals={} returned={}",
+        listed,
+        contracts.len(),
+        out.len()
+    );
+    metrics::record_perps("binance", out.len());
+
+    Ok(out)
+}
+
+/// Collect `!ticker@arr` frames for `ws_window` into `pairs`; returns
+/// (frames received, frames that didn't decode)
+async fn sample_ws(
+    symbol_map: &HashMap<String, (String, String)>,
+    feeds: &FeedRegistry,
+    ws_window: Duration,
+    pairs: &mut HashMap<String, PairPrice>,
+) -> Result<(usize, usize), ExchangeError> {
+    let (ws_stream, _) = match connect_async(STREAM_URL).await {
+        Ok(conn) => conn,
+        Err(e) => {
+            feeds.set_ws_connected("binance", false);
+            return Err(ExchangeError::websocket("binance", STREAM_URL, e));
+        }
+    };
+    feeds.set_ws_connected("binance", true);
+    let (_write, mut read) = ws_stream.split();
+
+    let mut ws_messages = 0usize;
+    let mut ws_decode_errors = 0usize;
+
+    let ws_result = timeout(ws_window, async {
+        while let Some(msg) = read.next().await {
+            ws_messages += 1;
+            let Ok(msg) = msg else { continue };
+            let Ok(text) = msg.to_text() else { continue };
+            if text.is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<Vec<WsTicker>>(text) {
+                Ok(list) => {
+                    for t in list {
+                        let symbol = t.symbol.to_uppercase();
+                        if let Some((base, quote)) = symbol_map.get(&symbol) {
+                            if let Some(p) =
+                                spot_pair(&symbol, base, quote, t.last_price, t.quote_volume)
+                            {
+                                pairs.insert(symbol, p);
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    if ws_decode_errors == 0 {
+                        warn!("binance ws frame did not match ticker schema: {}", e);
+                    }
+                    ws_decode_errors += 1;
+                }
+            }
+        }
+    })
+    .await;
+
+    if ws_result.is_err() {
+        info!(
+            "binance ws collection stopped after {}s timeout",
+            ws_window.as_secs()
+        );
+    }
+
+    Ok((ws_messages, ws_decode_errors))
+}
+
+/// Top of one spot market's book, `symbol` as in exchangeInfo
+pub async fn fetch_book(http: &HttpClient, symbol: &str) -> Result<OrderBook, ExchangeError> {
+    let url = format!("{}?symbol={}&limit={}", DEPTH_URL, symbol, BOOK_DEPTH);
+    Ok(http.get_json::<Levels>("binance", &url).await?.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXCHANGE_INFO: &str = include_str!("../../tests/fixtures/binance/exchange_info.json");
+    const TICKER_24HR: &str = include_str!("../../tests/fixtures/binance/ticker_24hr.json");
+    const WS_TICKER_ARR: &str = include_str!("../../tests/fixtures/binance/ws_ticker_arr.json");
+    const PERP_INFO: &str = include_str!("../../tests/fixtures/binance/perp_exchange_info.json");
+    const PREMIUM_INDEX: &str = include_str!("../../tests/fixtures/binance/premium_index.json");
+    const FUNDING_INFO: &str = include_str!("../../tests/fixtures/binance/funding_info.json");
+    const PERP_TICKER: &str = include_str!("../../tests/fixtures/binance/perp_ticker_24hr.json");
+    const DEPTH: &str = include_str!("../../tests/fixtures/binance/depth.json");
+
+    #[test]
+    fn exchange_info_maps_only_trading_symbols() {
+        let info: ExchangeInfo = serde_json::from_str(EXCHANGE_INFO).unwrap();
+        assert_eq!(info.symbols.len(), 4);
+
+        let map = symbol_map(info);
+        assert_eq!(map.len(), 3);
+        assert_eq!(map["ETHBTC"], ("ETH".to_string(), "BTC".to_string()));
+        assert!(
+            !map.contains_key("LUNAUSDT"),
+            "BREAK status must be dropped"
+        );
+    }
+
+    #[test]
+    fn exchange_info_filters_become_order_rules() {
+        let info: ExchangeInfo = serde_json::from_str(EXCHANGE_INFO).unwrap();
+        let rules = order_rules(&info);
+        assert_eq!(rules.len(), 3);
+        assert_eq!(
+            rules["BTCUSDT"],
+            OrderRules {
+                qty_step: Some(0.00001),
+                quote_step: Some(1e-8),
+                tick: Some(0.01),
+                min_qty: Some(0.00001),
+                min_notional: Some(5.0),
+            }
+        );
+        assert_eq!(rules["ETHBTC"].min_notional, None);
+    }
+
+    #[test]
+    fn rest_and_ws_tickers_decode() {
+        let rest: Vec<Ticker24h> = serde_json::from_str(TICKER_24HR).unwrap();
+        assert_eq!(rest.len(), 3);
+        assert_eq!(rest[0].symbol, "ETHBTC");
+        assert_eq!(rest[0].last_price, 0.05412);
+
+        let ws: Vec<WsTicker> = serde_json::from_str(WS_TICKER_ARR).unwrap();
+        assert_eq!(ws.len(), 2);
+        assert_eq!(ws[1].symbol, "ETHUSDT");
+        assert!(ws[1].quote_volume > 0.0);
+    }
+
+    #[test]
+    fn renamed_field_fails_loudly() {
+        let changed = TICKER_24HR.replace("\"quoteVolume\"", "\"quoteVol\"");
+        assert!(serde_json::from_str::<Vec<Ticker24h>>(&changed).is_err());
+    }
+
+    #[test]
+    fn malformed_price_is_an_error_not_zero() {
+        let changed = TICKER_24HR.replace("\"0.05412000\"", "\"n/a\"");
+        assert!(serde_json::from_str::<Vec<Ticker24h>>(&changed).is_err());
+    }
+
+    #[test]
+    fn perpetuals_join_funding_and_turnover() {
+        let contracts = perp_symbol_map(serde_json::from_str(PERP_INFO).unwrap());
+        assert_eq!(
+            contracts.len(),
+            2,
+            "quarterlies and settling contracts are dropped"
+        );
+
+        let perps = perp_quotes(
+            &contracts,
+            serde_json::from_str(PREMIUM_INDEX).unwrap(),
+            serde_json::from_str(FUNDING_INFO).unwrap(),
+            serde_json::from_str(PERP_TICKER).unwrap(),
+        );
+        assert_eq!(perps.len(), 2);
+        let btc = perps.iter().find(|p| p.symbol == "BTCUSDT").unwrap();
+        assert_eq!(btc.funding_rate, 0.0001);
+        assert_eq!(btc.funding_interval_hours, 8.0);
+        assert_eq!(btc.next_funding_time, Some(1_760_832_000_000));
+        assert_eq!(btc.index_price, Some(112503.41521739));
+        let eth = perps.iter().find(|p| p.symbol == "ETHUSDT").unwrap();
+        assert_eq!(eth.funding_interval_hours, 4.0);
+        assert!(eth.funding_rate < 0.0);
+    }
+
+    #[test]
+    fn depth_decodes_best_first() {
+        let book = OrderBook::from(serde_json::from_str::<Levels>(DEPTH).unwrap());
+        assert_eq!(book.bids.len(), 3);
+        assert_eq!(book.bids[0], (0.05412, 3.1));
+        assert_eq!(book.asks[0], (0.05413, 2.4));
+    }
+}
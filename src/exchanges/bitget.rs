@@ -0,0 +1,220 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use tracing::info;
+
+use super::book::{Levels, BOOK_DEPTH};
+use super::{de, spot_pair, ErrorKind, ExchangeError, HttpClient, Markets, OrderBook};
+use crate::metrics;
+use crate::models::PairPrice;
+
+const SYMBOLS_URL: &str = "https://api.bitget.com/api/v2/spot/public/symbols";
+const TICKERS_URL: &str = "https://api.bitget.com/api/v2/spot/market/tickers";
+const BOOK_URL: &str = "https://api.bitget.com/api/v2/spot/market/orderbook";
+
+/// Base-tier spot taker fee, percent per leg
+pub const TAKER_FEE_PERCENT: f64 = 0.10;
+
+/// Every Bitget v2 response: `{ "code": "00000", "msg": "success", "data": ... }`
+#[derive(Debug, Deserialize)]
+struct Envelope<T> {
+    code: String,
+    #[serde(default)]
+    msg: String,
+    data: Option<T>,
+}
+
+impl<T> Envelope<T> {
+    fn into_data(self, url: &str) -> Result<T, ExchangeError> {
+        let kind = match self.code.as_str() {
+            "00000" => {
+                return self
+                    .data
+                    .ok_or_else(|| ExchangeError::decode("bitget", url, "missing `data`", ""))
+            }
+            "429" => ErrorKind::RateLimited {
+                retry_after_secs: None,
+            },
+            code => ErrorKind::Api {
+                code: code.to_string(),
+                message: self.msg,
+            },
+        };
+        Err(ExchangeError::new("bitget", url, kind))
+    }
+}
+
+/// One entry of `GET /api/v2/spot/public/symbols`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SymbolInfo {
+    symbol: String,
+    base_coin: String,
+    quote_coin: String,
+    /// `online`, `gray` (pre-listing), `halt` or `offline`
+    status: String,
+}
+
+/// One entry of `GET /api/v2/spot/market/tickers`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Ticker {
+    symbol: String,
+    /// `""` before the first trade
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    last_pr: Option<f64>,
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    quote_volume: Option<f64>,
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    bid_pr: Option<f64>,
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    ask_pr: Option<f64>,
+}
+
+/// Symbols open for trading, from `/api/v2/spot/public/symbols`
+pub async fn load_markets(http: &HttpClient) -> Result<Markets, ExchangeError> {
+    let symbols = http
+        .get_json::<Envelope<Vec<SymbolInfo>>>("bitget", SYMBOLS_URL)
+        .await?
+        .into_data(SYMBOLS_URL)?;
+    let listed = symbols.len();
+    Ok(Markets::new(listed, tradable_symbols(symbols)))
+}
+
+/// ----------------- BITGET -----------------
+pub async fn fetch_bitget(
+    http: &HttpClient,
+    markets: &Markets,
+) -> Result<Vec<PairPrice>, ExchangeError> {
+    info!("fetching bitget (REST)");
+
+    let tickers = http
+        .get_json::<Envelope<Vec<Ticker>>>("bitget", TICKERS_URL)
+        .await?
+        .into_data(TICKERS_URL)?;
+    let ws_total = tickers.len();
+    let out = pairs_from_tickers(&markets.symbols, tickers);
+    let ws_skipped = ws_total - out.len();
+
+    info!(
+        "bitget: found_total={} info_skipped={} tickers_total={} tickers_skipped={} returned={}",
+        markets.listed,
+        markets.skipped(),
+        ws_total,
+        ws_skipped,
+        out.len()
+    );
+    metrics::record_pairs("bitget", "rest", out.len());
+    metrics::record_ingest("bitget", markets.listed, markets.skipped(), ws_skipped);
+
+    Ok(out)
+}
+
+/// symbol -> (base, quote) for `online` symbols; `gray` ones only accept
+/// orders from market makers and `halt` ones none at all
+fn tradable_symbols(symbols: Vec<SymbolInfo>) -> HashMap<String, (String, String)> {
+    symbols
+        .into_iter()
+        .filter(|s| s.status == "online")
+        .map(|s| {
+            (
+                s.symbol.to_uppercase(),
+                (s.base_coin.to_uppercase(), s.quote_coin.to_uppercase()),
+            )
+        })
+        .collect()
+}
+
+fn pairs_from_tickers(
+    tradable: &HashMap<String, (String, String)>,
+    tickers: Vec<Ticker>,
+) -> Vec<PairPrice> {
+    tickers
+        .into_iter()
+        .filter_map(|t| {
+            let (base, quote) = tradable.get(&t.symbol.to_uppercase())?;
+            let pair = spot_pair(&t.symbol, base, quote, t.last_pr?, t.quote_volume?)?;
+            Some(PairPrice {
+                bid: t.bid_pr,
+                ask: t.ask_pr,
+                ..pair
+            })
+        })
+        .collect()
+}
+
+/// Top of one market's book, `symbol` like `BTCUSDT`
+pub async fn fetch_book(http: &HttpClient, symbol: &str) -> Result<OrderBook, ExchangeError> {
+    let url = format!(
+        "{}?symbol={}&type=step0&limit={}",
+        BOOK_URL, symbol, BOOK_DEPTH
+    );
+    Ok(http
+        .get_json::<Envelope<Levels>>("bitget", &url)
+        .await?
+        .into_data(&url)?
+        .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SYMBOLS: &str = include_str!("../../tests/fixtures/bitget/symbols.json");
+    const TICKERS: &str = include_str!("../../tests/fixtures/bitget/tickers.json");
+    const RATE_LIMITED: &str = include_str!("../../tests/fixtures/bitget/error_429.json");
+    const ORDERBOOK: &str = include_str!("../../tests/fixtures/bitget/orderbook.json");
+
+    #[test]
+    fn symbols_and_tickers_produce_pairs() {
+        let symbols = serde_json::from_str::<Envelope<Vec<SymbolInfo>>>(SYMBOLS)
+            .unwrap()
+            .into_data(SYMBOLS_URL)
+            .unwrap();
+        let tradable = tradable_symbols(symbols);
+        assert_eq!(tradable.len(), 3);
+        assert!(!tradable.contains_key("HALTUSDT"));
+        assert!(!tradable.contains_key("GRAYUSDT"));
+
+        let tickers = serde_json::from_str::<Envelope<Vec<Ticker>>>(TICKERS)
+            .unwrap()
+            .into_data(TICKERS_URL)
+            .unwrap();
+        assert_eq!(tickers.len(), 5);
+        assert_eq!(tickers[4].last_pr, None, "empty last price decodes as None");
+
+        let pairs = pairs_from_tickers(&tradable, tickers);
+        assert_eq!(pairs.len(), 3);
+        let eth_btc = pairs
+            .iter()
+            .find(|p| p.base == "ETH" && p.quote == "BTC")
+            .unwrap();
+        assert_eq!(eth_btc.price, 0.05413);
+        assert_eq!(eth_btc.ask, Some(0.05414));
+    }
+
+    #[test]
+    fn error_code_maps_to_rate_limited() {
+        let err = serde_json::from_str::<Envelope<Vec<Ticker>>>(RATE_LIMITED)
+            .unwrap()
+            .into_data(TICKERS_URL)
+            .unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::RateLimited { .. }));
+    }
+
+    #[test]
+    fn renamed_price_field_fails_loudly() {
+        let changed = TICKERS.replace("\"lastPr\"", "\"close\"");
+        assert!(serde_json::from_str::<Envelope<Vec<Ticker>>>(&changed).is_err());
+    }
+
+    #[test]
+    fn orderbook_decodes() {
+        let levels = serde_json::from_str::<Envelope<Levels>>(ORDERBOOK)
+            .unwrap()
+            .into_data(BOOK_URL)
+            .unwrap();
+        let book = OrderBook::from(levels);
+        assert_eq!(book.bids[0], (0.05412, 3.1));
+        assert_eq!(book.asks[2], (0.05416, 9.15));
+    }
+}
@@ -0,0 +1,147 @@
+//! Order-book snapshots and the fills a market order would get from them.
+//!
+//! Venues only publish the top of the book over REST, so a fill that runs
+//! past the last level is reported as incomplete rather than extrapolated.
+
+use serde::Deserialize;
+
+use super::de;
+
+/// Levels requested per side
+pub const BOOK_DEPTH: usize = 20;
+
+/// Snapshot of one market; levels are `(price, base quantity)`, best first
+#[derive(Debug, Clone, Default)]
+pub struct OrderBook {
+    pub bids: Vec<(f64, f64)>,
+    pub asks: Vec<(f64, f64)>,
+}
+
+/// Result of walking one side of the book with a market order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fill {
+    /// Amount given up: base for a sell, quote for a buy
+    pub spent: f64,
+    /// Amount obtained: quote for a sell, base for a buy
+    pub received: f64,
+    /// Quote per base over the whole fill; 0 when nothing filled
+    pub avg_price: f64,
+    /// The order filled entirely within the snapshot's depth
+    pub complete: bool,
+}
+
+impl OrderBook {
+    /// Orders levels best first and drops empty or malformed ones; venues
+    /// already sort, but a mock or a generic source might not
+    pub fn new(mut bids: Vec<(f64, f64)>, mut asks: Vec<(f64, f64)>) -> Self {
+        let usable = |&(price, qty): &(f64, f64)| price > 0.0 && qty > 0.0 && price.is_finite();
+        bids.retain(usable);
+        asks.retain(usable);
+        bids.sort_by(|a, b| b.0.total_cmp(&a.0));
+        asks.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { bids, asks }
+    }
+
+    /// Sell `qty` base into the bids
+    pub fn sell(&self, qty: f64) -> Fill {
+        let (mut left, mut received) = (qty, 0.0);
+        for &(price, size) in &self.bids {
+            if left <= 0.0 {
+                break;
+            }
+            let take = left.min(size);
+            received += take * price;
+            left -= take;
+        }
+        let spent = qty - left.max(0.0);
+        Fill {
+            spent,
+            received,
+            avg_price: if spent > 0.0 { received / spent } else { 0.0 },
+            complete: left <= qty * 1e-12,
+        }
+    }
+
+    /// Spend `amount` quote on the asks
+    pub fn buy_with(&self, amount: f64) -> Fill {
+        let (mut left, mut received) = (amount, 0.0);
+        for &(price, size) in &self.asks {
+            if left <= 0.0 {
+                break;
+            }
+            let take = (left / price).min(size);
+            received += take;
+            left -= take * price;
+        }
+        let spent = amount - left.max(0.0);
+        Fill {
+            spent,
+            received,
+            avg_price: if received > 0.0 {
+                spent / received
+            } else {
+                0.0
+            },
+            complete: left <= amount * 1e-12,
+        }
+    }
+}
+
+/// `{ "bids": [[price, qty, ...]], "asks": [...] }`, the shape most venues use
+#[derive(Debug, Deserialize)]
+pub(super) struct Levels {
+    #[serde(deserialize_with = "de::levels")]
+    bids: Vec<(f64, f64)>,
+    #[serde(deserialize_with = "de::levels")]
+    asks: Vec<(f64, f64)>,
+}
+
+impl From<Levels> for OrderBook {
+    fn from(l: Levels) -> Self {
+        OrderBook::new(l.bids, l.asks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book() -> OrderBook {
+        OrderBook::new(
+            vec![(99.0, 2.0), (100.0, 1.0)],
+            vec![(102.0, 2.0), (101.0, 1.0), (0.0, 5.0)],
+        )
+    }
+
+    #[test]
+    fn levels_are_sorted_best_first() {
+        let b = book();
+        assert_eq!(b.bids[0], (100.0, 1.0));
+        assert_eq!(b.asks, vec![(101.0, 1.0), (102.0, 2.0)]);
+    }
+
+    #[test]
+    fn sell_walks_the_bids() {
+        let fill = book().sell(2.0);
+        assert_eq!(fill.received, 199.0);
+        assert_eq!(fill.avg_price, 99.5);
+        assert!(fill.complete);
+
+        let fill = book().sell(5.0);
+        assert_eq!(fill.spent, 3.0);
+        assert!(!fill.complete);
+    }
+
+    #[test]
+    fn buy_spends_quote_on_the_asks() {
+        let fill = book().buy_with(203.0);
+        assert_eq!(fill.received, 1.0 + 102.0 / 102.0);
+        assert_eq!(fill.avg_price, 101.5);
+        assert!(fill.complete);
+
+        let fill = book().buy_with(1_000.0);
+        assert_eq!(fill.spent, 305.0);
+        assert_eq!(fill.received, 3.0);
+        assert!(!fill.complete);
+    }
+}
@@ -0,0 +1,414 @@
+//! Client-side accounting of each venue's request-weight budget.
+//!
+//! Every request reserves its weight before it is sent; when the remaining
+//! budget would drop below the configured headroom the request waits for the
+//! window to reset instead of risking a 429/418 (and, on Binance, an IP ban).
+//! Quota headers in responses correct the local estimate.
+
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::metrics;
+
+/// Published limit and window of each venue's public REST pool
+fn default_quota(exchange: &str) -> (u32, Duration) {
+    match exchange {
+        // REQUEST_WEIGHT per IP, reset every calendar minute
+        "binance" => (6000, Duration::from_secs(60)),
+        // USDⓈ-M futures API, counted separately from spot
+        "binance-futures" => (2400, Duration::from_secs(60)),
+        // 600 requests / 5s per IP
+        "bybit" => (600, Duration::from_secs(5)),
+        // public resource pool, 30s window
+        "kucoin" => (2000, Duration::from_secs(30)),
+        // 200 requests / 10s per endpoint; tracked per venue to stay safe
+        "gateio" => (200, Duration::from_secs(10)),
+        // 20 requests / 2s per IP on instruments and tickers
+        "okx" => (20, Duration::from_secs(2)),
+        // public endpoints allow roughly one call per second per IP
+        "kraken" => (15, Duration::from_secs(15)),
+        // 10 requests / s per IP on the public market endpoints
+        "coinbase" => (10, Duration::from_secs(1)),
+        // 20 requests / s per IP on symbols and tickers
+        "bitget" => (20, Duration::from_secs(1)),
+        // 500 weight / 10s per endpoint; tracked per venue to stay safe
+        "mexc" => (500, Duration::from_secs(10)),
+        _ => (100, Duration::from_secs(10)),
+    }
+}
+
+/// Budget a request is charged to. Venues whose derivatives API has its own
+/// limits get a separate pool (`binance-futures`); everything else shares
+/// the exchange's.
+pub fn pool<'a>(exchange: &'a str, url: &str) -> &'a str {
+    let host = url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string));
+    match (exchange, host.as_deref()) {
+        ("binance", Some("fapi.binance.com")) => "binance-futures",
+        _ => exchange,
+    }
+}
+
+/// Weight a request consumes; everything not listed costs 1. `exchange` is
+/// the budget pool, see [`pool`].
+pub fn request_weight(exchange: &str, url: &str) -> u32 {
+    let path = url::Url::parse(url)
+        .map(|u| u.path().to_string())
+        .unwrap_or_default();
+    match (exchange, path.as_str()) {
+        ("binance", "/api/v3/exchangeInfo") => 20,
+        // all symbols at once
+        ("binance", "/api/v3/ticker/24hr") => 80,
+        // up to 100 levels
+        ("binance", "/api/v3/depth") => 5,
+        // all symbols at once
+        ("binance-futures", "/fapi/v1/premiumIndex") => 10,
+        ("binance-futures", "/fapi/v1/ticker/24hr") => 40,
+        ("kucoin", "/api/v1/market/allTickers") => 15,
+        ("kucoin", "/api/v1/symbols") => 4,
+        ("kucoin", "/api/v1/market/orderbook/level2_20") => 2,
+        ("mexc", "/api/v3/exchangeInfo") => 10,
+        // all symbols at once
+        ("mexc", "/api/v3/ticker/24hr") => 40,
+        _ => 1,
+    }
+}
+
+/// Quota as reported by a venue's response headers
+#[derive(Debug)]
+struct Reported {
+    limit: Option<u32>,
+    used: Option<u32>,
+    reset_in: Option<Duration>,
+}
+
+fn header<T: std::str::FromStr>(headers: &HeaderMap, name: &str) -> Option<T> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+/// Time until a reset given as unix milliseconds
+fn until_unix_ms(ms: u64) -> Duration {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    Duration::from_millis(ms).saturating_sub(now)
+}
+
+fn parse_headers(exchange: &str, headers: &HeaderMap) -> Option<Reported> {
+    let from_remaining = |limit: Option<u32>, remaining: Option<u32>| match (limit, remaining) {
+        (Some(l), Some(r)) => Some(l.saturating_sub(r)),
+        _ => None,
+    };
+    let reported = match exchange {
+        "binance" | "binance-futures" => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default();
+            Reported {
+                limit: None,
+                used: header(headers, "x-mbx-used-weight-1m"),
+                reset_in: Some(Duration::from_secs(60 - now.as_secs() % 60)),
+            }
+        }
+        "bybit" => {
+            let limit = header(headers, "x-bapi-limit");
+            Reported {
+                limit,
+                used: from_remaining(limit, header(headers, "x-bapi-limit-status")),
+                reset_in: header(headers, "x-bapi-limit-reset-timestamp").map(until_unix_ms),
+            }
+        }
+        "kucoin" => {
+            let limit = header(headers, "gw-ratelimit-limit");
+            Reported {
+                limit,
+                used: from_remaining(limit, header(headers, "gw-ratelimit-remaining")),
+                reset_in: header(headers, "gw-ratelimit-reset").map(Duration::from_millis),
+            }
+        }
+        "gateio" => {
+            let limit = header(headers, "x-gate-ratelimit-limit");
+            Reported {
+                limit,
+                used: from_remaining(limit, header(headers, "x-gate-ratelimit-requests-remain")),
+                reset_in: header(headers, "x-gate-ratelimit-reset-timestamp").map(until_unix_ms),
+            }
+        }
+        _ => return None,
+    };
+    reported.used.is_some().then_some(reported)
+}
+
+#[derive(Debug)]
+struct Budget {
+    limit: u32,
+    window: Duration,
+    used: u32,
+    resets_at: Instant,
+    /// Whether `used` last came from the venue rather than our own count
+    confirmed: bool,
+    throttled: u64,
+}
+
+impl Budget {
+    fn new(exchange: &str) -> Self {
+        let (limit, window) = default_quota(exchange);
+        Self {
+            limit,
+            window,
+            used: 0,
+            resets_at: Instant::now() + window,
+            confirmed: false,
+            throttled: 0,
+        }
+    }
+
+    fn roll(&mut self, now: Instant) {
+        if now >= self.resets_at {
+            self.used = 0;
+            self.resets_at = now + self.window;
+            self.confirmed = false;
+        }
+    }
+}
+
+/// Remaining budget of one venue, as served on `/status`
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetStatus {
+    pub limit: u32,
+    pub used: u32,
+    pub remaining: u32,
+    pub resets_in_ms: u64,
+    /// `headers` when the venue reported usage this window, else `estimate`
+    pub source: &'static str,
+    /// Requests delayed so far to stay under the limit
+    pub throttled: u64,
+}
+
+/// Per-exchange weight budgets, shared by every HTTP client generation so a
+/// config reload doesn't forget what has been spent.
+#[derive(Debug, Default)]
+pub struct RateBudgets {
+    venues: Mutex<HashMap<String, Budget>>,
+}
+
+impl RateBudgets {
+    pub fn new(exchanges: &[&str]) -> Self {
+        let venues = exchanges
+            .iter()
+            .map(|ex| (ex.to_string(), Budget::new(ex)))
+            .collect();
+        Self {
+            venues: Mutex::new(venues),
+        }
+    }
+
+    fn with<R>(&self, exchange: &str, f: impl FnOnce(&mut Budget) -> R) -> R {
+        let mut venues = self.venues.lock().expect("rate budgets poisoned");
+        let budget = venues
+            .entry(exchange.to_string())
+            .or_insert_with(|| Budget::new(exchange));
+        budget.roll(Instant::now());
+        let out = f(budget);
+        publish(exchange, budget);
+        out
+    }
+
+    /// Spend `weight` if it fits under `limit - headroom_percent`, otherwise
+    /// return how long until the window resets; `None` when `weight` is more
+    /// than a whole window allows, so waiting would never help.
+    pub fn reserve(
+        &self,
+        exchange: &str,
+        weight: u32,
+        headroom_percent: u32,
+    ) -> Result<(), Option<Duration>> {
+        self.with(exchange, |b| {
+            // limits come from response headers: widen rather than overflow
+            let headroom = u64::from(b.limit) * u64::from(headroom_percent.min(100)) / 100;
+            let usable = b.limit - headroom as u32;
+            if b.used.saturating_add(weight) <= usable {
+                b.used += weight;
+                return Ok(());
+            }
+            b.throttled += 1;
+            metrics::RATE_LIMIT_THROTTLED
+                .with_label_values(&[exchange])
+                .inc();
+            if weight > usable {
+                return Err(None);
+            }
+            Err(Some(b.resets_at.saturating_duration_since(Instant::now())))
+        })
+    }
+
+    /// Replace the local estimate with what the venue reported
+    pub fn observe(&self, exchange: &str, headers: &HeaderMap) {
+        let Some(reported) = parse_headers(exchange, headers) else {
+            return;
+        };
+        self.with(exchange, |b| {
+            if let Some(limit) = reported.limit {
+                b.limit = limit;
+            }
+            if let Some(used) = reported.used {
+                b.used = used;
+                b.confirmed = true;
+            }
+            if let Some(reset_in) = reported.reset_in {
+                b.resets_at = Instant::now() + reset_in;
+            }
+        });
+    }
+
+    /// The venue throttled us anyway: treat the budget as spent until it
+    /// says we may come back.
+    pub fn exhausted(&self, exchange: &str, retry_after: Option<Duration>) {
+        self.with(exchange, |b| {
+            b.used = b.limit;
+            if let Some(wait) = retry_after {
+                b.resets_at = Instant::now() + wait;
+            }
+        });
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<String, BudgetStatus> {
+        let now = Instant::now();
+        let mut venues = self.venues.lock().expect("rate budgets poisoned");
+        venues
+            .iter_mut()
+            .map(|(ex, b)| {
+                b.roll(now);
+                (ex.clone(), status(b, now))
+            })
+            .collect()
+    }
+}
+
+fn status(b: &Budget, now: Instant) -> BudgetStatus {
+    BudgetStatus {
+        limit: b.limit,
+        used: b.used,
+        remaining: b.limit.saturating_sub(b.used),
+        resets_in_ms: b.resets_at.saturating_duration_since(now).as_millis() as u64,
+        source: if b.confirmed { "headers" } else { "estimate" },
+        throttled: b.throttled,
+    }
+}
+
+fn publish(exchange: &str, b: &Budget) {
+    metrics::RATE_LIMIT_LIMIT
+        .with_label_values(&[exchange])
+        .set(b.limit as i64);
+    metrics::RATE_LIMIT_REMAINING
+        .with_label_values(&[exchange])
+        .set(b.limit.saturating_sub(b.used) as i64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut h = HeaderMap::new();
+        for (k, v) in pairs {
+            h.insert(*k, HeaderValue::from_str(v).unwrap());
+        }
+        h
+    }
+
+    #[test]
+    fn heavy_binance_calls_are_weighted() {
+        assert_eq!(
+            request_weight("binance", "https://api.binance.com/api/v3/ticker/24hr"),
+            80
+        );
+        assert_eq!(
+            request_weight(
+                "bybit",
+                "https://api.bybit.com/v5/market/tickers?category=spot"
+            ),
+            1
+        );
+    }
+
+    #[test]
+    fn futures_api_has_its_own_pool() {
+        let url = "https://fapi.binance.com/fapi/v1/premiumIndex";
+        assert_eq!(pool("binance", url), "binance-futures");
+        assert_eq!(request_weight(pool("binance", url), url), 10);
+        assert_eq!(
+            pool("binance", "https://api.binance.com/api/v3/exchangeInfo"),
+            "binance"
+        );
+    }
+
+    #[test]
+    fn reserve_throttles_before_the_limit() {
+        let budgets = RateBudgets::default();
+        // 6000 limit, 10% headroom => 5400 usable
+        for _ in 0..67 {
+            budgets.reserve("binance", 80, 10).unwrap();
+        }
+        let wait = budgets.reserve("binance", 80, 10).unwrap_err().unwrap();
+        assert!(wait <= Duration::from_secs(60));
+        assert_eq!(budgets.snapshot()["binance"].throttled, 1);
+    }
+
+    #[test]
+    fn binance_used_weight_header_replaces_estimate() {
+        let budgets = RateBudgets::default();
+        budgets.reserve("binance", 20, 10).unwrap();
+        budgets.observe("binance", &headers(&[("x-mbx-used-weight-1m", "5390")]));
+
+        let status = &budgets.snapshot()["binance"];
+        assert_eq!(status.used, 5390);
+        assert_eq!(status.source, "headers");
+        assert!(budgets.reserve("binance", 20, 10).is_err());
+    }
+
+    #[test]
+    fn kucoin_quota_headers_set_limit_and_remaining() {
+        let budgets = RateBudgets::default();
+        budgets.observe(
+            "kucoin",
+            &headers(&[
+                ("gw-ratelimit-limit", "4000"),
+                ("gw-ratelimit-remaining", "3985"),
+                ("gw-ratelimit-reset", "29000"),
+            ]),
+        );
+        let status = &budgets.snapshot()["kucoin"];
+        assert_eq!(status.limit, 4000);
+        assert_eq!(status.remaining, 3985);
+        assert!(status.resets_in_ms <= 29_000);
+    }
+
+    #[test]
+    fn oversized_requests_and_reported_quotas_fail_fast() {
+        let budgets = RateBudgets::default();
+        // 20 limit, 10% headroom => 18 usable: a window never fits 19
+        assert_eq!(budgets.reserve("okx", 19, 10), Err(None));
+        assert_eq!(budgets.reserve("okx", 1, 100), Err(None));
+
+        budgets.observe(
+            "bybit",
+            &headers(&[("x-bapi-limit", "4294967295"), ("x-bapi-limit-status", "0")]),
+        );
+        assert_eq!(budgets.snapshot()["bybit"].used, u32::MAX);
+        assert!(budgets.reserve("bybit", 1, 50).unwrap_err().is_some());
+    }
+
+    #[test]
+    fn missing_headers_leave_the_estimate_alone() {
+        let budgets = RateBudgets::default();
+        budgets.reserve("bybit", 1, 10).unwrap();
+        budgets.observe("bybit", &HeaderMap::new());
+        assert_eq!(budgets.snapshot()["bybit"].used, 1);
+    }
+}
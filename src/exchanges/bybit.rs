@@ -0,0 +1,417 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use tracing::info;
+
+use super::book::BOOK_DEPTH;
+use super::{
+    de, perp_quote, spot_pair, ErrorKind, ExchangeError, HttpClient, Markets, OrderBook, OrderRules,
+};
+use crate::metrics;
+use crate::models::{PairPrice, PerpQuote};
+
+const INFO_URL: &str = "https://api.bybit.com/v5/market/instruments-info?category=spot";
+const TICKERS_URL: &str = "https://api.bybit.com/v5/market/tickers?category=spot";
+/// ~500 linear contracts, so one page of the 1000 maximum covers them
+const PERP_INFO_URL: &str =
+    "https://api.bybit.com/v5/market/instruments-info?category=linear&limit=1000";
+const PERP_TICKERS_URL: &str = "https://api.bybit.com/v5/market/tickers?category=linear";
+const BOOK_URL: &str = "https://api.bybit.com/v5/market/orderbook?category=spot";
+
+/// Non-VIP derivatives taker fee, percent per trade
+pub const PERP_TAKER_FEE_PERCENT: f64 = 0.055;
+
+/// Quote assets worth scanning on Bybit
+const QUOTES: &[&str] = &["USDT", "USDC", "BTC", "ETH"];
+
+/// Every Bybit v5 response: `{ "retCode": 0, "retMsg": "OK", "result": ... }`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Envelope<T> {
+    ret_code: i64,
+    #[serde(default)]
+    ret_msg: String,
+    result: Option<T>,
+}
+
+impl<T> Envelope<T> {
+    /// Bybit v5 reports failures via `retCode` on a 200 response
+    fn into_result(self, url: &str) -> Result<T, ExchangeError> {
+        let kind = match self.ret_code {
+            0 => {
+                return self
+                    .result
+                    .ok_or_else(|| ExchangeError::decode("bybit", url, "missing `result`", ""))
+            }
+            // too many visits / IP banned
+            10006 | 10018 => ErrorKind::RateLimited {
+                retry_after_secs: None,
+            },
+            code => ErrorKind::Api {
+                code: code.to_string(),
+                message: self.ret_msg,
+            },
+        };
+        Err(ExchangeError::new("bybit", url, kind))
+    }
+}
+
+/// `result` is `{}` on error responses, hence the default
+#[derive(Debug, Deserialize)]
+struct List<T> {
+    #[serde(default = "Vec::new")]
+    list: Vec<T>,
+}
+
+/// One entry of `GET /v5/market/instruments-info?category=spot`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Instrument {
+    symbol: String,
+    base_coin: String,
+    quote_coin: String,
+    status: String,
+    #[serde(default)]
+    lot_size_filter: Option<LotSizeFilter>,
+    #[serde(default)]
+    price_filter: Option<PriceFilter>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LotSizeFilter {
+    /// Step of the base quantity
+    #[serde(default, deserialize_with = "de::opt_f64_str")]
+    base_precision: Option<f64>,
+    /// Step of a market buy's quote amount
+    #[serde(default, deserialize_with = "de::opt_f64_str")]
+    quote_precision: Option<f64>,
+    #[serde(default, deserialize_with = "de::opt_f64_str")]
+    min_order_qty: Option<f64>,
+    #[serde(default, deserialize_with = "de::opt_f64_str")]
+    min_order_amt: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PriceFilter {
+    #[serde(default, deserialize_with = "de::opt_f64_str")]
+    tick_size: Option<f64>,
+}
+
+/// One entry of `GET /v5/market/tickers?category=spot`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Ticker {
+    symbol: String,
+    #[serde(deserialize_with = "de::f64_str")]
+    last_price: f64,
+    /// 24h turnover in the quote asset
+    #[serde(rename = "turnover24h", deserialize_with = "de::f64_str")]
+    turnover_24h: f64,
+}
+
+/// `GET /v5/market/orderbook?category=spot`
+#[derive(Debug, Deserialize)]
+struct Book {
+    #[serde(rename = "b", deserialize_with = "de::levels")]
+    bids: Vec<(f64, f64)>,
+    #[serde(rename = "a", deserialize_with = "de::levels")]
+    asks: Vec<(f64, f64)>,
+}
+
+/// One entry of `GET /v5/market/instruments-info?category=linear`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PerpInstrument {
+    symbol: String,
+    /// `LinearPerpetual` or `LinearFutures`
+    contract_type: String,
+    status: String,
+    base_coin: String,
+    quote_coin: String,
+    /// Minutes between settlements
+    funding_interval: u64,
+}
+
+/// One entry of `GET /v5/market/tickers?category=linear`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PerpTicker {
+    symbol: String,
+    #[serde(deserialize_with = "de::f64_str")]
+    mark_price: f64,
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    index_price: Option<f64>,
+    /// `""` for dated futures
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    funding_rate: Option<f64>,
+    /// Unix ms as a string
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    next_funding_time: Option<f64>,
+    #[serde(rename = "turnover24h", deserialize_with = "de::f64_str")]
+    turnover_24h: f64,
+}
+
+/// Trading spot instruments with a scanned quote asset
+pub async fn load_markets(http: &HttpClient) -> Result<Markets, ExchangeError> {
+    let instruments = http
+        .get_json::<Envelope<List<Instrument>>>("bybit", INFO_URL)
+        .await?
+        .into_result(INFO_URL)?
+        .list;
+    let listed = instruments.len();
+    let rules = order_rules(&instruments);
+    Ok(Markets::new(listed, symbol_map(instruments)).with_rules(rules))
+}
+
+/// ----------------- BYBIT -----------------
+pub async fn fetch_bybit(
+    http: &HttpClient,
+    markets: &Markets,
+) -> Result<Vec<PairPrice>, ExchangeError> {
+    info!("fetching bybit (REST)");
+
+    // tickers
+    let tickers = http
+        .get_json::<Envelope<List<Ticker>>>("bybit", TICKERS_URL)
+        .await?
+        .into_result(TICKERS_URL)?
+        .list;
+    let ws_total = tickers.len();
+    let out: Vec<PairPrice> = tickers
+        .into_iter()
+        .filter_map(|t| {
+            let (base, quote) = markets.symbols.get(&t.symbol.to_uppercase())?;
+            spot_pair(&t.symbol, base, quote, t.last_price, t.turnover_24h)
+        })
+        .collect();
+    let ws_skipped = ws_total - out.len();
+
+    info!(
+        "bybit: found_total={} info_skipped={} tickers_total={} tickers_skipped={} returned={}",
+        markets.listed,
+        markets.skipped(),
+        ws_total,
+        ws_skipped,
+        out.len()
+    );
+    metrics::record_pairs("bybit", "rest", out.len());
+    metrics::record_ingest("bybit", markets.listed, markets.skipped(), ws_skipped);
+
+    Ok(out)
+}
+
+/// ---------------- Bybit linear perpetuals (REST) ----------------
+pub async fn fetch_bybit_perps(http: &HttpClient) -> Result<Vec<PerpQuote>, ExchangeError> {
+    info!("fetching bybit perpetuals (REST)");
+
+    let instruments = http
+        .get_json::<Envelope<List<PerpInstrument>>>("bybit", PERP_INFO_URL)
+        .await?
+        .into_result(PERP_INFO_URL)?
+        .list;
+    let listed = instruments.len();
+    let tickers = http
+        .get_json::<Envelope<List<PerpTicker>>>("bybit", PERP_TICKERS_URL)
+        .await?
+        .into_result(PERP_TICKERS_URL)?
+        .list;
+
+    let out = perp_quotes(instruments, tickers);
+    info!("bybit: perps_listed={} returned={}", listed, out.len());
+    metrics::record_perps("bybit", out.len());
+
+    Ok(out)
+}
+
+/// Trading perpetuals with their tickers
+fn perp_quotes(instruments: Vec<PerpInstrument>, tickers: Vec<PerpTicker>) -> Vec<PerpQuote> {
+    let contracts: HashMap<String, PerpInstrument> = instruments
+        .into_iter()
+        .filter(|i| i.contract_type == "LinearPerpetual" && i.status == "Trading")
+        .map(|i| (i.symbol.to_uppercase(), i))
+        .collect();
+    tickers
+        .into_iter()
+        .filter_map(|t| {
+            let symbol = t.symbol.to_uppercase();
+            let c = contracts.get(&symbol)?;
+            let perp = perp_quote(
+                &c.base_coin.to_uppercase(),
+                &c.quote_coin.to_uppercase(),
+                &symbol,
+                t.mark_price,
+                t.turnover_24h,
+            )?;
+            Some(PerpQuote {
+                index_price: t.index_price.filter(|i| *i > 0.0),
+                funding_rate: t.funding_rate?,
+                next_funding_time: t.next_funding_time.filter(|t| *t > 0.0).map(|t| t as u64),
+                funding_interval_hours: if c.funding_interval > 0 {
+                    c.funding_interval as f64 / 60.0
+                } else {
+                    perp.funding_interval_hours
+                },
+                ..perp
+            })
+        })
+        .collect()
+}
+
+/// symbol -> (base, quote) for trading instruments quoted in one of [`QUOTES`]
+fn symbol_map(instruments: Vec<Instrument>) -> HashMap<String, (String, String)> {
+    instruments
+        .into_iter()
+        .filter(|i| i.status == "Trading")
+        .map(|i| {
+            (
+                i.symbol.to_uppercase(),
+                (i.base_coin.to_uppercase(), i.quote_coin.to_uppercase()),
+            )
+        })
+        .filter(|(_, (_, quote))| QUOTES.contains(&quote.as_str()))
+        .collect()
+}
+
+/// Order rules of every trading instrument from its lot size and price
+/// filters
+fn order_rules(instruments: &[Instrument]) -> HashMap<String, OrderRules> {
+    instruments
+        .iter()
+        .filter(|i| i.status == "Trading")
+        .map(|i| {
+            let lot = i.lot_size_filter.as_ref();
+            let rules = OrderRules {
+                qty_step: lot.and_then(|l| l.base_precision),
+                quote_step: lot.and_then(|l| l.quote_precision),
+                tick: i.price_filter.as_ref().and_then(|p| p.tick_size),
+                min_qty: lot.and_then(|l| l.min_order_qty),
+                min_notional: lot.and_then(|l| l.min_order_amt),
+            };
+            (i.symbol.to_uppercase(), rules.normalized())
+        })
+        .collect()
+}
+
+/// Top of one spot market's book, `symbol` like `BTCUSDT`
+pub async fn fetch_book(http: &HttpClient, symbol: &str) -> Result<OrderBook, ExchangeError> {
+    let url = format!("{}&symbol={}&limit={}", BOOK_URL, symbol, BOOK_DEPTH);
+    let book = http
+        .get_json::<Envelope<Book>>("bybit", &url)
+        .await?
+        .into_result(&url)?;
+    Ok(OrderBook::new(book.bids, book.asks))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INSTRUMENTS: &str = include_str!("../../tests/fixtures/bybit/instruments_info.json");
+    const TICKERS: &str = include_str!("../../tests/fixtures/bybit/tickers.json");
+    const RATE_LIMITED: &str = include_str!("../../tests/fixtures/bybit/error_10006.json");
+    const PERP_INSTRUMENTS: &str =
+        include_str!("../../tests/fixtures/bybit/linear_instruments.json");
+    const PERP_TICKERS: &str = include_str!("../../tests/fixtures/bybit/linear_tickers.json");
+    const ORDERBOOK: &str = include_str!("../../tests/fixtures/bybit/orderbook.json");
+
+    #[test]
+    fn instruments_filter_status_and_quote() {
+        let list = serde_json::from_str::<Envelope<List<Instrument>>>(INSTRUMENTS)
+            .unwrap()
+            .into_result(INFO_URL)
+            .unwrap()
+            .list;
+        assert_eq!(list.len(), 5);
+
+        let map = symbol_map(list);
+        assert_eq!(map.len(), 3);
+        assert!(
+            !map.contains_key("OLDUSDT"),
+            "non-Trading status is dropped"
+        );
+        assert!(!map.contains_key("BTCEUR"), "unsupported quote is dropped");
+    }
+
+    #[test]
+    fn lot_size_and_price_filters_become_order_rules() {
+        let list = serde_json::from_str::<Envelope<List<Instrument>>>(INSTRUMENTS)
+            .unwrap()
+            .into_result(INFO_URL)
+            .unwrap()
+            .list;
+        let rules = order_rules(&list);
+        assert_eq!(rules.len(), 4);
+        assert!(!rules.contains_key("OLDUSDT"));
+        assert_eq!(
+            rules["ETHBTC"],
+            OrderRules {
+                qty_step: Some(0.0001),
+                quote_step: Some(0.0000001),
+                tick: Some(0.000001),
+                min_qty: Some(0.001),
+                min_notional: Some(0.00001),
+            }
+        );
+    }
+
+    #[test]
+    fn tickers_use_turnover_as_liquidity() {
+        let list = serde_json::from_str::<Envelope<List<Ticker>>>(TICKERS)
+            .unwrap()
+            .into_result(TICKERS_URL)
+            .unwrap()
+            .list;
+        assert_eq!(list.len(), 3);
+        assert_eq!(list[0].symbol, "BTCUSDT");
+        assert_eq!(list[0].turnover_24h, 1_023_482_211.81);
+    }
+
+    #[test]
+    fn missing_turnover_fails_loudly() {
+        // the old fetcher guessed between quoteVolume24h / turnover24h / volume24h
+        let changed = TICKERS.replace("\"turnover24h\"", "\"quoteVolume24h\"");
+        assert!(serde_json::from_str::<Envelope<List<Ticker>>>(&changed).is_err());
+    }
+
+    #[test]
+    fn ret_code_maps_to_rate_limited() {
+        let err = serde_json::from_str::<Envelope<List<Ticker>>>(RATE_LIMITED)
+            .unwrap()
+            .into_result(TICKERS_URL)
+            .unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::RateLimited { .. }));
+    }
+
+    #[test]
+    fn linear_perpetuals_carry_funding() {
+        let instruments = serde_json::from_str::<Envelope<List<PerpInstrument>>>(PERP_INSTRUMENTS)
+            .unwrap()
+            .into_result(PERP_INFO_URL)
+            .unwrap()
+            .list;
+        let tickers = serde_json::from_str::<Envelope<List<PerpTicker>>>(PERP_TICKERS)
+            .unwrap()
+            .into_result(PERP_TICKERS_URL)
+            .unwrap()
+            .list;
+
+        let perps = perp_quotes(instruments, tickers);
+        assert_eq!(perps.len(), 2, "dated futures are dropped");
+        let sol = perps.iter().find(|p| p.symbol == "SOLUSDT").unwrap();
+        assert_eq!(sol.funding_rate, 0.00025);
+        assert_eq!(sol.funding_interval_hours, 4.0);
+        assert_eq!(sol.next_funding_time, Some(1_760_817_600_000));
+        assert_eq!(sol.liquidity, 1_811_402_117.4);
+    }
+
+    #[test]
+    fn orderbook_decodes_short_side_names() {
+        let book = serde_json::from_str::<Envelope<Book>>(ORDERBOOK)
+            .unwrap()
+            .into_result(BOOK_URL)
+            .unwrap();
+        assert_eq!(book.bids[0], (0.05412, 3.1));
+        assert_eq!(book.asks.len(), 3);
+    }
+}
@@ -0,0 +1,443 @@
+use futures::future::join_all;
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use std::collections::HashMap;
+use tokio::time::{timeout, Duration};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{info, warn};
+
+use super::book::BOOK_DEPTH;
+use super::{de, spot_pair, ExchangeError, HttpClient, Markets, OrderBook};
+use crate::health::FeedRegistry;
+use crate::metrics;
+use crate::models::PairPrice;
+
+const PRODUCTS_URL: &str =
+    "https://api.coinbase.com/api/v3/brokerage/market/products?product_type=SPOT";
+const BOOK_URL: &str = "https://api.coinbase.com/api/v3/brokerage/market/product_book";
+const STREAM_URL: &str = "wss://advanced-trade-ws.coinbase.com";
+
+/// Advanced Trade entry-tier taker fee, percent per leg
+pub const TAKER_FEE_PERCENT: f64 = 1.20;
+
+/// Pairs missed by the stream whose top of book is fetched over REST, most
+/// liquid first; each costs one request
+const BOOK_BACKFILL: usize = 20;
+
+/// `GET /api/v3/brokerage/market/products`
+#[derive(Debug, Deserialize)]
+struct Products {
+    products: Vec<Product>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Product {
+    product_id: String,
+    base_currency_id: String,
+    quote_currency_id: String,
+    /// `""` for products that haven't traded
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    price: Option<f64>,
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    approximate_quote_24h_volume: Option<f64>,
+    status: String,
+    #[serde(default)]
+    trading_disabled: bool,
+    #[serde(default)]
+    is_disabled: bool,
+    #[serde(default)]
+    cancel_only: bool,
+    /// Set on USDC books that are the USD book under another name
+    /// (`BTC-USDC` -> `BTC-USD`)
+    #[serde(default)]
+    alias: String,
+}
+
+/// `GET /api/v3/brokerage/market/product_book?product_id=..&limit=..`
+#[derive(Debug, Deserialize)]
+struct ProductBook {
+    pricebook: PriceBook,
+}
+
+#[derive(Debug, Deserialize)]
+struct PriceBook {
+    product_id: String,
+    bids: Vec<BookLevel>,
+    asks: Vec<BookLevel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BookLevel {
+    #[serde(deserialize_with = "de::f64_str")]
+    price: f64,
+    #[serde(deserialize_with = "de::f64_str")]
+    size: f64,
+}
+
+impl From<PriceBook> for OrderBook {
+    fn from(book: PriceBook) -> Self {
+        let side = |levels: Vec<BookLevel>| levels.into_iter().map(|l| (l.price, l.size)).collect();
+        OrderBook::new(side(book.bids), side(book.asks))
+    }
+}
+
+/// A frame on the market data WebSocket
+#[derive(Debug, Deserialize)]
+struct WsFrame {
+    #[serde(default)]
+    channel: String,
+    /// `error` frames carry a `message` and no channel
+    #[serde(default, rename = "type")]
+    kind: String,
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    events: Vec<WsEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WsEvent {
+    #[serde(default)]
+    tickers: Vec<WsTicker>,
+}
+
+/// One entry of a `ticker_batch` event
+#[derive(Debug, Deserialize)]
+struct WsTicker {
+    product_id: String,
+    #[serde(deserialize_with = "de::f64_str")]
+    price: f64,
+    /// 24h base volume
+    #[serde(rename = "volume_24_h", deserialize_with = "de::f64_str")]
+    volume_24h: f64,
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    best_bid: Option<f64>,
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    best_ask: Option<f64>,
+}
+
+impl Product {
+    fn tradable(&self) -> bool {
+        self.status == "online"
+            && !self.trading_disabled
+            && !self.is_disabled
+            && !self.cancel_only
+            && self.alias.is_empty()
+    }
+}
+
+impl WsTicker {
+    fn to_pair(&self, base: &str, quote: &str) -> Option<PairPrice> {
+        let pair = spot_pair(
+            &self.product_id,
+            base,
+            quote,
+            self.price,
+            self.volume_24h * self.price,
+        )?;
+        Some(PairPrice {
+            bid: self.best_bid,
+            ask: self.best_ask,
+            ..pair
+        })
+    }
+}
+
+/// Tradable spot products, minus USDC aliases of USD books
+pub async fn load_markets(http: &HttpClient) -> Result<Markets, ExchangeError> {
+    let products: Products = http.get_json("coinbase", PRODUCTS_URL).await?;
+    let listed = products.products.len();
+    Ok(Markets::new(listed, symbol_map(&products.products)))
+}
+
+fn symbol_map(products: &[Product]) -> HashMap<String, (String, String)> {
+    products
+        .iter()
+        .filter(|p| p.tradable())
+        .map(|p| {
+            (
+                p.product_id.to_uppercase(),
+                (
+                    p.base_currency_id.to_uppercase(),
+                    p.quote_currency_id.to_uppercase(),
+                ),
+            )
+        })
+        .collect()
+}
+
+/// ---------------- Coinbase (Hybrid: WS + REST fallback) ----------------
+pub async fn fetch_coinbase(
+    http: &HttpClient,
+    markets: &Markets,
+    feeds: &FeedRegistry,
+    ws_window: Duration,
+) -> Result<Vec<PairPrice>, ExchangeError> {
+    info!(
+        "fetching coinbase via websocket ({}s) + REST backfill",
+        ws_window.as_secs()
+    );
+    let symbol_map = &markets.symbols;
+
+    // the stream can't go through a configured proxy / private CA
+    let mut pairs: HashMap<String, PairPrice> = HashMap::new();
+    let (ws_messages, ws_decode_errors) = if http.has_custom_transport("coinbase") {
+        info!("coinbase: proxy or CA bundle configured, skipping websocket");
+        (0, 0)
+    } else {
+        sample_ws(symbol_map, feeds, ws_window, &mut pairs).await?
+    };
+    let ws_pairs = pairs.len();
+
+    // last price and 24h turnover for everything the stream missed
+    let products: Products = http.get_json("coinbase", PRODUCTS_URL).await?;
+    let mut rest_added = 0usize;
+    let mut rest_skipped = 0usize;
+    for p in products.products {
+        let symbol = p.product_id.to_uppercase();
+        if pairs.contains_key(&symbol) {
+            continue;
+        }
+        match symbol_map.get(&symbol).and_then(|(base, quote)| {
+            spot_pair(
+                &symbol,
+                base,
+                quote,
+                p.price?,
+                p.approximate_quote_24h_volume?,
+            )
+        }) {
+            Some(pair) => {
+                pairs.insert(symbol, pair);
+                rest_added += 1;
+            }
+            None => rest_skipped += 1,
+        }
+    }
+
+    let books = backfill_books(http, &mut pairs).await;
+
+    info!(
+        "coinbase: total_symbols={} ws_messages={} ws_decode_errors={} ws_pairs={} rest_added={} books={} final_pairs={}",
+        symbol_map.len(),
+        ws_messages,
+        ws_decode_errors,
+        ws_pairs,
+        rest_added,
+        books,
+        pairs.len()
+    );
+    metrics::WS_MESSAGES
+        .with_label_values(&["coinbase"])
+        .inc_by(ws_messages as u64);
+    metrics::record_pairs("coinbase", "ws", ws_pairs);
+    metrics::record_pairs("coinbase", "rest", rest_added);
+    metrics::record_ingest("coinbase", markets.listed, markets.skipped(), rest_skipped);
+
+    Ok(pairs.into_values().collect())
+}
+
+/// Top of book for the most liquid pairs still lacking bid/ask; a failed
+/// book request only leaves that pair without a spread. Returns how many
+/// books were applied.
+async fn backfill_books(http: &HttpClient, pairs: &mut HashMap<String, PairPrice>) -> usize {
+    let mut missing: Vec<(&String, f64)> = pairs
+        .iter()
+        .filter(|(_, p)| p.bid.is_none() || p.ask.is_none())
+        .map(|(symbol, p)| (symbol, p.liquidity))
+        .collect();
+    missing.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let urls: Vec<String> = missing
+        .iter()
+        .take(BOOK_BACKFILL)
+        .map(|(symbol, _)| format!("{}?product_id={}&limit=1", BOOK_URL, symbol))
+        .collect();
+
+    let books = join_all(
+        urls.iter()
+            .map(|url| http.get_json::<ProductBook>("coinbase", url)),
+    )
+    .await;
+
+    let mut applied = 0usize;
+    for book in books {
+        let book = match book {
+            Ok(b) => b.pricebook,
+            Err(e) => {
+                warn!("coinbase: {}", e);
+                continue;
+            }
+        };
+        if let Some(pair) = pairs.get_mut(&book.product_id.to_uppercase()) {
+            pair.bid = book.bids.first().map(|l| l.price);
+            pair.ask = book.asks.first().map(|l| l.price);
+            applied += 1;
+        }
+    }
+    applied
+}
+
+/// Subscribe to `ticker_batch` (one frame per 5s with every changed
+/// product) and collect it for `ws_window`; returns (frames received,
+/// frames that didn't decode)
+async fn sample_ws(
+    symbol_map: &HashMap<String, (String, String)>,
+    feeds: &FeedRegistry,
+    ws_window: Duration,
+    pairs: &mut HashMap<String, PairPrice>,
+) -> Result<(usize, usize), ExchangeError> {
+    let (ws_stream, _) = match connect_async(STREAM_URL).await {
+        Ok(conn) => conn,
+        Err(e) => {
+            feeds.set_ws_connected("coinbase", false);
+            return Err(ExchangeError::websocket("coinbase", STREAM_URL, e));
+        }
+    };
+    feeds.set_ws_connected("coinbase", true);
+    let (mut write, mut read) = ws_stream.split();
+
+    let mut product_ids: Vec<&String> = symbol_map.keys().collect();
+    product_ids.sort();
+    let subscribe = serde_json::json!({
+        "type": "subscribe",
+        "channel": "ticker_batch",
+        "product_ids": product_ids,
+    });
+    if let Err(e) = write.send(Message::Text(subscribe.to_string())).await {
+        feeds.set_ws_connected("coinbase", false);
+        return Err(ExchangeError::websocket("coinbase", STREAM_URL, e));
+    }
+
+    let mut ws_messages = 0usize;
+    let mut ws_decode_errors = 0usize;
+
+    let ws_result = timeout(ws_window, async {
+        while let Some(msg) = read.next().await {
+            ws_messages += 1;
+            let Ok(msg) = msg else { continue };
+            let Ok(text) = msg.to_text() else { continue };
+            if text.is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<WsFrame>(text) {
+                Ok(frame) if frame.kind == "error" => {
+                    warn!("coinbase ws error: {}", frame.message);
+                }
+                Ok(frame) if frame.channel == "ticker_batch" => {
+                    for t in frame.events.into_iter().flat_map(|e| e.tickers) {
+                        let symbol = t.product_id.to_uppercase();
+                        if let Some((base, quote)) = symbol_map.get(&symbol) {
+                            if let Some(p) = t.to_pair(base, quote) {
+                                pairs.insert(symbol, p);
+                            }
+                        }
+                    }
+                }
+                // subscriptions / heartbeats
+                Ok(_) => {}
+                Err(e) => {
+                    if ws_decode_errors == 0 {
+                        warn!("coinbase ws frame did not match ticker schema: {}", e);
+                    }
+                    ws_decode_errors += 1;
+                }
+            }
+        }
+    })
+    .await;
+
+    if ws_result.is_err() {
+        info!(
+            "coinbase ws collection stopped after {}s timeout",
+            ws_window.as_secs()
+        );
+    }
+
+    Ok((ws_messages, ws_decode_errors))
+}
+
+/// Top of one product's book, `product_id` like `BTC-USD`
+pub async fn fetch_book(http: &HttpClient, product_id: &str) -> Result<OrderBook, ExchangeError> {
+    let url = format!(
+        "{}?product_id={}&limit={}",
+        BOOK_URL, product_id, BOOK_DEPTH
+    );
+    Ok(http
+        .get_json::<ProductBook>("coinbase", &url)
+        .await?
+        .pricebook
+        .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRODUCTS: &str = include_str!("../../tests/fixtures/coinbase/products.json");
+    const PRODUCT_BOOK: &str = include_str!("../../tests/fixtures/coinbase/product_book.json");
+    const PRODUCT_BOOK_DEPTH: &str =
+        include_str!("../../tests/fixtures/coinbase/product_book_depth.json");
+    const WS_TICKER_BATCH: &str =
+        include_str!("../../tests/fixtures/coinbase/ws_ticker_batch.json");
+
+    #[test]
+    fn products_drop_disabled_and_usdc_aliases() {
+        let products: Products = serde_json::from_str(PRODUCTS).unwrap();
+        assert_eq!(products.products.len(), 5);
+
+        let map = symbol_map(&products.products);
+        assert_eq!(map.len(), 3);
+        assert_eq!(map["ETH-BTC"], ("ETH".to_string(), "BTC".to_string()));
+        assert!(
+            !map.contains_key("BTC-USDC"),
+            "alias of BTC-USD would fake a 1:1 USD/USDC leg"
+        );
+        assert!(!map.contains_key("OLD-USD"));
+    }
+
+    #[test]
+    fn product_book_gives_top_of_book() {
+        let book: ProductBook = serde_json::from_str(PRODUCT_BOOK).unwrap();
+        assert_eq!(book.pricebook.product_id, "ETH-BTC");
+        assert_eq!(book.pricebook.bids[0].price, 0.05412);
+        assert_eq!(book.pricebook.asks[0].price, 0.05414);
+    }
+
+    #[test]
+    fn ticker_batch_decodes_and_skips_unlisted() {
+        let products: Products = serde_json::from_str(PRODUCTS).unwrap();
+        let map = symbol_map(&products.products);
+
+        let frame: WsFrame = serde_json::from_str(WS_TICKER_BATCH).unwrap();
+        assert_eq!(frame.channel, "ticker_batch");
+        let tickers: Vec<WsTicker> = frame.events.into_iter().flat_map(|e| e.tickers).collect();
+        assert_eq!(tickers.len(), 2);
+
+        let pairs: Vec<PairPrice> = tickers
+            .iter()
+            .filter_map(|t| {
+                let (base, quote) = map.get(&t.product_id)?;
+                t.to_pair(base, quote)
+            })
+            .collect();
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(pairs[0].bid, Some(0.05415));
+        assert!((pairs[0].liquidity - 4128.12 * 0.05415).abs() < 1e-9);
+    }
+
+    #[test]
+    fn error_frame_is_recognised() {
+        let frame: WsFrame =
+            serde_json::from_str(r#"{"type":"error","message":"failure to subscribe"}"#).unwrap();
+        assert_eq!(frame.kind, "error");
+        assert!(frame.events.is_empty());
+    }
+
+    #[test]
+    fn product_book_levels_carry_size() {
+        let book: ProductBook = serde_json::from_str(PRODUCT_BOOK_DEPTH).unwrap();
+        let book = OrderBook::from(book.pricebook);
+        assert_eq!(book.bids[0], (112498.30, 0.412305));
+        assert_eq!(book.asks[2], (112502.77, 2.0));
+    }
+}
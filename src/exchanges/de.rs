@@ -0,0 +1,68 @@
+//! Serde helpers for the decimal-as-string fields every venue uses.
+//!
+//! Malformed numbers are a decode error, not a silent zero: a venue changing
+//! a field's format should fail the fetch loudly.
+
+use serde::{de, Deserialize, Deserializer};
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StrOrNum<'a> {
+    Str(&'a str),
+    Owned(String),
+    Num(f64),
+}
+
+fn parse<E: de::Error>(raw: &str) -> Result<f64, E> {
+    raw.trim()
+        .parse::<f64>()
+        .map_err(|_| E::custom(format!("invalid decimal string `{}`", raw)))
+}
+
+fn value<E: de::Error>(v: StrOrNum) -> Result<f64, E> {
+    match v {
+        StrOrNum::Str(s) => parse(s),
+        StrOrNum::Owned(s) => parse(&s),
+        StrOrNum::Num(n) => Ok(n),
+    }
+}
+
+/// Required decimal, sent as `"123.45"` (or occasionally a bare number)
+pub fn f64_str<'de, D: Deserializer<'de>>(d: D) -> Result<f64, D::Error> {
+    value(StrOrNum::deserialize(d)?)
+}
+
+/// Decimal that venues leave `null` or `""` for pairs without trades yet
+pub fn opt_f64_str<'de, D: Deserializer<'de>>(d: D) -> Result<Option<f64>, D::Error> {
+    match Option::<StrOrNum>::deserialize(d)? {
+        None => Ok(None),
+        Some(StrOrNum::Str(s)) if s.trim().is_empty() => Ok(None),
+        Some(StrOrNum::Owned(s)) if s.trim().is_empty() => Ok(None),
+        Some(StrOrNum::Str(s)) => parse(s).map(Some),
+        Some(StrOrNum::Owned(s)) => parse(&s).map(Some),
+        Some(StrOrNum::Num(n)) => Ok(Some(n)),
+    }
+}
+
+/// Array of decimals, e.g. Kraken's `"v": ["1211.4", "2801.9"]`
+pub fn f64_str_seq<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<f64>, D::Error> {
+    Vec::<StrOrNum>::deserialize(d)?
+        .into_iter()
+        .map(value)
+        .collect()
+}
+
+/// Order-book side as `[[price, qty, ...], ...]`; trailing entries (order
+/// counts, timestamps) are ignored
+pub fn levels<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<(f64, f64)>, D::Error> {
+    Vec::<Vec<StrOrNum>>::deserialize(d)?
+        .into_iter()
+        .map(|level| {
+            let mut entries = level.into_iter();
+            match (entries.next(), entries.next()) {
+                (Some(price), Some(qty)) => Ok((value(price)?, value(qty)?)),
+                _ => Err(de::Error::custom("book level without price and quantity")),
+            }
+        })
+        .collect()
+}
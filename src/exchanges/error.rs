@@ -0,0 +1,262 @@
+use reqwest::{header::HeaderMap, StatusCode};
+use serde::Serialize;
+use std::fmt;
+use std::time::Duration;
+
+/// What went wrong talking to an exchange
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// DNS, connect, TLS or a dropped connection
+    Network { message: String },
+    /// No response within the configured timeout
+    Timeout,
+    /// Non-success status not covered by a more specific kind
+    Http { status: u16, body: String },
+    /// 429 / 418, or an exchange-specific throttling code
+    RateLimited { retry_after_secs: Option<u64> },
+    /// Body didn't match the expected shape
+    Decode { message: String },
+    /// Venue-level error code returned inside a 200 response
+    Api { code: String, message: String },
+    /// Venue refuses service from our location (HTTP 451, CloudFront 403)
+    GeoBlocked,
+    /// Venue reports scheduled or emergency maintenance
+    Maintenance,
+    /// Certificate verification or TLS handshake failed; never retried, as
+    /// it may mean someone is intercepting the connection
+    Tls { message: String },
+    /// WebSocket handshake or stream failure
+    WebSocket { message: String },
+    /// Exchange name the dispatcher doesn't know
+    Unsupported,
+}
+
+/// Failure of one exchange request, carrying enough context for retries and
+/// for the API to report which venue/endpoint misbehaved.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExchangeError {
+    pub exchange: String,
+    /// Path of the failing request, e.g. `/api/v3/exchangeInfo`
+    pub endpoint: String,
+    pub kind: ErrorKind,
+}
+
+impl ExchangeError {
+    pub fn new(exchange: &str, endpoint: &str, kind: ErrorKind) -> Self {
+        Self {
+            exchange: exchange.to_string(),
+            endpoint: endpoint_path(endpoint),
+            kind,
+        }
+    }
+
+    pub fn unsupported(exchange: &str) -> Self {
+        Self::new(exchange, "", ErrorKind::Unsupported)
+    }
+
+    pub fn decode(exchange: &str, endpoint: &str, err: impl fmt::Display, raw: &str) -> Self {
+        Self::new(
+            exchange,
+            endpoint,
+            ErrorKind::Decode {
+                message: format!(
+                    "{}. First 100 chars: {}",
+                    err,
+                    raw.chars().take(100).collect::<String>()
+                ),
+            },
+        )
+    }
+
+    pub fn from_reqwest(exchange: &str, endpoint: &str, err: reqwest::Error) -> Self {
+        let kind = if err.is_timeout() {
+            ErrorKind::Timeout
+        } else if err.is_decode() {
+            ErrorKind::Decode {
+                message: err.to_string(),
+            }
+        } else {
+            let message = error_chain(&err);
+            if is_tls_failure(&message) {
+                ErrorKind::Tls { message }
+            } else {
+                ErrorKind::Network { message }
+            }
+        };
+        Self::new(exchange, endpoint, kind)
+    }
+
+    /// Classify a non-success response
+    pub fn from_status(
+        exchange: &str,
+        endpoint: &str,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &str,
+    ) -> Self {
+        let lower = body.to_lowercase();
+        let kind = match status.as_u16() {
+            // Binance answers 418 once an IP is auto-banned for ignoring 429s
+            429 | 418 => ErrorKind::RateLimited {
+                retry_after_secs: retry_after(headers),
+            },
+            451 => ErrorKind::GeoBlocked,
+            403 if lower.contains("country")
+                || lower.contains("region")
+                || lower.contains("restricted location") =>
+            {
+                ErrorKind::GeoBlocked
+            }
+            503 if lower.contains("maintenance") || lower.contains("system upgrade") => {
+                ErrorKind::Maintenance
+            }
+            code => ErrorKind::Http {
+                status: code,
+                body: body.chars().take(200).collect(),
+            },
+        };
+        Self::new(exchange, endpoint, kind)
+    }
+
+    pub fn websocket(exchange: &str, endpoint: &str, err: impl fmt::Display) -> Self {
+        let message = err.to_string();
+        let kind = if is_tls_failure(&message) {
+            ErrorKind::Tls { message }
+        } else {
+            ErrorKind::WebSocket { message }
+        };
+        Self::new(exchange, endpoint, kind)
+    }
+
+    /// Worth retrying after a backoff
+    pub fn is_retryable(&self) -> bool {
+        match &self.kind {
+            ErrorKind::Network { .. }
+            | ErrorKind::Timeout
+            | ErrorKind::RateLimited { .. }
+            | ErrorKind::WebSocket { .. } => true,
+            ErrorKind::Http { status, .. } => *status >= 500,
+            ErrorKind::Api { .. }
+            | ErrorKind::Decode { .. }
+            | ErrorKind::GeoBlocked
+            | ErrorKind::Maintenance
+            | ErrorKind::Tls { .. }
+            | ErrorKind::Unsupported => false,
+        }
+    }
+
+    /// Server-requested wait before the next attempt
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self.kind {
+            ErrorKind::RateLimited {
+                retry_after_secs: Some(secs),
+            } => Some(Duration::from_secs(secs)),
+            _ => None,
+        }
+    }
+
+    /// Short label for metrics
+    pub fn kind_label(&self) -> &'static str {
+        match self.kind {
+            ErrorKind::Network { .. } => "network",
+            ErrorKind::Timeout => "timeout",
+            ErrorKind::Http { .. } => "http",
+            ErrorKind::RateLimited { .. } => "rate_limited",
+            ErrorKind::Decode { .. } => "decode",
+            ErrorKind::Api { .. } => "api",
+            ErrorKind::GeoBlocked => "geo_blocked",
+            ErrorKind::Maintenance => "maintenance",
+            ErrorKind::Tls { .. } => "tls",
+            ErrorKind::WebSocket { .. } => "websocket",
+            ErrorKind::Unsupported => "unsupported",
+        }
+    }
+}
+
+impl fmt::Display for ExchangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}: ", self.exchange, self.endpoint)?;
+        match &self.kind {
+            ErrorKind::Network { message } => write!(f, "network error: {}", message),
+            ErrorKind::Timeout => write!(f, "request timed out"),
+            ErrorKind::Http { status, body } => write!(f, "HTTP {}: {}", status, body),
+            ErrorKind::RateLimited {
+                retry_after_secs: Some(secs),
+            } => write!(f, "rate limited, retry after {}s", secs),
+            ErrorKind::RateLimited { .. } => write!(f, "rate limited"),
+            ErrorKind::Decode { message } => write!(f, "decode error: {}", message),
+            ErrorKind::Api { code, message } => write!(f, "API error {}: {}", code, message),
+            ErrorKind::GeoBlocked => write!(f, "access blocked from this location"),
+            ErrorKind::Maintenance => write!(f, "exchange under maintenance"),
+            ErrorKind::Tls { message } => write!(f, "TLS verification failed: {}", message),
+            ErrorKind::WebSocket { message } => write!(f, "websocket error: {}", message),
+            ErrorKind::Unsupported => write!(f, "unsupported exchange"),
+        }
+    }
+}
+
+impl std::error::Error for ExchangeError {}
+
+/// `Retry-After` in seconds (the HTTP-date form isn't used by these venues)
+pub fn retry_after(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Certificate/handshake failures as reported by native-tls (OpenSSL,
+/// Secure Transport, SChannel) and rustls
+fn is_tls_failure(message: &str) -> bool {
+    let lower = message.to_lowercase();
+    ["certificate", "handshake", "ssl", "tls error"]
+        .iter()
+        .any(|needle| lower.contains(needle))
+}
+
+/// Strip scheme/host/query so errors and metrics carry just the path
+fn endpoint_path(url: &str) -> String {
+    match url::Url::parse(url) {
+        Ok(u) => u.path().to_string(),
+        Err(_) => url.to_string(),
+    }
+}
+
+/// reqwest's Display may hide the root cause (e.g. the DNS or TLS failure)
+fn error_chain(err: &dyn std::error::Error) -> String {
+    let mut msg = err.to_string();
+    let mut source = err.source();
+    while let Some(s) = source {
+        let part = s.to_string();
+        if !msg.contains(&part) {
+            msg.push_str(": ");
+            msg.push_str(&part);
+        }
+        source = s.source();
+    }
+    msg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn certificate_failures_get_their_own_kind() {
+        let err = ExchangeError::websocket(
+            "binance",
+            "wss://stream.binance.com:9443/ws/!ticker@arr",
+            "TLS error: invalid peer certificate: UnknownIssuer",
+        );
+        assert!(matches!(err.kind, ErrorKind::Tls { .. }));
+        assert!(!err.is_retryable());
+        assert_eq!(err.kind_label(), "tls");
+
+        let err = ExchangeError::websocket("binance", "", "connection reset by peer");
+        assert!(matches!(err.kind, ErrorKind::WebSocket { .. }));
+    }
+}
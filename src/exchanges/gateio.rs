@@ -0,0 +1,191 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use tracing::info;
+
+use super::book::{Levels, BOOK_DEPTH};
+use super::{
+    de, decimals_step, spot_pair, ExchangeError, HttpClient, Markets, OrderBook, OrderRules,
+};
+use crate::metrics;
+use crate::models::PairPrice;
+
+const PAIRS_URL: &str = "https://api.gateio.ws/api/v4/spot/currency_pairs";
+const TICKERS_URL: &str = "https://api.gateio.ws/api/v4/spot/tickers";
+const BOOK_URL: &str = "https://api.gateio.ws/api/v4/spot/order_book";
+
+/// One entry of `GET /api/v4/spot/currency_pairs`
+#[derive(Debug, Deserialize)]
+struct CurrencyPair {
+    id: String,
+    base: String,
+    quote: String,
+    trade_status: String,
+    /// Decimals of the base amount
+    #[serde(default)]
+    amount_precision: Option<u32>,
+    /// Decimals of the price
+    #[serde(default)]
+    precision: Option<u32>,
+    #[serde(default, deserialize_with = "de::opt_f64_str")]
+    min_base_amount: Option<f64>,
+    #[serde(default, deserialize_with = "de::opt_f64_str")]
+    min_quote_amount: Option<f64>,
+}
+
+/// One entry of `GET /api/v4/spot/tickers`
+#[derive(Debug, Deserialize)]
+struct Ticker {
+    currency_pair: String,
+    /// `""` for pairs that haven't traded yet
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    last: Option<f64>,
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    quote_volume: Option<f64>,
+}
+
+/// Tradable pairs from `/spot/currency_pairs`
+pub async fn load_markets(http: &HttpClient) -> Result<Markets, ExchangeError> {
+    let pairs: Vec<CurrencyPair> = http.get_json("gateio", PAIRS_URL).await?;
+    let listed = pairs.len();
+    let rules = order_rules(&pairs);
+    Ok(Markets::new(listed, tradable_pairs(pairs)).with_rules(rules))
+}
+
+/// ----------------- GATE.IO -----------------
+pub async fn fetch_gateio(
+    http: &HttpClient,
+    markets: &Markets,
+) -> Result<Vec<PairPrice>, ExchangeError> {
+    info!("fetching gateio (REST)");
+
+    let tickers: Vec<Ticker> = http.get_json("gateio", TICKERS_URL).await?;
+    let ws_total = tickers.len();
+    let out = pairs_from_tickers(&markets.symbols, tickers);
+    let ws_skipped = ws_total - out.len();
+
+    info!(
+        "gateio: found_total={} info_skipped={} tickers_total={} tickers_skipped={} returned={}",
+        markets.listed,
+        markets.skipped(),
+        ws_total,
+        ws_skipped,
+        out.len()
+    );
+    metrics::record_pairs("gateio", "rest", out.len());
+    metrics::record_ingest("gateio", markets.listed, markets.skipped(), ws_skipped);
+
+    Ok(out)
+}
+
+/// Order rules of every tradable pair; Gate.io gives no step for a market
+/// buy's quote amount
+fn order_rules(pairs: &[CurrencyPair]) -> HashMap<String, OrderRules> {
+    pairs
+        .iter()
+        .filter(|p| p.trade_status == "tradable")
+        .map(|p| {
+            let rules = OrderRules {
+                qty_step: p.amount_precision.map(decimals_step),
+                quote_step: None,
+                tick: p.precision.map(decimals_step),
+                min_qty: p.min_base_amount,
+                min_notional: p.min_quote_amount,
+            };
+            (p.id.to_uppercase(), rules.normalized())
+        })
+        .collect()
+}
+
+/// pair id -> (base, quote) for tradable pairs
+fn tradable_pairs(pairs: Vec<CurrencyPair>) -> HashMap<String, (String, String)> {
+    pairs
+        .into_iter()
+        .filter(|p| p.trade_status == "tradable")
+        .map(|p| {
+            (
+                p.id.to_uppercase(),
+                (p.base.to_uppercase(), p.quote.to_uppercase()),
+            )
+        })
+        .collect()
+}
+
+fn pairs_from_tickers(
+    tradable: &HashMap<String, (String, String)>,
+    tickers: Vec<Ticker>,
+) -> Vec<PairPrice> {
+    tickers
+        .into_iter()
+        .filter_map(|t| {
+            let (base, quote) = tradable.get(&t.currency_pair.to_uppercase())?;
+            spot_pair(&t.currency_pair, base, quote, t.last?, t.quote_volume?)
+        })
+        .collect()
+}
+
+/// Top of one pair's book, `pair` like `BTC_USDT`
+pub async fn fetch_book(http: &HttpClient, pair: &str) -> Result<OrderBook, ExchangeError> {
+    let url = format!("{}?currency_pair={}&limit={}", BOOK_URL, pair, BOOK_DEPTH);
+    Ok(http.get_json::<Levels>("gateio", &url).await?.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURRENCY_PAIRS: &str = include_str!("../../tests/fixtures/gateio/currency_pairs.json");
+    const TICKERS: &str = include_str!("../../tests/fixtures/gateio/tickers.json");
+    const ORDER_BOOK: &str = include_str!("../../tests/fixtures/gateio/order_book.json");
+
+    #[test]
+    fn currency_pairs_keep_only_tradable() {
+        let pairs: Vec<CurrencyPair> = serde_json::from_str(CURRENCY_PAIRS).unwrap();
+        assert_eq!(pairs.len(), 4);
+
+        let tradable = tradable_pairs(pairs);
+        assert_eq!(tradable.len(), 3);
+        assert!(!tradable.contains_key("NEW_USDT"));
+    }
+
+    #[test]
+    fn precisions_and_minimums_become_order_rules() {
+        let pairs: Vec<CurrencyPair> = serde_json::from_str(CURRENCY_PAIRS).unwrap();
+        let rules = order_rules(&pairs);
+        assert_eq!(rules.len(), 3);
+        let btc = rules["BTC_USDT"];
+        assert!((btc.qty_step.unwrap() - 0.000001).abs() < 1e-15);
+        assert!((btc.tick.unwrap() - 0.1).abs() < 1e-15);
+        assert_eq!(btc.quote_step, None);
+        assert_eq!(btc.min_qty, Some(0.00001));
+        assert_eq!(btc.min_notional, Some(3.0));
+    }
+
+    #[test]
+    fn tickers_produce_pairs_and_skip_untraded() {
+        let tradable = tradable_pairs(serde_json::from_str(CURRENCY_PAIRS).unwrap());
+        let tickers: Vec<Ticker> = serde_json::from_str(TICKERS).unwrap();
+        assert_eq!(tickers.len(), 4);
+        assert_eq!(tickers[3].last, None, "empty last price decodes as None");
+
+        let pairs = pairs_from_tickers(&tradable, tickers);
+        assert_eq!(pairs.len(), 3);
+        let eth_btc = pairs
+            .iter()
+            .find(|p| p.base == "ETH" && p.quote == "BTC")
+            .unwrap();
+        assert_eq!(eth_btc.price, 0.054131);
+    }
+
+    #[test]
+    fn malformed_volume_fails_loudly() {
+        let changed = TICKERS.replace("\"211.4418\"", "\"-\"");
+        assert!(serde_json::from_str::<Vec<Ticker>>(&changed).is_err());
+    }
+
+    #[test]
+    fn order_book_decodes() {
+        let book = OrderBook::from(serde_json::from_str::<Levels>(ORDER_BOOK).unwrap());
+        assert_eq!(book.asks[0], (0.054135, 1.2));
+        assert_eq!(book.bids[0], (0.054127, 0.8));
+    }
+}
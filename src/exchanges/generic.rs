@@ -0,0 +1,343 @@
+//! Venues described in config instead of code.
+//!
+//! Most spot venues follow the same shape: one GET for the symbol listing,
+//! one GET for all tickers, then a field mapping. A [`VenueSpec`] captures
+//! that mapping (URLs plus dot-separated JSON paths such as `data.list` or
+//! `c.0`), so a simple venue can be added in TOML or JSON without a new
+//! `fetch_*` function. Venues needing signing, WebSockets or pagination
+//! still get a hand-written adapter.
+
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use tracing::info;
+
+use super::{spot_pair, ExchangeError, HttpClient, Markets};
+use crate::metrics;
+use crate::models::PairPrice;
+
+/// Generic venues by name
+pub type GenericVenues = HashMap<String, VenueSpec>;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VenueSpec {
+    /// Exchange name used in requests, config keys and metrics
+    pub name: String,
+    /// Taker fee per leg (%) when `exchanges.fees` has no entry
+    pub fee: Option<f64>,
+    pub symbols_url: String,
+    pub tickers_url: String,
+    pub symbols: SymbolFields,
+    pub tickers: TickerFields,
+}
+
+/// Where the listing keeps each symbol and its assets
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SymbolFields {
+    /// Path to the array of entries (or an object keyed by symbol); empty
+    /// for the response body itself
+    #[serde(default)]
+    pub list: String,
+    /// Path to the symbol inside an entry; omit when `list` is keyed by symbol
+    pub symbol: Option<String>,
+    pub base: Option<String>,
+    pub quote: Option<String>,
+    /// Split the symbol on this when `base`/`quote` aren't given (`BTC-USDT`)
+    pub delimiter: Option<String>,
+    pub status: Option<String>,
+    /// Statuses that mean tradable; compared as strings, so `true` or `1` work
+    #[serde(default)]
+    pub status_values: Vec<String>,
+}
+
+/// Where the ticker response keeps each field
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TickerFields {
+    #[serde(default)]
+    pub list: String,
+    pub symbol: Option<String>,
+    /// Last trade price
+    pub price: String,
+    pub bid: Option<String>,
+    pub ask: Option<String>,
+    /// 24h volume
+    pub volume: String,
+    /// Unit of `volume`; base volumes are converted with the last price
+    #[serde(default)]
+    pub volume_in: VolumeUnit,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VolumeUnit {
+    #[default]
+    Quote,
+    Base,
+}
+
+impl VenueSpec {
+    /// Problems with the description itself; empty when usable
+    pub fn problems(&self) -> Vec<String> {
+        let at = |field: &str| format!("generic venue `{}`: {}", self.name, field);
+        let mut problems = Vec::new();
+        for (field, raw) in [
+            ("symbols_url", &self.symbols_url),
+            ("tickers_url", &self.tickers_url),
+        ] {
+            match url::Url::parse(raw) {
+                Ok(u) if matches!(u.scheme(), "http" | "https") => {}
+                _ => problems.push(at(&format!("{} is not an http(s) URL", field))),
+            }
+        }
+        let s = &self.symbols;
+        if s.base.is_some() != s.quote.is_some() {
+            problems.push(at("symbols.base and symbols.quote go together"));
+        }
+        if s.base.is_none() && s.delimiter.as_deref().unwrap_or("").is_empty() {
+            problems.push(at("symbols needs base/quote paths or a delimiter"));
+        }
+        if s.status.is_some() && s.status_values.is_empty() {
+            problems.push(at("symbols.status_values must list the tradable statuses"));
+        }
+        if self.tickers.price.is_empty() || self.tickers.volume.is_empty() {
+            problems.push(at("tickers.price and tickers.volume are required"));
+        }
+        if let Some(fee) = self.fee {
+            if !fee.is_finite() || !(0.0..5.0).contains(&fee) {
+                problems.push(at(&format!("fee must be in [0, 5) percent, got {}", fee)));
+            }
+        }
+        problems
+    }
+}
+
+/// Follow a dot-separated path; numeric segments index arrays
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    if path.is_empty() {
+        return Some(value);
+    }
+    path.split('.').try_fold(value, |v, key| match v {
+        Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+        _ => v.get(key),
+    })
+}
+
+/// Strings as-is, other scalars in their JSON spelling
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Null => None,
+        other => Some(other.to_string()),
+    }
+}
+
+/// Missing, `null` and `""` are `None`; anything else must be a number
+fn number(entry: &Value, path: &str) -> Result<Option<f64>, String> {
+    match lookup(entry, path) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::Number(n)) => Ok(n.as_f64()),
+        Some(Value::String(s)) if s.trim().is_empty() => Ok(None),
+        Some(Value::String(s)) => s
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("`{}` is not a decimal: `{}`", path, s)),
+        Some(other) => Err(format!("`{}` is not a decimal: {}", path, other)),
+    }
+}
+
+/// (symbol, entry) pairs of the list at `list`
+fn entries<'a>(
+    body: &'a Value,
+    list: &str,
+    symbol: Option<&str>,
+) -> Result<Vec<(String, &'a Value)>, String> {
+    let key = |entry: &Value| -> Result<String, String> {
+        let path = symbol.ok_or("entries in an array need a `symbol` path")?;
+        lookup(entry, path)
+            .and_then(text)
+            .ok_or_else(|| format!("entry without `{}`", path))
+    };
+    match lookup(body, list) {
+        Some(Value::Array(items)) => items.iter().map(|entry| Ok((key(entry)?, entry))).collect(),
+        Some(Value::Object(map)) => map
+            .iter()
+            .map(|(name, entry)| match symbol {
+                Some(_) => Ok((key(entry)?, entry)),
+                None => Ok((name.clone(), entry)),
+            })
+            .collect(),
+        _ => Err(format!("no array or object at `{}`", list)),
+    }
+}
+
+fn markets(spec: &SymbolFields, body: &Value) -> Result<Markets, String> {
+    let listed = entries(body, &spec.list, spec.symbol.as_deref())?;
+    let total = listed.len();
+    let mut map = HashMap::new();
+    for (symbol, entry) in listed {
+        if let Some(path) = &spec.status {
+            let status = lookup(entry, path).and_then(text).unwrap_or_default();
+            if !spec.status_values.contains(&status) {
+                continue;
+            }
+        }
+        let assets = match (&spec.base, &spec.quote, &spec.delimiter) {
+            (Some(base), Some(quote), _) => lookup(entry, base)
+                .and_then(text)
+                .zip(lookup(entry, quote).and_then(text)),
+            (_, _, Some(delimiter)) => symbol
+                .split_once(delimiter.as_str())
+                .map(|(b, q)| (b.to_string(), q.to_string())),
+            _ => None,
+        };
+        if let Some((base, quote)) = assets {
+            map.insert(
+                symbol.to_uppercase(),
+                (base.to_uppercase(), quote.to_uppercase()),
+            );
+        }
+    }
+    Ok(Markets::new(total, map))
+}
+
+fn pairs_from_tickers(
+    spec: &TickerFields,
+    tradable: &HashMap<String, (String, String)>,
+    body: &Value,
+) -> Result<(usize, Vec<PairPrice>), String> {
+    let tickers = entries(body, &spec.list, spec.symbol.as_deref())?;
+    let total = tickers.len();
+    let mut out = Vec::new();
+    for (symbol, entry) in tickers {
+        let Some((base, quote)) = tradable.get(&symbol.to_uppercase()) else {
+            continue;
+        };
+        let (Some(price), Some(volume)) =
+            (number(entry, &spec.price)?, number(entry, &spec.volume)?)
+        else {
+            continue;
+        };
+        let liquidity = match spec.volume_in {
+            VolumeUnit::Quote => volume,
+            VolumeUnit::Base => volume * price,
+        };
+        let optional = |path: &Option<String>| match path {
+            Some(p) => number(entry, p),
+            None => Ok(None),
+        };
+        let (bid, ask) = (optional(&spec.bid)?, optional(&spec.ask)?);
+        if let Some(pair) = spot_pair(&symbol, base, quote, price, liquidity) {
+            out.push(PairPrice { bid, ask, ..pair });
+        }
+    }
+    Ok((total, out))
+}
+
+/// Symbol listing at `symbols_url`
+pub async fn load_markets(http: &HttpClient, spec: &VenueSpec) -> Result<Markets, ExchangeError> {
+    let body: Value = http.get_json(&spec.name, &spec.symbols_url).await?;
+    markets(&spec.symbols, &body)
+        .map_err(|e| ExchangeError::decode(&spec.name, &spec.symbols_url, e, ""))
+}
+
+/// ----------------- Generic (REST, described in config) -----------------
+pub async fn fetch_generic(
+    http: &HttpClient,
+    spec: &VenueSpec,
+    markets: &Markets,
+) -> Result<Vec<PairPrice>, ExchangeError> {
+    let name = spec.name.as_str();
+    info!("fetching {} (generic REST)", name);
+
+    let body: Value = http.get_json(name, &spec.tickers_url).await?;
+    let (ws_total, out) = pairs_from_tickers(&spec.tickers, &markets.symbols, &body)
+        .map_err(|e| ExchangeError::decode(name, &spec.tickers_url, e, ""))?;
+    let ws_skipped = ws_total - out.len();
+
+    info!(
+        "{}: found_total={} info_skipped={} tickers_total={} tickers_skipped={} returned={}",
+        name,
+        markets.listed,
+        markets.skipped(),
+        ws_total,
+        ws_skipped,
+        out.len()
+    );
+    metrics::record_pairs(name, "rest", out.len());
+    metrics::record_ingest(name, markets.listed, markets.skipped(), ws_skipped);
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = include_str!("../../tests/fixtures/generic/venue.toml");
+    const SYMBOLS: &str = include_str!("../../tests/fixtures/generic/symbols.json");
+    const TICKERS: &str = include_str!("../../tests/fixtures/generic/tickers.json");
+
+    fn spec() -> VenueSpec {
+        toml::from_str(SPEC).unwrap()
+    }
+
+    #[test]
+    fn spec_from_toml_is_valid() {
+        let spec = spec();
+        assert_eq!(spec.name, "examplex");
+        assert!(spec.problems().is_empty(), "{:?}", spec.problems());
+        assert_eq!(spec.tickers.volume_in, VolumeUnit::Base);
+    }
+
+    #[test]
+    fn listing_filters_status_and_splits_symbols() {
+        let body: Value = serde_json::from_str(SYMBOLS).unwrap();
+        let markets = markets(&spec().symbols, &body).unwrap();
+        assert_eq!(markets.listed, 4);
+        assert_eq!(markets.skipped(), 1);
+        assert_eq!(
+            markets.symbols["ETH_BTC"],
+            ("ETH".to_string(), "BTC".to_string())
+        );
+        assert!(!markets.symbols.contains_key("OLD_USDT"));
+    }
+
+    #[test]
+    fn tickers_keyed_by_symbol_map_to_pairs() {
+        let spec = spec();
+        let symbols: Value = serde_json::from_str(SYMBOLS).unwrap();
+        let map = markets(&spec.symbols, &symbols).unwrap().symbols;
+        let body: Value = serde_json::from_str(TICKERS).unwrap();
+
+        let (total, pairs) = pairs_from_tickers(&spec.tickers, &map, &body).unwrap();
+        assert_eq!(total, 4);
+        assert_eq!(pairs.len(), 3);
+        let eth_btc = pairs.iter().find(|p| p.base == "ETH").unwrap();
+        assert_eq!(eth_btc.price, 0.05413);
+        assert_eq!(eth_btc.bid, Some(0.05412));
+        // base volume converted with the last price
+        assert!((eth_btc.liquidity - 4120.0 * 0.05413).abs() < 1e-9);
+    }
+
+    #[test]
+    fn malformed_number_fails_loudly() {
+        let spec = spec();
+        let symbols: Value = serde_json::from_str(SYMBOLS).unwrap();
+        let map = markets(&spec.symbols, &symbols).unwrap().symbols;
+        let changed = TICKERS.replace("\"0.05413\"", "\"n/a\"");
+        let body: Value = serde_json::from_str(&changed).unwrap();
+        assert!(pairs_from_tickers(&spec.tickers, &map, &body).is_err());
+    }
+
+    #[test]
+    fn incomplete_spec_is_rejected() {
+        let mut spec = spec();
+        spec.symbols.delimiter = None;
+        spec.symbols_url = "ftp://example.com".to_string();
+        assert_eq!(spec.problems().len(), 2);
+    }
+}
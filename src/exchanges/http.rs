@@ -0,0 +1,417 @@
+use rand::Rng;
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use tokio::time::Duration;
+use tracing::{info, warn};
+
+use super::budget::{pool, request_weight, RateBudgets};
+use super::{ErrorKind, ExchangeError, SUPPORTED_EXCHANGES};
+use crate::config::{load_ca_bundle, HttpConfig};
+use crate::metrics;
+
+/// When and how long to back off between attempts
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt (0 = fail fast)
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    /// Cap for the exponential delay; a `Retry-After` longer than this is
+    /// not waited out, the error is returned instead
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Delay before retry number `attempt` (0-based), or `None` to give up.
+    ///
+    /// Server-provided `Retry-After` wins; otherwise the delay doubles each
+    /// attempt up to `max_delay`, with half of it randomized so that venues
+    /// aren't hit by every scan in lockstep.
+    pub fn delay(&self, attempt: u32, err: &ExchangeError) -> Option<Duration> {
+        if attempt >= self.max_retries || !err.is_retryable() {
+            return None;
+        }
+        if let Some(wait) = err.retry_after() {
+            return (wait <= self.max_delay).then_some(wait);
+        }
+        let exp = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        let half = exp / 2;
+        Some(half + rand::thread_rng().gen_range(Duration::ZERO..=half))
+    }
+}
+
+/// The HTTP client couldn't be built from the `[http]` config
+#[derive(Debug)]
+pub enum ClientError {
+    CaBundle(String),
+    Proxy {
+        exchange: String,
+        source: reqwest::Error,
+    },
+    Build {
+        exchange: String,
+        source: reqwest::Error,
+    },
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::CaBundle(e) => write!(f, "CA bundle: {}", e),
+            ClientError::Proxy { exchange, source } => {
+                write!(f, "{}: invalid proxy: {}", exchange, source)
+            }
+            ClientError::Build { exchange, source } => {
+                write!(f, "{}: cannot build HTTP client: {}", exchange, source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+/// Connection-pooled HTTP clients shared by every fetch.
+///
+/// One `reqwest::Client` per venue so per-exchange transport settings stay
+/// isolated; all of them reuse connections across scans. Generic venues
+/// without an `[http.venues]` entry share one more client.
+pub struct HttpClient {
+    clients: HashMap<String, Client>,
+    shared: Client,
+    retry: RetryPolicy,
+    budgets: Arc<RateBudgets>,
+    /// Percent of each venue's limit left unused as a safety margin
+    budget_headroom_percent: u32,
+    /// Longest wait for a budget window to reset before failing the request
+    budget_max_wait: Duration,
+    /// Venues with a proxy or private CA configured
+    custom_transport: Vec<String>,
+    /// `http.ca_bundle` is set, so every venue has a private CA
+    shared_ca: bool,
+}
+
+impl HttpClient {
+    pub fn new(cfg: &HttpConfig, budgets: Arc<RateBudgets>) -> Result<Self, ClientError> {
+        let shared_roots = match &cfg.ca_bundle {
+            Some(path) => load_ca_bundle(path).map_err(ClientError::CaBundle)?,
+            None => Vec::new(),
+        };
+
+        // certificate verification stays on for every venue; a private CA
+        // is added to the trusted roots, never a replacement for checking
+        let builder = || {
+            let mut builder = Client::builder()
+                .connect_timeout(Duration::from_secs(cfg.connect_timeout_secs))
+                .timeout(Duration::from_secs(cfg.request_timeout_secs))
+                .pool_idle_timeout(Duration::from_secs(cfg.pool_idle_timeout_secs))
+                .tcp_keepalive(Duration::from_secs(cfg.tcp_keepalive_secs))
+                .gzip(true);
+            for cert in &shared_roots {
+                builder = builder.add_root_certificate(cert.clone());
+            }
+            builder
+        };
+
+        let mut clients = HashMap::new();
+        let mut custom_transport = Vec::new();
+        let names = SUPPORTED_EXCHANGES
+            .iter()
+            .map(|ex| ex.to_string())
+            .chain(cfg.venues.keys().cloned());
+        for exchange in names {
+            if clients.contains_key(&exchange) {
+                continue;
+            }
+            let mut builder = builder();
+            if let Some(venue) = cfg.venues.get(&exchange) {
+                if let Some(path) = &venue.ca_bundle {
+                    for cert in load_ca_bundle(path).map_err(ClientError::CaBundle)? {
+                        builder = builder.add_root_certificate(cert);
+                    }
+                }
+                if let Some(proxy) = &venue.proxy {
+                    let proxy =
+                        reqwest::Proxy::all(proxy).map_err(|source| ClientError::Proxy {
+                            exchange: exchange.clone(),
+                            source,
+                        })?;
+                    builder = builder.proxy(proxy);
+                }
+                if venue.proxy.is_some() || venue.ca_bundle.is_some() {
+                    custom_transport.push(exchange.clone());
+                }
+            }
+
+            let client = builder.build().map_err(|source| ClientError::Build {
+                exchange: exchange.clone(),
+                source,
+            })?;
+            clients.insert(exchange, client);
+        }
+        let shared = builder().build().map_err(|source| ClientError::Build {
+            exchange: "generic".to_string(),
+            source,
+        })?;
+        Ok(Self {
+            clients,
+            shared,
+            retry: RetryPolicy {
+                max_retries: cfg.max_retries,
+                base_delay: Duration::from_millis(cfg.backoff_base_ms),
+                max_delay: Duration::from_millis(cfg.backoff_max_ms),
+            },
+            budgets,
+            budget_headroom_percent: cfg.budget_headroom_percent,
+            budget_max_wait: Duration::from_secs(cfg.budget_max_wait_secs),
+            custom_transport,
+            shared_ca: cfg.ca_bundle.is_some(),
+        })
+    }
+
+    /// Whether `exchange` goes through a proxy or private CA. Non-HTTP
+    /// transports (WebSockets) can't honor those and must be skipped.
+    pub fn has_custom_transport(&self, exchange: &str) -> bool {
+        self.shared_ca || self.custom_transport.iter().any(|ex| ex == exchange)
+    }
+
+    /// Transport of `exchange`, proxy and CA settings included. Signed
+    /// trading calls use it directly: they must never be retried and have
+    /// their own venue limits.
+    pub fn client(&self, exchange: &str) -> &Client {
+        self.clients.get(exchange).unwrap_or(&self.shared)
+    }
+
+    /// GET `url` and decode the JSON body, retrying transient failures
+    pub async fn get_json<T: DeserializeOwned>(
+        &self,
+        exchange: &str,
+        url: &str,
+    ) -> Result<T, ExchangeError> {
+        let mut attempt = 0;
+        loop {
+            // the budget already waited as long as allowed, so its refusal
+            // isn't retried
+            self.reserve(exchange, url).await?;
+            let err = match self.get_once(exchange, url).await {
+                Ok(body) => return Ok(body),
+                Err(e) => e,
+            };
+            let Some(delay) = self.retry.delay(attempt, &err) else {
+                return Err(err);
+            };
+            attempt += 1;
+            warn!(
+                "{} (attempt {}/{}), retrying in {}ms",
+                err,
+                attempt,
+                self.retry.max_retries + 1,
+                delay.as_millis()
+            );
+            metrics::HTTP_RETRIES
+                .with_label_values(&[exchange, err.kind_label()])
+                .inc();
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Wait until the venue's budget has room for this request
+    async fn reserve(&self, exchange: &str, url: &str) -> Result<(), ExchangeError> {
+        let pool = pool(exchange, url);
+        let weight = request_weight(pool, url);
+        loop {
+            match self
+                .budgets
+                .reserve(pool, weight, self.budget_headroom_percent)
+            {
+                Ok(()) => return Ok(()),
+                Err(Some(wait)) if wait <= self.budget_max_wait => {
+                    info!(
+                        "{}: request budget nearly spent, holding {} for {}ms",
+                        pool,
+                        url,
+                        wait.as_millis()
+                    );
+                    tokio::time::sleep(wait).await;
+                }
+                // too long a wait, or a weight that never fits the budget
+                Err(wait) => {
+                    return Err(ExchangeError::new(
+                        exchange,
+                        url,
+                        ErrorKind::RateLimited {
+                            retry_after_secs: wait.map(|w| w.as_secs_f64().ceil() as u64),
+                        },
+                    ))
+                }
+            }
+        }
+    }
+
+    /// One attempt, classifying every failure
+    async fn get_once<T: DeserializeOwned>(
+        &self,
+        exchange: &str,
+        url: &str,
+    ) -> Result<T, ExchangeError> {
+        let resp = self
+            .client(exchange)
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| ExchangeError::from_reqwest(exchange, url, e))?;
+        let status = resp.status();
+        let headers = resp.headers().clone();
+        self.budgets.observe(pool(exchange, url), &headers);
+        let body = resp
+            .text()
+            .await
+            .map_err(|e| ExchangeError::from_reqwest(exchange, url, e))?;
+
+        if !status.is_success() {
+            let err = ExchangeError::from_status(exchange, url, status, &headers, &body);
+            if let ErrorKind::RateLimited { .. } = err.kind {
+                self.budgets
+                    .exhausted(pool(exchange, url), err.retry_after());
+            }
+            return Err(err);
+        }
+        serde_json::from_str(&body).map_err(|e| ExchangeError::decode(exchange, url, e, &body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(2),
+        }
+    }
+
+    fn error(kind: ErrorKind) -> ExchangeError {
+        ExchangeError::new("binance", "https://api.binance.com/api/v3/ping", kind)
+    }
+
+    #[test]
+    fn backoff_grows_with_jitter_and_stops() {
+        let p = policy();
+        let err = error(ErrorKind::Timeout);
+        for attempt in 0..3 {
+            let exp = Duration::from_millis(100 * 2u64.pow(attempt));
+            let d = p.delay(attempt, &err).unwrap();
+            assert!(d >= exp / 2 && d <= exp, "attempt {}: {:?}", attempt, d);
+        }
+        assert_eq!(p.delay(3, &err), None, "retries exhausted");
+    }
+
+    #[test]
+    fn retry_after_is_honored_unless_too_long() {
+        let p = policy();
+        let short = error(ErrorKind::RateLimited {
+            retry_after_secs: Some(1),
+        });
+        assert_eq!(p.delay(0, &short), Some(Duration::from_secs(1)));
+
+        let long = error(ErrorKind::RateLimited {
+            retry_after_secs: Some(60),
+        });
+        assert_eq!(p.delay(0, &long), None);
+    }
+
+    #[test]
+    fn permanent_errors_are_not_retried() {
+        let p = policy();
+        assert_eq!(p.delay(0, &error(ErrorKind::GeoBlocked)), None);
+        assert_eq!(
+            p.delay(
+                0,
+                &error(ErrorKind::Http {
+                    status: 404,
+                    body: String::new()
+                })
+            ),
+            None
+        );
+    }
+
+    /// Answers the first connection with a 502, every later one with `{"ok":true}`
+    async fn flaky_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut served = 0;
+            while let Ok((mut sock, _)) = listener.accept().await {
+                let mut buf = [0u8; 1024];
+                let _ = sock.read(&mut buf).await;
+                let (status, body) = if served == 0 {
+                    ("502 Bad Gateway", "upstream down")
+                } else {
+                    ("200 OK", r#"{"ok":true}"#)
+                };
+                served += 1;
+                let resp = format!(
+                    "HTTP/1.1 {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = sock.write_all(resp.as_bytes()).await;
+            }
+        });
+        format!("http://{}/ping", addr)
+    }
+
+    #[tokio::test]
+    async fn transient_failure_is_retried() {
+        let url = flaky_server().await;
+        let http = HttpClient::new(
+            &HttpConfig {
+                backoff_base_ms: 1,
+                ..HttpConfig::default()
+            },
+            Arc::default(),
+        )
+        .unwrap();
+
+        let body: serde_json::Value = http.get_json("binance", &url).await.unwrap();
+        assert_eq!(body["ok"], true);
+    }
+
+    #[test]
+    fn proxy_marks_venue_as_custom_transport() {
+        let mut cfg = HttpConfig::default();
+        cfg.venues.insert(
+            "gateio".to_string(),
+            crate::config::VenueHttpConfig {
+                proxy: Some("socks5h://127.0.0.1:1080".to_string()),
+                ca_bundle: None,
+            },
+        );
+        let http = HttpClient::new(&cfg, Arc::default()).unwrap();
+        assert!(http.has_custom_transport("gateio"));
+        assert!(!http.has_custom_transport("binance"));
+    }
+
+    #[test]
+    fn unreadable_ca_bundle_is_rejected() {
+        let path = std::env::temp_dir().join(format!("arb-not-a-ca-{}.pem", std::process::id()));
+        std::fs::write(&path, "not a certificate").unwrap();
+        let cfg = HttpConfig {
+            ca_bundle: Some(path.clone()),
+            ..HttpConfig::default()
+        };
+        let err = HttpClient::new(&cfg, Arc::default()).err().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(err, ClientError::CaBundle(_)), "{}", err);
+    }
+}
@@ -0,0 +1,466 @@
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use std::collections::HashMap;
+use tokio::time::{timeout, Duration};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{info, warn};
+
+use super::book::{Levels, BOOK_DEPTH};
+use super::{de, spot_pair, ErrorKind, ExchangeError, HttpClient, Markets, OrderBook};
+use crate::health::FeedRegistry;
+use crate::metrics;
+use crate::models::PairPrice;
+
+const PAIRS_URL: &str = "https://api.kraken.com/0/public/AssetPairs";
+const TICKER_URL: &str = "https://api.kraken.com/0/public/Ticker";
+const DEPTH_URL: &str = "https://api.kraken.com/0/public/Depth";
+const STREAM_URL: &str = "wss://ws.kraken.com/v2";
+
+/// Starter-tier spot taker fee, percent per leg
+pub const TAKER_FEE_PERCENT: f64 = 0.40;
+
+/// Symbols per `subscribe` request
+const SUBSCRIBE_BATCH: usize = 100;
+
+/// Every Kraken REST response: `{ "error": [...], "result": ... }`
+#[derive(Debug, Deserialize)]
+struct Envelope<T> {
+    #[serde(default)]
+    error: Vec<String>,
+    result: Option<T>,
+}
+
+impl<T> Envelope<T> {
+    /// Errors come back as `"EAPI:Rate limit exceeded"`-style strings on a 200
+    fn into_result(self, url: &str) -> Result<T, ExchangeError> {
+        let Some(first) = self.error.into_iter().next() else {
+            return self
+                .result
+                .ok_or_else(|| ExchangeError::decode("kraken", url, "missing `result`", ""));
+        };
+        let kind = if first.contains("Rate limit") || first.contains("Too many requests") {
+            ErrorKind::RateLimited {
+                retry_after_secs: None,
+            }
+        } else {
+            let (code, message) = first.split_once(':').unwrap_or(("", first.as_str()));
+            ErrorKind::Api {
+                code: code.to_string(),
+                message: message.to_string(),
+            }
+        };
+        Err(ExchangeError::new("kraken", url, kind))
+    }
+}
+
+/// One value of `GET /0/public/AssetPairs`, keyed by pair name (`XXBTZUSD`)
+#[derive(Debug, Deserialize)]
+struct AssetPair {
+    /// Legacy asset codes (`XXBT`, `ZUSD`)
+    base: String,
+    quote: String,
+    /// `XBT/USD`: the names Kraken shows users
+    wsname: Option<String>,
+    /// `online`, `cancel_only`, `post_only`, `limit_only`, `reduce_only`, ...
+    status: String,
+}
+
+/// One value of `GET /0/public/Ticker`
+#[derive(Debug, Deserialize)]
+struct Ticker {
+    /// `[price, whole lot volume, lot volume]`
+    #[serde(rename = "a", deserialize_with = "de::f64_str_seq")]
+    ask: Vec<f64>,
+    #[serde(rename = "b", deserialize_with = "de::f64_str_seq")]
+    bid: Vec<f64>,
+    /// `[price, lot volume]` of the last trade
+    #[serde(rename = "c", deserialize_with = "de::f64_str_seq")]
+    last: Vec<f64>,
+    /// Base volume `[today, last 24h]`
+    #[serde(rename = "v", deserialize_with = "de::f64_str_seq")]
+    volume: Vec<f64>,
+    /// Volume-weighted average price `[today, last 24h]`
+    #[serde(rename = "p", deserialize_with = "de::f64_str_seq")]
+    vwap: Vec<f64>,
+}
+
+/// One entry of a WS v2 `ticker` push
+#[derive(Debug, Deserialize)]
+struct WsTicker {
+    /// `BTC/USD`; v2 already uses `BTC` / `DOGE` instead of `XBT` / `XDG`
+    symbol: String,
+    #[serde(deserialize_with = "de::f64_str")]
+    bid: f64,
+    #[serde(deserialize_with = "de::f64_str")]
+    ask: f64,
+    #[serde(deserialize_with = "de::f64_str")]
+    last: f64,
+    /// 24h base volume
+    #[serde(deserialize_with = "de::f64_str")]
+    volume: f64,
+    /// 24h volume-weighted average price
+    #[serde(deserialize_with = "de::f64_str")]
+    vwap: f64,
+}
+
+/// Any WS v2 frame: channel pushes (`ticker`, `heartbeat`, `status`) or
+/// method responses
+#[derive(Debug, Deserialize)]
+struct WsFrame {
+    #[serde(default)]
+    channel: String,
+    #[serde(default)]
+    success: Option<bool>,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    data: serde_json::Value,
+}
+
+/// Pairs with `status: online`, keyed by pair name. Assets keep the
+/// `wsname` spelling (`XBT`, `USD`); the registry maps them to canonical
+/// names, including the `XXBT`/`ZUSD` codes used when `wsname` is missing.
+pub async fn load_markets(http: &HttpClient) -> Result<Markets, ExchangeError> {
+    let pairs = http
+        .get_json::<Envelope<HashMap<String, AssetPair>>>("kraken", PAIRS_URL)
+        .await?
+        .into_result(PAIRS_URL)?;
+    let listed = pairs.len();
+    Ok(Markets::new(listed, symbol_map(pairs)))
+}
+
+fn symbol_map(pairs: HashMap<String, AssetPair>) -> HashMap<String, (String, String)> {
+    pairs
+        .into_iter()
+        .filter(|(_, p)| p.status == "online")
+        .map(|(name, p)| {
+            let assets = p
+                .wsname
+                .as_deref()
+                .and_then(|ws| ws.split_once('/'))
+                .map(|(b, q)| (b.to_string(), q.to_string()))
+                .unwrap_or((p.base, p.quote));
+            (
+                name.to_uppercase(),
+                (assets.0.to_uppercase(), assets.1.to_uppercase()),
+            )
+        })
+        .collect()
+}
+
+/// WS v2 renamed the two assets whose v1 names differ from the ISO-style ones
+fn v2_asset(asset: &str) -> &str {
+    match asset {
+        "XBT" => "BTC",
+        "XDG" => "DOGE",
+        other => other,
+    }
+}
+
+/// WS v2 symbol (`BTC/USD`) -> pair name (`XXBTZUSD`)
+fn ws_symbols(symbol_map: &HashMap<String, (String, String)>) -> HashMap<String, String> {
+    symbol_map
+        .iter()
+        .map(|(name, (base, quote))| {
+            (
+                format!("{}/{}", v2_asset(base), v2_asset(quote)),
+                name.clone(),
+            )
+        })
+        .collect()
+}
+
+impl Ticker {
+    fn to_pair(&self, name: &str, base: &str, quote: &str) -> Option<PairPrice> {
+        // 24h quote turnover ~ base volume * vwap
+        let liquidity = self.volume.get(1)? * self.vwap.get(1)?;
+        let pair = spot_pair(name, base, quote, *self.last.first()?, liquidity)?;
+        Some(PairPrice {
+            bid: self.bid.first().copied(),
+            ask: self.ask.first().copied(),
+            ..pair
+        })
+    }
+}
+
+impl WsTicker {
+    /// `name` is the REST pair name, not the v2 symbol
+    fn to_pair(&self, name: &str, base: &str, quote: &str) -> Option<PairPrice> {
+        let pair = spot_pair(name, base, quote, self.last, self.volume * self.vwap)?;
+        Some(PairPrice {
+            bid: Some(self.bid),
+            ask: Some(self.ask),
+            ..pair
+        })
+    }
+}
+
+/// ---------------- Kraken (Hybrid: WS v2 + REST fallback) ----------------
+pub async fn fetch_kraken(
+    http: &HttpClient,
+    markets: &Markets,
+    feeds: &FeedRegistry,
+    ws_window: Duration,
+) -> Result<Vec<PairPrice>, ExchangeError> {
+    info!(
+        "fetching kraken via websocket ({}s) + REST backfill",
+        ws_window.as_secs()
+    );
+    let symbol_map = &markets.symbols;
+
+    // the stream can't go through a configured proxy / private CA
+    let mut pairs: HashMap<String, PairPrice> = HashMap::new();
+    let (ws_messages, ws_decode_errors) = if http.has_custom_transport("kraken") {
+        info!("kraken: proxy or CA bundle configured, skipping websocket");
+        (0, 0)
+    } else {
+        sample_ws(symbol_map, feeds, ws_window, &mut pairs).await?
+    };
+    let ws_pairs = pairs.len();
+
+    let tickers = http
+        .get_json::<Envelope<HashMap<String, Ticker>>>("kraken", TICKER_URL)
+        .await?
+        .into_result(TICKER_URL)?;
+
+    let mut rest_added = 0usize;
+    let mut rest_skipped = 0usize;
+    for (name, t) in tickers {
+        let name = name.to_uppercase();
+        if pairs.contains_key(&name) {
+            continue;
+        }
+        match symbol_map
+            .get(&name)
+            .and_then(|(base, quote)| t.to_pair(&name, base, quote))
+        {
+            Some(p) => {
+                pairs.insert(name, p);
+                rest_added += 1;
+            }
+            None => rest_skipped += 1,
+        }
+    }
+
+    info!(
+        "kraken: total_symbols={} ws_messages={} ws_decode_errors={} ws_pairs={} rest_added={} final_pairs={}",
+        symbol_map.len(),
+        ws_messages,
+        ws_decode_errors,
+        ws_pairs,
+        rest_added,
+        pairs.len()
+    );
+    metrics::WS_MESSAGES
+        .with_label_values(&["kraken"])
+        .inc_by(ws_messages as u64);
+    metrics::record_pairs("kraken", "ws", ws_pairs);
+    metrics::record_pairs("kraken", "rest", rest_added);
+    metrics::record_ingest("kraken", markets.listed, markets.skipped(), rest_skipped);
+
+    Ok(pairs.into_values().collect())
+}
+
+/// `subscribe` requests for the `ticker` channel of every WS v2 symbol
+fn subscribe_frames(ws_symbols: &HashMap<String, String>) -> Vec<String> {
+    let mut symbols: Vec<&String> = ws_symbols.keys().collect();
+    symbols.sort();
+    symbols
+        .chunks(SUBSCRIBE_BATCH)
+        .map(|chunk| {
+            serde_json::json!({
+                "method": "subscribe",
+                "params": { "channel": "ticker", "symbol": chunk },
+            })
+            .to_string()
+        })
+        .collect()
+}
+
+/// Subscribe to the v2 `ticker` channel and collect snapshots and updates
+/// for `ws_window`; returns (frames received, frames that didn't decode)
+async fn sample_ws(
+    symbol_map: &HashMap<String, (String, String)>,
+    feeds: &FeedRegistry,
+    ws_window: Duration,
+    pairs: &mut HashMap<String, PairPrice>,
+) -> Result<(usize, usize), ExchangeError> {
+    let (ws_stream, _) = match connect_async(STREAM_URL).await {
+        Ok(conn) => conn,
+        Err(e) => {
+            feeds.set_ws_connected("kraken", false);
+            return Err(ExchangeError::websocket("kraken", STREAM_URL, e));
+        }
+    };
+    feeds.set_ws_connected("kraken", true);
+    let (mut write, mut read) = ws_stream.split();
+
+    let by_ws_symbol = ws_symbols(symbol_map);
+    for frame in subscribe_frames(&by_ws_symbol) {
+        if let Err(e) = write.send(Message::Text(frame)).await {
+            feeds.set_ws_connected("kraken", false);
+            return Err(ExchangeError::websocket("kraken", STREAM_URL, e));
+        }
+    }
+
+    let mut ws_messages = 0usize;
+    let mut ws_decode_errors = 0usize;
+
+    // the server heartbeats every second, so no client ping is needed
+    let ws_result = timeout(ws_window, async {
+        while let Some(msg) = read.next().await {
+            ws_messages += 1;
+            let Ok(msg) = msg else { continue };
+            let Ok(text) = msg.to_text() else { continue };
+            if text.is_empty() {
+                continue;
+            }
+            let decoded = serde_json::from_str::<WsFrame>(text).and_then(|frame| {
+                if frame.success == Some(false) {
+                    warn!(
+                        "kraken ws request failed: {}",
+                        frame.error.unwrap_or_default()
+                    );
+                }
+                if frame.channel != "ticker" {
+                    return Ok(Vec::new());
+                }
+                serde_json::from_value::<Vec<WsTicker>>(frame.data)
+            });
+            match decoded {
+                Ok(list) => {
+                    for t in list {
+                        let Some(name) = by_ws_symbol.get(&t.symbol) else {
+                            continue;
+                        };
+                        let (base, quote) = &symbol_map[name];
+                        if let Some(p) = t.to_pair(name, base, quote) {
+                            pairs.insert(name.clone(), p);
+                        }
+                    }
+                }
+                Err(e) => {
+                    if ws_decode_errors == 0 {
+                        warn!("kraken ws frame did not match ticker schema: {}", e);
+                    }
+                    ws_decode_errors += 1;
+                }
+            }
+        }
+    })
+    .await;
+
+    if ws_result.is_err() {
+        info!(
+            "kraken ws collection stopped after {}s timeout",
+            ws_window.as_secs()
+        );
+    }
+
+    Ok((ws_messages, ws_decode_errors))
+}
+
+/// Top of one pair's book, `pair` as in AssetPairs (`XXBTZUSD`). The result
+/// is keyed by pair name, which may differ in case from the request.
+pub async fn fetch_book(http: &HttpClient, pair: &str) -> Result<OrderBook, ExchangeError> {
+    let url = format!("{}?pair={}&count={}", DEPTH_URL, pair, BOOK_DEPTH);
+    http.get_json::<Envelope<HashMap<String, Levels>>>("kraken", &url)
+        .await?
+        .into_result(&url)?
+        .into_values()
+        .next()
+        .map(OrderBook::from)
+        .ok_or_else(|| ExchangeError::decode("kraken", &url, "empty `result`", ""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::AssetRegistry;
+
+    const ASSET_PAIRS: &str = include_str!("../../tests/fixtures/kraken/asset_pairs.json");
+    const TICKER: &str = include_str!("../../tests/fixtures/kraken/ticker.json");
+    const WS_TICKER: &str = include_str!("../../tests/fixtures/kraken/ws_ticker.json");
+    const RATE_LIMITED: &str = include_str!("../../tests/fixtures/kraken/error_rate_limit.json");
+    const DEPTH: &str = include_str!("../../tests/fixtures/kraken/depth.json");
+
+    fn markets() -> HashMap<String, (String, String)> {
+        let pairs = serde_json::from_str::<Envelope<HashMap<String, AssetPair>>>(ASSET_PAIRS)
+            .unwrap()
+            .into_result(PAIRS_URL)
+            .unwrap();
+        symbol_map(pairs)
+    }
+
+    #[test]
+    fn asset_pairs_keep_online_with_ws_names() {
+        let map = markets();
+        assert_eq!(map.len(), 4);
+        assert_eq!(map["XETHXXBT"], ("ETH".to_string(), "XBT".to_string()));
+        assert!(!map.contains_key("OLDUSD"), "cancel_only is dropped");
+
+        let ws = ws_symbols(&map);
+        assert_eq!(ws["BTC/USD"], "XXBTZUSD");
+        assert_eq!(ws["DOGE/EUR"], "XDGEUR");
+    }
+
+    #[test]
+    fn rest_ticker_yields_canonical_pairs() {
+        let map = markets();
+        let tickers = serde_json::from_str::<Envelope<HashMap<String, Ticker>>>(TICKER)
+            .unwrap()
+            .into_result(TICKER_URL)
+            .unwrap();
+        let (base, quote) = &map["XXBTZUSD"];
+        let pair = tickers["XXBTZUSD"]
+            .to_pair("XXBTZUSD", base, quote)
+            .unwrap();
+        assert_eq!(pair.price, 112498.3);
+        assert_eq!(pair.bid, Some(112498.3));
+        assert_eq!(pair.ask, Some(112498.4));
+        assert!((pair.liquidity - 2801.95218836 * 112122.06511).abs() < 1e-3);
+
+        // no 24h volume, nothing to scan
+        assert!(tickers["OLDUSD"].to_pair("OLDUSD", "OLD", "USD").is_none());
+
+        let out = AssetRegistry::default().normalize("kraken", vec![pair]);
+        assert_eq!(
+            (out[0].base.as_str(), out[0].quote.as_str()),
+            ("BTC", "USD")
+        );
+        assert_eq!(out[0].native_base, "XBT");
+    }
+
+    #[test]
+    fn ws_v2_push_decodes() {
+        let frame: WsFrame = serde_json::from_str(WS_TICKER).unwrap();
+        assert_eq!(frame.channel, "ticker");
+        let list: Vec<WsTicker> = serde_json::from_value(frame.data).unwrap();
+        assert_eq!(list[0].symbol, "ETH/BTC");
+        let pair = list[0].to_pair("XETHXXBT", "ETH", "XBT").unwrap();
+        assert_eq!(pair.ask, Some(0.05416));
+
+        let heartbeat: WsFrame = serde_json::from_str(r#"{"channel":"heartbeat"}"#).unwrap();
+        assert_eq!(heartbeat.channel, "heartbeat");
+    }
+
+    #[test]
+    fn rate_limit_error_string_is_classified() {
+        let err = serde_json::from_str::<Envelope<HashMap<String, Ticker>>>(RATE_LIMITED)
+            .unwrap()
+            .into_result(TICKER_URL)
+            .unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::RateLimited { .. }));
+    }
+
+    #[test]
+    fn depth_ignores_level_timestamps() {
+        let mut result = serde_json::from_str::<Envelope<HashMap<String, Levels>>>(DEPTH)
+            .unwrap()
+            .into_result(DEPTH_URL)
+            .unwrap();
+        let book = OrderBook::from(result.remove("XXBTZUSD").unwrap());
+        assert_eq!(book.asks[0], (112498.4, 0.325));
+        assert_eq!(book.bids[2], (112495.0, 1.106));
+    }
+}
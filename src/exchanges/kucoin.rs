@@ -0,0 +1,268 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use tracing::info;
+
+use super::book::Levels;
+use super::{de, spot_pair, ErrorKind, ExchangeError, HttpClient, Markets, OrderBook, OrderRules};
+use crate::metrics;
+use crate::models::PairPrice;
+
+const SYMBOLS_URL: &str = "https://api.kucoin.com/api/v1/symbols";
+const TICKERS_URL: &str = "https://api.kucoin.com/api/v1/market/allTickers";
+/// Fixed 20 levels per side
+const BOOK_URL: &str = "https://api.kucoin.com/api/v1/market/orderbook/level2_20";
+
+/// Every KuCoin REST response: `{ "code": "200000", "data": ... }`
+#[derive(Debug, Deserialize)]
+struct Envelope<T> {
+    code: String,
+    #[serde(default)]
+    msg: Option<String>,
+    data: Option<T>,
+}
+
+impl<T> Envelope<T> {
+    /// KuCoin wraps errors in a 200 with `code != "200000"`
+    fn into_data(self, url: &str) -> Result<T, ExchangeError> {
+        let kind = match self.code.as_str() {
+            "200000" => {
+                return self
+                    .data
+                    .ok_or_else(|| ExchangeError::decode("kucoin", url, "missing `data`", ""))
+            }
+            "429000" => ErrorKind::RateLimited {
+                retry_after_secs: None,
+            },
+            code => ErrorKind::Api {
+                code: code.to_string(),
+                message: self.msg.unwrap_or_default(),
+            },
+        };
+        Err(ExchangeError::new("kucoin", url, kind))
+    }
+}
+
+/// One entry of `GET /api/v1/symbols`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SymbolInfo {
+    symbol: String,
+    base_currency: String,
+    quote_currency: String,
+    enable_trading: bool,
+    #[serde(default, deserialize_with = "de::opt_f64_str")]
+    base_increment: Option<f64>,
+    #[serde(default, deserialize_with = "de::opt_f64_str")]
+    quote_increment: Option<f64>,
+    #[serde(default, deserialize_with = "de::opt_f64_str")]
+    price_increment: Option<f64>,
+    #[serde(default, deserialize_with = "de::opt_f64_str")]
+    base_min_size: Option<f64>,
+    /// Minimum order value; `null` for some symbols, which fall back to
+    /// `quoteMinSize`
+    #[serde(default, deserialize_with = "de::opt_f64_str")]
+    min_funds: Option<f64>,
+    #[serde(default, deserialize_with = "de::opt_f64_str")]
+    quote_min_size: Option<f64>,
+}
+
+/// `data` of `GET /api/v1/market/allTickers`
+#[derive(Debug, Deserialize)]
+struct AllTickers {
+    ticker: Vec<Ticker>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Ticker {
+    symbol: String,
+    /// `null` for pairs that haven't traded in 24h
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    last: Option<f64>,
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    vol_value: Option<f64>,
+}
+
+/// Symbols with trading enabled, from `/api/v1/symbols`
+pub async fn load_markets(http: &HttpClient) -> Result<Markets, ExchangeError> {
+    let symbols = http
+        .get_json::<Envelope<Vec<SymbolInfo>>>("kucoin", SYMBOLS_URL)
+        .await?
+        .into_data(SYMBOLS_URL)?;
+    let listed = symbols.len();
+    let rules = order_rules(&symbols);
+    Ok(Markets::new(listed, tradable_symbols(symbols)).with_rules(rules))
+}
+
+/// ---------------- KuCoin ----------------
+pub async fn fetch_kucoin(
+    http: &HttpClient,
+    markets: &Markets,
+) -> Result<Vec<PairPrice>, ExchangeError> {
+    info!("fetching kucoin (REST)");
+
+    // fetch tickers
+    let tickers = http
+        .get_json::<Envelope<AllTickers>>("kucoin", TICKERS_URL)
+        .await?
+        .into_data(TICKERS_URL)?
+        .ticker;
+
+    let ws_total = tickers.len();
+    let out = pairs_from_tickers(&markets.symbols, tickers);
+    let ws_skipped = ws_total - out.len();
+
+    info!(
+        "kucoin: found_total={} info_skipped={} tickers_total={} tickers_skipped={} returned={}",
+        markets.listed,
+        markets.skipped(),
+        ws_total,
+        ws_skipped,
+        out.len()
+    );
+    metrics::record_pairs("kucoin", "rest", out.len());
+    metrics::record_ingest("kucoin", markets.listed, markets.skipped(), ws_skipped);
+
+    Ok(out)
+}
+
+/// Order rules of every symbol with trading enabled
+fn order_rules(symbols: &[SymbolInfo]) -> HashMap<String, OrderRules> {
+    symbols
+        .iter()
+        .filter(|s| s.enable_trading)
+        .map(|s| {
+            let rules = OrderRules {
+                qty_step: s.base_increment,
+                quote_step: s.quote_increment,
+                tick: s.price_increment,
+                min_qty: s.base_min_size,
+                min_notional: s.min_funds.or(s.quote_min_size),
+            };
+            (s.symbol.clone(), rules.normalized())
+        })
+        .collect()
+}
+
+/// symbol -> (base, quote) for symbols with trading enabled
+fn tradable_symbols(symbols: Vec<SymbolInfo>) -> HashMap<String, (String, String)> {
+    symbols
+        .into_iter()
+        .filter(|s| s.enable_trading)
+        .map(|s| {
+            (
+                s.symbol,
+                (
+                    s.base_currency.to_uppercase(),
+                    s.quote_currency.to_uppercase(),
+                ),
+            )
+        })
+        .collect()
+}
+
+fn pairs_from_tickers(
+    tradable: &HashMap<String, (String, String)>,
+    tickers: Vec<Ticker>,
+) -> Vec<PairPrice> {
+    tickers
+        .into_iter()
+        .filter_map(|t| {
+            let (base, quote) = tradable.get(&t.symbol)?;
+            spot_pair(&t.symbol, base, quote, t.last?, t.vol_value?)
+        })
+        .collect()
+}
+
+/// Top 20 levels of one market's book, `symbol` like `BTC-USDT`
+pub async fn fetch_book(http: &HttpClient, symbol: &str) -> Result<OrderBook, ExchangeError> {
+    let url = format!("{}?symbol={}", BOOK_URL, symbol);
+    Ok(http
+        .get_json::<Envelope<Levels>>("kucoin", &url)
+        .await?
+        .into_data(&url)?
+        .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SYMBOLS: &str = include_str!("../../tests/fixtures/kucoin/symbols.json");
+    const ALL_TICKERS: &str = include_str!("../../tests/fixtures/kucoin/all_tickers.json");
+    const ERROR: &str = include_str!("../../tests/fixtures/kucoin/error_429000.json");
+    const LEVEL2_20: &str = include_str!("../../tests/fixtures/kucoin/level2_20.json");
+
+    #[test]
+    fn symbols_and_tickers_produce_pairs() {
+        let symbols = serde_json::from_str::<Envelope<Vec<SymbolInfo>>>(SYMBOLS)
+            .unwrap()
+            .into_data(SYMBOLS_URL)
+            .unwrap();
+        let tradable = tradable_symbols(symbols);
+        assert_eq!(tradable.len(), 3);
+        assert!(!tradable.contains_key("DEAD-USDT"));
+
+        let tickers = serde_json::from_str::<Envelope<AllTickers>>(ALL_TICKERS)
+            .unwrap()
+            .into_data(TICKERS_URL)
+            .unwrap()
+            .ticker;
+        assert_eq!(tickers.len(), 4);
+        assert_eq!(tickers[3].last, None, "null last price decodes as None");
+
+        let pairs = pairs_from_tickers(&tradable, tickers);
+        assert_eq!(pairs.len(), 3);
+        let eth_btc = pairs
+            .iter()
+            .find(|p| p.base == "ETH" && p.quote == "BTC")
+            .unwrap();
+        assert_eq!(eth_btc.price, 0.05413);
+    }
+
+    #[test]
+    fn increments_and_minimums_become_order_rules() {
+        let symbols = serde_json::from_str::<Envelope<Vec<SymbolInfo>>>(SYMBOLS)
+            .unwrap()
+            .into_data(SYMBOLS_URL)
+            .unwrap();
+        let rules = order_rules(&symbols);
+        assert_eq!(rules.len(), 3);
+        assert_eq!(
+            rules["BTC-USDT"],
+            OrderRules {
+                qty_step: Some(0.00000001),
+                quote_step: Some(0.000001),
+                tick: Some(0.1),
+                min_qty: Some(0.00001),
+                min_notional: Some(0.1),
+            }
+        );
+    }
+
+    #[test]
+    fn error_code_maps_to_rate_limited() {
+        let err = serde_json::from_str::<Envelope<AllTickers>>(ERROR)
+            .unwrap()
+            .into_data(TICKERS_URL)
+            .unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::RateLimited { .. }));
+    }
+
+    #[test]
+    fn missing_vol_value_field_fails_loudly() {
+        let changed = ALL_TICKERS.replace("\"volValue\"", "\"quoteVolume\"");
+        assert!(serde_json::from_str::<Envelope<AllTickers>>(&changed).is_err());
+    }
+
+    #[test]
+    fn level2_book_decodes() {
+        let levels = serde_json::from_str::<Envelope<Levels>>(LEVEL2_20)
+            .unwrap()
+            .into_data(BOOK_URL)
+            .unwrap();
+        let book = OrderBook::from(levels);
+        assert_eq!(book.bids[0], (6021.45, 4.2031));
+        assert_eq!(book.asks[2], (6022.5, 7.0001));
+    }
+}
@@ -0,0 +1,274 @@
+//! Symbol listings (exchangeInfo, /symbols, instruments-info,
+//! currency_pairs) cached per exchange.
+//!
+//! Listings change rarely but are among the heaviest calls each venue
+//! offers, so they are refetched only once older than the exchange's TTL.
+//! Every fetched listing is also written to disk and read back on the first
+//! lookup after a restart.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+use super::{load_markets, ExchangeError, GenericVenues, HttpClient};
+
+/// Size and price rules a venue applies to orders on one symbol; `None`
+/// where it sets none
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct OrderRules {
+    /// Base quantities are a multiple of this
+    pub qty_step: Option<f64>,
+    /// Quote amounts of market buys are a multiple of this
+    pub quote_step: Option<f64>,
+    /// Prices are a multiple of this
+    pub tick: Option<f64>,
+    /// Smallest base quantity
+    pub min_qty: Option<f64>,
+    /// Smallest order value, in the quote asset
+    pub min_notional: Option<f64>,
+}
+
+impl OrderRules {
+    /// Drop the zeros venues send for limits they don't set
+    pub fn normalized(self) -> Self {
+        let set = |v: Option<f64>| v.filter(|v| *v > 0.0);
+        Self {
+            qty_step: set(self.qty_step),
+            quote_step: set(self.quote_step),
+            tick: set(self.tick),
+            min_qty: set(self.min_qty),
+            min_notional: set(self.min_notional),
+        }
+    }
+}
+
+/// Step of a venue that gives precision as a number of decimal places
+pub fn decimals_step(places: u32) -> f64 {
+    10f64.powi(-(places as i32))
+}
+
+/// Tradable symbols of one exchange
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Markets {
+    /// Unix seconds of the download
+    pub fetched_at: u64,
+    /// Entries in the venue's listing before filtering
+    pub listed: usize,
+    /// Venue symbol -> (base, quote)
+    pub symbols: HashMap<String, (String, String)>,
+    /// Venue symbol -> order rules, for the venues orders are sent to
+    #[serde(default)]
+    pub rules: HashMap<String, OrderRules>,
+}
+
+impl Markets {
+    pub fn new(listed: usize, symbols: HashMap<String, (String, String)>) -> Self {
+        Self {
+            fetched_at: unix_now(),
+            listed,
+            symbols,
+            rules: HashMap::new(),
+        }
+    }
+
+    pub fn with_rules(mut self, rules: HashMap<String, OrderRules>) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(unix_now().saturating_sub(self.fetched_at))
+    }
+
+    /// Listing entries that were not tradable or not wanted
+    pub fn skipped(&self) -> usize {
+        self.listed.saturating_sub(self.symbols.len())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MetadataCache {
+    /// Where listings are persisted; `None` keeps them in memory only
+    dir: Option<PathBuf>,
+    entries: Mutex<HashMap<String, Arc<Markets>>>,
+}
+
+impl MetadataCache {
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        Self {
+            dir,
+            entries: Mutex::default(),
+        }
+    }
+
+    /// Listing for `exchange`, downloaded again once older than `ttl`. A
+    /// stale listing is still served when the venue can't be reached.
+    pub async fn get(
+        &self,
+        exchange: &str,
+        ttl: Duration,
+        http: &HttpClient,
+        generic: &GenericVenues,
+    ) -> Result<Arc<Markets>, ExchangeError> {
+        let cached = self.cached(exchange);
+        if let Some(markets) = cached.as_ref().filter(|m| m.age() < ttl) {
+            return Ok(markets.clone());
+        }
+        match self.refresh(exchange, http, generic).await {
+            Ok(markets) => Ok(markets),
+            Err(e) => match cached {
+                Some(stale) => {
+                    warn!(
+                        "{}: metadata refresh failed, using listing from {}s ago: {}",
+                        exchange,
+                        stale.age().as_secs(),
+                        e
+                    );
+                    Ok(stale)
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    /// Download the listing now, regardless of its age
+    pub async fn refresh(
+        &self,
+        exchange: &str,
+        http: &HttpClient,
+        generic: &GenericVenues,
+    ) -> Result<Arc<Markets>, ExchangeError> {
+        let markets = load_markets(exchange, http, generic).await?;
+        info!(
+            "{}: metadata refreshed ({} of {} symbols tradable)",
+            exchange,
+            markets.symbols.len(),
+            markets.listed
+        );
+        Ok(self.insert(exchange, markets).await)
+    }
+
+    /// The listing already loaded, without downloading it
+    pub fn listing(&self, exchange: &str) -> Option<Arc<Markets>> {
+        self.cached(exchange)
+    }
+
+    async fn insert(&self, exchange: &str, markets: Markets) -> Arc<Markets> {
+        if let Some(dir) = &self.dir {
+            if let Err(e) = persist(dir, exchange, &markets).await {
+                warn!(
+                    "{}: cannot persist metadata to {}: {}",
+                    exchange,
+                    dir.display(),
+                    e
+                );
+            }
+        }
+        let markets = Arc::new(markets);
+        self.entries
+            .lock()
+            .expect("metadata cache poisoned")
+            .insert(exchange.to_string(), markets.clone());
+        markets
+    }
+
+    /// In-memory entry, falling back to the on-disk copy after a restart
+    fn cached(&self, exchange: &str) -> Option<Arc<Markets>> {
+        let mut entries = self.entries.lock().expect("metadata cache poisoned");
+        if let Some(markets) = entries.get(exchange) {
+            return Some(markets.clone());
+        }
+        let path = file_for(self.dir.as_ref()?, exchange);
+        let raw = std::fs::read(&path).ok()?;
+        match serde_json::from_slice::<Markets>(&raw) {
+            Ok(markets) => {
+                info!(
+                    "{}: loaded metadata from {} ({}s old)",
+                    exchange,
+                    path.display(),
+                    markets.age().as_secs()
+                );
+                let markets = Arc::new(markets);
+                entries.insert(exchange.to_string(), markets.clone());
+                Some(markets)
+            }
+            Err(e) => {
+                warn!(
+                    "{}: ignoring unreadable {}: {}",
+                    exchange,
+                    path.display(),
+                    e
+                );
+                None
+            }
+        }
+    }
+}
+
+fn file_for(dir: &Path, exchange: &str) -> PathBuf {
+    dir.join(format!("{}.json", exchange))
+}
+
+/// Write via a temp file so a crash never leaves a truncated listing behind
+async fn persist(dir: &Path, exchange: &str, markets: &Markets) -> std::io::Result<()> {
+    tokio::fs::create_dir_all(dir).await?;
+    let path = file_for(dir, exchange);
+    let tmp = path.with_extension("json.tmp");
+    tokio::fs::write(&tmp, serde_json::to_vec(markets)?).await?;
+    tokio::fs::rename(&tmp, &path).await
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn listing_survives_a_restart() {
+        let dir = std::env::temp_dir().join(format!("arb-metadata-{}", std::process::id()));
+        let symbols =
+            HashMap::from([("ETHBTC".to_string(), ("ETH".to_string(), "BTC".to_string()))]);
+
+        let cache = MetadataCache::new(Some(dir.clone()));
+        cache.insert("binance", Markets::new(2, symbols)).await;
+
+        let restarted = MetadataCache::new(Some(dir.clone()));
+        let markets = restarted.cached("binance").unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(markets.listed, 2);
+        assert_eq!(markets.skipped(), 1);
+        assert_eq!(markets.symbols["ETHBTC"].1, "BTC");
+        assert!(restarted.cached("kucoin").is_none());
+    }
+
+    #[tokio::test]
+    async fn fresh_listing_is_served_without_network() {
+        let cache = MetadataCache::new(None);
+        cache.insert("bybit", Markets::new(0, HashMap::new())).await;
+
+        // any download would spend request weight
+        let budgets = Arc::new(crate::exchanges::RateBudgets::default());
+        let http = HttpClient::new(&Default::default(), budgets.clone()).unwrap();
+        let markets = cache
+            .get(
+                "bybit",
+                Duration::from_secs(60),
+                &http,
+                &GenericVenues::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(markets.listed, 0);
+        assert!(budgets.snapshot().is_empty());
+    }
+}
@@ -0,0 +1,171 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use tracing::info;
+
+use super::book::{Levels, BOOK_DEPTH};
+use super::{de, spot_pair, ExchangeError, HttpClient, Markets, OrderBook};
+use crate::metrics;
+use crate::models::PairPrice;
+
+const INFO_URL: &str = "https://api.mexc.com/api/v3/exchangeInfo";
+const TICKER_URL: &str = "https://api.mexc.com/api/v3/ticker/24hr";
+const DEPTH_URL: &str = "https://api.mexc.com/api/v3/depth";
+
+/// Default spot taker fee, percent per leg (makers pay nothing)
+pub const TAKER_FEE_PERCENT: f64 = 0.05;
+
+/// `GET /api/v3/exchangeInfo`
+#[derive(Debug, Deserialize)]
+struct ExchangeInfo {
+    symbols: Vec<SymbolInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SymbolInfo {
+    symbol: String,
+    /// `1` online, `2` paused, `3` offline (older responses: `ENABLED`)
+    status: String,
+    base_asset: String,
+    quote_asset: String,
+    /// `false` for symbols that can't be traded through the API
+    #[serde(default)]
+    is_spot_trading_allowed: bool,
+}
+
+/// One entry of `GET /api/v3/ticker/24hr`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Ticker24h {
+    symbol: String,
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    last_price: Option<f64>,
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    quote_volume: Option<f64>,
+    /// `null` when that side of the book is empty
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    bid_price: Option<f64>,
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    ask_price: Option<f64>,
+}
+
+/// Online symbols open to API trading, from exchangeInfo
+pub async fn load_markets(http: &HttpClient) -> Result<Markets, ExchangeError> {
+    let info: ExchangeInfo = http.get_json("mexc", INFO_URL).await?;
+    let listed = info.symbols.len();
+    Ok(Markets::new(listed, tradable_symbols(info)))
+}
+
+/// ----------------- MEXC -----------------
+pub async fn fetch_mexc(
+    http: &HttpClient,
+    markets: &Markets,
+) -> Result<Vec<PairPrice>, ExchangeError> {
+    info!("fetching mexc (REST)");
+
+    let tickers: Vec<Ticker24h> = http.get_json("mexc", TICKER_URL).await?;
+    let ws_total = tickers.len();
+    let out = pairs_from_tickers(&markets.symbols, tickers);
+    let ws_skipped = ws_total - out.len();
+
+    info!(
+        "mexc: found_total={} info_skipped={} tickers_total={} tickers_skipped={} returned={}",
+        markets.listed,
+        markets.skipped(),
+        ws_total,
+        ws_skipped,
+        out.len()
+    );
+    metrics::record_pairs("mexc", "rest", out.len());
+    metrics::record_ingest("mexc", markets.listed, markets.skipped(), ws_skipped);
+
+    Ok(out)
+}
+
+/// symbol -> (base, quote) for online symbols that allow API spot trading
+fn tradable_symbols(info: ExchangeInfo) -> HashMap<String, (String, String)> {
+    info.symbols
+        .into_iter()
+        .filter(|s| matches!(s.status.as_str(), "1" | "ENABLED") && s.is_spot_trading_allowed)
+        .map(|s| {
+            (
+                s.symbol.to_uppercase(),
+                (s.base_asset.to_uppercase(), s.quote_asset.to_uppercase()),
+            )
+        })
+        .collect()
+}
+
+fn pairs_from_tickers(
+    tradable: &HashMap<String, (String, String)>,
+    tickers: Vec<Ticker24h>,
+) -> Vec<PairPrice> {
+    tickers
+        .into_iter()
+        .filter_map(|t| {
+            let (base, quote) = tradable.get(&t.symbol.to_uppercase())?;
+            let pair = spot_pair(&t.symbol, base, quote, t.last_price?, t.quote_volume?)?;
+            Some(PairPrice {
+                bid: t.bid_price,
+                ask: t.ask_price,
+                ..pair
+            })
+        })
+        .collect()
+}
+
+/// Top of one market's book, `symbol` as in exchangeInfo
+pub async fn fetch_book(http: &HttpClient, symbol: &str) -> Result<OrderBook, ExchangeError> {
+    let url = format!("{}?symbol={}&limit={}", DEPTH_URL, symbol, BOOK_DEPTH);
+    Ok(http.get_json::<Levels>("mexc", &url).await?.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXCHANGE_INFO: &str = include_str!("../../tests/fixtures/mexc/exchange_info.json");
+    const TICKER_24HR: &str = include_str!("../../tests/fixtures/mexc/ticker_24hr.json");
+    const DEPTH: &str = include_str!("../../tests/fixtures/mexc/depth.json");
+
+    #[test]
+    fn exchange_info_keeps_online_api_tradable_symbols() {
+        let info: ExchangeInfo = serde_json::from_str(EXCHANGE_INFO).unwrap();
+        assert_eq!(info.symbols.len(), 5);
+
+        let tradable = tradable_symbols(info);
+        assert_eq!(tradable.len(), 3);
+        assert!(!tradable.contains_key("PAUSEUSDT"), "paused is dropped");
+        assert!(
+            !tradable.contains_key("APIOFFUSDT"),
+            "no API trading is dropped"
+        );
+    }
+
+    #[test]
+    fn tickers_produce_pairs_with_book() {
+        let tradable = tradable_symbols(serde_json::from_str(EXCHANGE_INFO).unwrap());
+        let tickers: Vec<Ticker24h> = serde_json::from_str(TICKER_24HR).unwrap();
+        assert_eq!(tickers.len(), 4);
+        assert_eq!(tickers[3].bid_price, None, "null bid decodes as None");
+
+        let pairs = pairs_from_tickers(&tradable, tickers);
+        assert_eq!(pairs.len(), 3);
+        let btc = pairs.iter().find(|p| p.base == "BTC").unwrap();
+        assert_eq!(btc.liquidity, 812_233_101.2);
+        assert_eq!(btc.bid, Some(112498.3));
+    }
+
+    #[test]
+    fn malformed_price_is_an_error_not_zero() {
+        let changed = TICKER_24HR.replace("\"0.05413\"", "\"n/a\"");
+        assert!(serde_json::from_str::<Vec<Ticker24h>>(&changed).is_err());
+    }
+
+    #[test]
+    fn depth_decodes_both_sides() {
+        let book = OrderBook::from(serde_json::from_str::<Levels>(DEPTH).unwrap());
+        assert_eq!(book.bids[0], (112498.12, 0.412305));
+        assert_eq!(book.asks.len(), 3);
+    }
+}
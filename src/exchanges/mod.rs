@@ -0,0 +1,266 @@
+mod binance;
+mod bitget;
+mod book;
+mod budget;
+mod bybit;
+mod coinbase;
+pub(crate) mod de;
+mod error;
+mod gateio;
+mod generic;
+mod http;
+mod kraken;
+mod kucoin;
+mod metadata;
+mod mexc;
+mod okx;
+
+pub use book::OrderBook;
+pub use budget::RateBudgets;
+pub use error::{ErrorKind, ExchangeError};
+pub use generic::{GenericVenues, VenueSpec};
+pub use http::{ClientError, HttpClient};
+pub use metadata::{decimals_step, Markets, MetadataCache, OrderRules};
+
+use crate::assets::AssetRegistry;
+use crate::health::FeedRegistry;
+use crate::metrics;
+use crate::models::{PairPrice, PerpQuote};
+use std::sync::Arc;
+use tokio::time::{Duration, Instant};
+
+/// A spot pair worth scanning: drops rows with no price or no turnover.
+/// Assets keep the venue's spelling until the dispatcher normalizes them.
+fn spot_pair(
+    symbol: &str,
+    base: &str,
+    quote: &str,
+    price: f64,
+    liquidity: f64,
+) -> Option<PairPrice> {
+    (price > 0.0 && liquidity > 0.0).then(|| PairPrice {
+        symbol: symbol.to_string(),
+        base: base.to_string(),
+        quote: quote.to_string(),
+        native_base: base.to_string(),
+        native_quote: quote.to_string(),
+        price,
+        bid: None,
+        ask: None,
+        is_spot: true,
+        liquidity,
+    })
+}
+
+/// A perpetual worth scanning: drops contracts with no mark price or no
+/// turnover. Funding defaults to the common 8h interval with no rate set.
+fn perp_quote(
+    base: &str,
+    quote: &str,
+    symbol: &str,
+    mark_price: f64,
+    liquidity: f64,
+) -> Option<PerpQuote> {
+    (mark_price > 0.0 && liquidity > 0.0).then(|| PerpQuote {
+        base: base.to_string(),
+        quote: quote.to_string(),
+        symbol: symbol.to_string(),
+        mark_price,
+        index_price: None,
+        funding_rate: 0.0,
+        next_funding_time: None,
+        funding_interval_hours: 8.0,
+        liquidity,
+    })
+}
+
+/// ---------------- Dispatcher ----------------
+/// Runtime knobs for one fetch, taken from the config at scan time
+#[derive(Debug, Clone, Copy)]
+pub struct FetchSettings {
+    /// How long the ticker WebSockets (Binance, OKX, Kraken, Coinbase) are
+    /// sampled
+    pub ws_window: Duration,
+    /// Symbol listings older than this are downloaded again
+    pub metadata_ttl: Duration,
+}
+
+/// Shared components every fetch uses, cloned out of the app state
+#[derive(Clone)]
+pub struct FetchContext {
+    pub http: Arc<HttpClient>,
+    pub feeds: Arc<FeedRegistry>,
+    pub metadata: Arc<MetadataCache>,
+    pub assets: Arc<AssetRegistry>,
+    /// Venues described in config, keyed by name
+    pub generic: Arc<GenericVenues>,
+}
+
+/// Canonical names of the built-in venues; generic ones come from config
+pub const SUPPORTED_EXCHANGES: &[&str] = &[
+    "binance", "kucoin", "bybit", "gateio", "okx", "kraken", "coinbase", "bitget", "mexc",
+];
+
+/// Map user-facing aliases (`gate`) onto the canonical exchange name
+pub fn canonical_exchange(name: &str) -> Option<&'static str> {
+    match name.to_lowercase().as_str() {
+        "binance" => Some("binance"),
+        "kucoin" => Some("kucoin"),
+        "bybit" => Some("bybit"),
+        "gate" | "gateio" => Some("gateio"),
+        "okx" | "okex" => Some("okx"),
+        "kraken" => Some("kraken"),
+        "coinbase" => Some("coinbase"),
+        "bitget" => Some("bitget"),
+        "mexc" => Some("mexc"),
+        _ => None,
+    }
+}
+
+/// Venues whose perpetual swaps are ingested for the basis scanner
+pub const PERP_EXCHANGES: &[&str] = &["binance", "bybit", "okx"];
+
+/// Published base-tier taker fee (%) on linear perpetuals
+pub fn perp_venue_fee(exchange: &str) -> Option<f64> {
+    match exchange {
+        "binance" => Some(binance::PERP_TAKER_FEE_PERCENT),
+        "bybit" => Some(bybit::PERP_TAKER_FEE_PERCENT),
+        "okx" => Some(okx::PERP_TAKER_FEE_PERCENT),
+        _ => None,
+    }
+}
+
+/// Published base-tier taker fee (%) for venues that differ from
+/// `exchanges.default_fee` or have been checked against their fee page
+pub fn venue_fee(exchange: &str) -> Option<f64> {
+    match exchange {
+        "okx" => Some(okx::TAKER_FEE_PERCENT),
+        "kraken" => Some(kraken::TAKER_FEE_PERCENT),
+        "coinbase" => Some(coinbase::TAKER_FEE_PERCENT),
+        "bitget" => Some(bitget::TAKER_FEE_PERCENT),
+        "mexc" => Some(mexc::TAKER_FEE_PERCENT),
+        _ => None,
+    }
+}
+
+/// Download and filter the symbol listing of `exchange` (canonical name)
+async fn load_markets(
+    exchange: &str,
+    http: &HttpClient,
+    generic: &GenericVenues,
+) -> Result<Markets, ExchangeError> {
+    match exchange {
+        "binance" => binance::load_markets(http).await,
+        "kucoin" => kucoin::load_markets(http).await,
+        "bybit" => bybit::load_markets(http).await,
+        "gateio" => gateio::load_markets(http).await,
+        "okx" => okx::load_markets(http).await,
+        "kraken" => kraken::load_markets(http).await,
+        "coinbase" => coinbase::load_markets(http).await,
+        "bitget" => bitget::load_markets(http).await,
+        "mexc" => mexc::load_markets(http).await,
+        _ => match generic.get(exchange) {
+            Some(spec) => generic::load_markets(http, spec).await,
+            None => Err(ExchangeError::unsupported(exchange)),
+        },
+    }
+}
+
+pub async fn fetch_exchange_data(
+    exchange: &str,
+    ctx: &FetchContext,
+    settings: &FetchSettings,
+) -> Result<Vec<PairPrice>, ExchangeError> {
+    let exchange = canonical_exchange(exchange)
+        .or_else(|| ctx.generic.get(exchange).map(|spec| spec.name.as_str()))
+        .ok_or_else(|| ExchangeError::unsupported(exchange))?;
+    let (http, feeds) = (&*ctx.http, &*ctx.feeds);
+    let started = Instant::now();
+    let result = match ctx
+        .metadata
+        .get(exchange, settings.metadata_ttl, http, &ctx.generic)
+        .await
+    {
+        Ok(markets) => match exchange {
+            "binance" => binance::fetch_binance(http, &markets, feeds, settings.ws_window).await,
+            "kucoin" => kucoin::fetch_kucoin(http, &markets).await,
+            "bybit" => bybit::fetch_bybit(http, &markets).await,
+            "gateio" => gateio::fetch_gateio(http, &markets).await,
+            "okx" => okx::fetch_okx(http, &markets, feeds, settings.ws_window).await,
+            "kraken" => kraken::fetch_kraken(http, &markets, feeds, settings.ws_window).await,
+            "coinbase" => coinbase::fetch_coinbase(http, &markets, feeds, settings.ws_window).await,
+            "bitget" => bitget::fetch_bitget(http, &markets).await,
+            "mexc" => mexc::fetch_mexc(http, &markets).await,
+            _ => generic::fetch_generic(http, &ctx.generic[exchange], &markets).await,
+        },
+        Err(e) => Err(e),
+    }
+    .map(|pairs| ctx.assets.normalize(exchange, pairs));
+
+    metrics::FETCH_DURATION
+        .with_label_values(&[exchange])
+        .observe(started.elapsed().as_secs_f64());
+    match &result {
+        Ok(_) => feeds.record_success(exchange),
+        Err(e) => {
+            metrics::FETCH_ERRORS
+                .with_label_values(&[exchange, e.kind_label()])
+                .inc();
+            feeds.record_failure(exchange, &e.to_string());
+        }
+    }
+
+    result
+}
+
+/// Linear perpetuals of `exchange` (canonical name) with mark price and
+/// funding. Not cached: funding moves between scans and each venue serves
+/// every contract in a few requests.
+pub async fn fetch_perp_data(
+    exchange: &str,
+    ctx: &FetchContext,
+) -> Result<Vec<PerpQuote>, ExchangeError> {
+    let http = &*ctx.http;
+    let result = match exchange {
+        "binance" => binance::fetch_binance_perps(http).await,
+        "bybit" => bybit::fetch_bybit_perps(http).await,
+        "okx" => okx::fetch_okx_perps(http).await,
+        _ => Err(ExchangeError::unsupported(exchange)),
+    }
+    .map(|perps| ctx.assets.normalize_perps(exchange, perps));
+
+    if let Err(e) = &result {
+        metrics::FETCH_ERRORS
+            .with_label_values(&[exchange, e.kind_label()])
+            .inc();
+    }
+    result
+}
+
+/// Current order book of one spot market; `symbol` as in
+/// [`PairPrice::symbol`]. Venues described in config have no book endpoint.
+pub async fn fetch_order_book(
+    exchange: &str,
+    symbol: &str,
+    http: &HttpClient,
+) -> Result<OrderBook, ExchangeError> {
+    let result = match exchange {
+        "binance" => binance::fetch_book(http, symbol).await,
+        "kucoin" => kucoin::fetch_book(http, symbol).await,
+        "bybit" => bybit::fetch_book(http, symbol).await,
+        "gateio" => gateio::fetch_book(http, symbol).await,
+        "okx" => okx::fetch_book(http, symbol).await,
+        "kraken" => kraken::fetch_book(http, symbol).await,
+        "coinbase" => coinbase::fetch_book(http, symbol).await,
+        "bitget" => bitget::fetch_book(http, symbol).await,
+        "mexc" => mexc::fetch_book(http, symbol).await,
+        _ => Err(ExchangeError::unsupported(exchange)),
+    };
+
+    if let Err(e) = &result {
+        metrics::FETCH_ERRORS
+            .with_label_values(&[exchange, e.kind_label()])
+            .inc();
+    }
+    result
+}
@@ -0,0 +1,625 @@
+use futures::future::join_all;
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use std::collections::HashMap;
+use tokio::time::{interval, timeout, Duration};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{info, warn};
+
+use super::book::{Levels, BOOK_DEPTH};
+use super::{de, perp_quote, spot_pair, ErrorKind, ExchangeError, HttpClient, Markets, OrderBook};
+use crate::health::FeedRegistry;
+use crate::metrics;
+use crate::models::{PairPrice, PerpQuote};
+
+const INSTRUMENTS_URL: &str = "https://www.okx.com/api/v5/public/instruments?instType=SPOT";
+const TICKERS_URL: &str = "https://www.okx.com/api/v5/market/tickers?instType=SPOT";
+const STREAM_URL: &str = "wss://ws.okx.com:8443/ws/v5/public";
+const BOOKS_URL: &str = "https://www.okx.com/api/v5/market/books";
+const SWAP_INSTRUMENTS_URL: &str = "https://www.okx.com/api/v5/public/instruments?instType=SWAP";
+const SWAP_TICKERS_URL: &str = "https://www.okx.com/api/v5/market/tickers?instType=SWAP";
+const MARK_PRICE_URL: &str = "https://www.okx.com/api/v5/public/mark-price?instType=SWAP";
+/// Index tickers are listed per quote currency
+const INDEX_URLS: &[&str] = &[
+    "https://www.okx.com/api/v5/market/index-tickers?quoteCcy=USDT",
+    "https://www.okx.com/api/v5/market/index-tickers?quoteCcy=USDC",
+];
+const FUNDING_URL: &str = "https://www.okx.com/api/v5/public/funding-rate?instId=";
+
+/// Funding is only served per contract, so just the most liquid swaps are
+/// asked; each request spends one unit of the 20 / 2s budget
+const FUNDING_CONTRACTS: usize = 30;
+
+/// Regular-user (Lv1) spot taker fee, percent per leg
+pub const TAKER_FEE_PERCENT: f64 = 0.10;
+
+/// Regular-user (Lv1) swap taker fee, percent per trade
+pub const PERP_TAKER_FEE_PERCENT: f64 = 0.05;
+
+/// Subscription args per `subscribe` frame; OKX caps a frame at 64 KiB
+const SUBSCRIBE_BATCH: usize = 100;
+
+/// The server drops connections idle for 30s
+const PING_EVERY: Duration = Duration::from_secs(20);
+
+/// Every OKX v5 REST response: `{ "code": "0", "msg": "", "data": [...] }`
+#[derive(Debug, Deserialize)]
+struct Envelope<T> {
+    code: String,
+    #[serde(default)]
+    msg: String,
+    #[serde(default = "Vec::new")]
+    data: Vec<T>,
+}
+
+impl<T> Envelope<T> {
+    /// OKX reports failures via `code != "0"`, sometimes on a 200
+    fn into_data(self, url: &str) -> Result<Vec<T>, ExchangeError> {
+        let kind = match self.code.as_str() {
+            "0" => return Ok(self.data),
+            // Too Many Requests
+            "50011" | "50061" => ErrorKind::RateLimited {
+                retry_after_secs: None,
+            },
+            code => ErrorKind::Api {
+                code: code.to_string(),
+                message: self.msg,
+            },
+        };
+        Err(ExchangeError::new("okx", url, kind))
+    }
+}
+
+/// One entry of `GET /api/v5/public/instruments?instType=SPOT`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Instrument {
+    inst_id: String,
+    base_ccy: String,
+    quote_ccy: String,
+    /// `live`, `suspend`, `preopen` or `test`
+    state: String,
+}
+
+/// One entry of `GET /api/v5/market/tickers` and of a `tickers` channel push
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Ticker {
+    inst_id: String,
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    last: Option<f64>,
+    /// `""` when that side of the book is empty
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    bid_px: Option<f64>,
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    ask_px: Option<f64>,
+    /// 24h volume in the quote currency (spot)
+    #[serde(rename = "volCcy24h", deserialize_with = "de::opt_f64_str")]
+    vol_ccy_24h: Option<f64>,
+}
+
+/// One entry of `GET /api/v5/public/instruments?instType=SWAP`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SwapInstrument {
+    inst_id: String,
+    /// Underlying, `BTC-USDT`
+    uly: String,
+    /// `linear` (USDT/USDC margined) or `inverse` (coin margined)
+    ct_type: String,
+    state: String,
+}
+
+/// One entry of `GET /api/v5/public/mark-price`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MarkPrice {
+    inst_id: String,
+    #[serde(deserialize_with = "de::f64_str")]
+    mark_px: f64,
+}
+
+/// One entry of `GET /api/v5/market/index-tickers`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexTicker {
+    /// Index name, `BTC-USDT`
+    inst_id: String,
+    #[serde(deserialize_with = "de::f64_str")]
+    idx_px: f64,
+}
+
+/// `GET /api/v5/public/funding-rate?instId=...`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FundingRate {
+    inst_id: String,
+    #[serde(deserialize_with = "de::f64_str")]
+    funding_rate: f64,
+    /// Unix ms of the upcoming settlement, as a string
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    funding_time: Option<f64>,
+    /// Unix ms of the settlement after that
+    #[serde(deserialize_with = "de::opt_f64_str")]
+    next_funding_time: Option<f64>,
+}
+
+/// A frame on the public WebSocket: a data push, or an event such as a
+/// subscribe ack or an error
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum WsFrame {
+    Push {
+        data: Vec<Ticker>,
+    },
+    Event {
+        event: String,
+        #[serde(default)]
+        code: String,
+        #[serde(default)]
+        msg: String,
+    },
+}
+
+impl Ticker {
+    fn to_pair(&self, markets: &HashMap<String, (String, String)>) -> Option<PairPrice> {
+        let (base, quote) = markets.get(&self.inst_id.to_uppercase())?;
+        let pair = spot_pair(&self.inst_id, base, quote, self.last?, self.vol_ccy_24h?)?;
+        Some(PairPrice {
+            bid: self.bid_px,
+            ask: self.ask_px,
+            ..pair
+        })
+    }
+}
+
+/// Live spot instruments from `/api/v5/public/instruments`
+pub async fn load_markets(http: &HttpClient) -> Result<Markets, ExchangeError> {
+    let instruments = http
+        .get_json::<Envelope<Instrument>>("okx", INSTRUMENTS_URL)
+        .await?
+        .into_data(INSTRUMENTS_URL)?;
+    let listed = instruments.len();
+    Ok(Markets::new(listed, symbol_map(instruments)))
+}
+
+/// instId -> (base, quote) for `live` instruments
+fn symbol_map(instruments: Vec<Instrument>) -> HashMap<String, (String, String)> {
+    instruments
+        .into_iter()
+        .filter(|i| i.state == "live")
+        .map(|i| {
+            (
+                i.inst_id.to_uppercase(),
+                (i.base_ccy.to_uppercase(), i.quote_ccy.to_uppercase()),
+            )
+        })
+        .collect()
+}
+
+/// ---------------- OKX (Hybrid: WS + REST fallback) ----------------
+pub async fn fetch_okx(
+    http: &HttpClient,
+    markets: &Markets,
+    feeds: &FeedRegistry,
+    ws_window: Duration,
+) -> Result<Vec<PairPrice>, ExchangeError> {
+    info!(
+        "fetching okx via websocket ({}s) + REST backfill",
+        ws_window.as_secs()
+    );
+    let symbol_map = &markets.symbols;
+
+    // the stream can't go through a configured proxy / private CA
+    let mut pairs: HashMap<String, PairPrice> = HashMap::new();
+    let (ws_messages, ws_decode_errors) = if http.has_custom_transport("okx") {
+        info!("okx: proxy or CA bundle configured, skipping websocket");
+        (0, 0)
+    } else {
+        sample_ws(symbol_map, feeds, ws_window, &mut pairs).await?
+    };
+    let ws_pairs = pairs.len();
+
+    let tickers = http
+        .get_json::<Envelope<Ticker>>("okx", TICKERS_URL)
+        .await?
+        .into_data(TICKERS_URL)?;
+
+    let mut rest_added = 0usize;
+    let mut rest_skipped = 0usize;
+    for t in tickers {
+        let symbol = t.inst_id.to_uppercase();
+        if pairs.contains_key(&symbol) {
+            continue;
+        }
+        match t.to_pair(symbol_map) {
+            Some(p) => {
+                pairs.insert(symbol, p);
+                rest_added += 1;
+            }
+            None => rest_skipped += 1,
+        }
+    }
+
+    info!(
+        "okx: total_symbols={} ws_messages={} ws_decode_errors={} ws_pairs={} rest_added={} final_pairs={}",
+        symbol_map.len(),
+        ws_messages,
+        ws_decode_errors,
+        ws_pairs,
+        rest_added,
+        pairs.len()
+    );
+    metrics::WS_MESSAGES
+        .with_label_values(&["okx"])
+        .inc_by(ws_messages as u64);
+    metrics::record_pairs("okx", "ws", ws_pairs);
+    metrics::record_pairs("okx", "rest", rest_added);
+    metrics::record_ingest("okx", markets.listed, markets.skipped(), rest_skipped);
+
+    Ok(pairs.into_values().collect())
+}
+
+/// ---------------- OKX linear swaps (REST) ----------------
+pub async fn fetch_okx_perps(http: &HttpClient) -> Result<Vec<PerpQuote>, ExchangeError> {
+    info!("fetching okx swaps (REST)");
+
+    let instruments = http
+        .get_json::<Envelope<SwapInstrument>>("okx", SWAP_INSTRUMENTS_URL)
+        .await?
+        .into_data(SWAP_INSTRUMENTS_URL)?;
+    let listed = instruments.len();
+    let tickers = http
+        .get_json::<Envelope<Ticker>>("okx", SWAP_TICKERS_URL)
+        .await?
+        .into_data(SWAP_TICKERS_URL)?;
+    let marks = http
+        .get_json::<Envelope<MarkPrice>>("okx", MARK_PRICE_URL)
+        .await?
+        .into_data(MARK_PRICE_URL)?;
+    let mut indexes = Vec::new();
+    for url in INDEX_URLS {
+        let page = http.get_json::<Envelope<IndexTicker>>("okx", url).await?;
+        indexes.extend(page.into_data(url)?);
+    }
+
+    let mut perps = perp_quotes(instruments, tickers, marks, indexes);
+    perps.sort_by(|a, b| b.liquidity.total_cmp(&a.liquidity));
+    perps.truncate(FUNDING_CONTRACTS);
+
+    let urls: Vec<String> = perps
+        .iter()
+        .map(|p| format!("{}{}", FUNDING_URL, p.symbol))
+        .collect();
+    let rates = join_all(urls.iter().map(|url| async move {
+        http.get_json::<Envelope<FundingRate>>("okx", url)
+            .await?
+            .into_data(url)
+    }))
+    .await;
+
+    let mut funding: HashMap<String, FundingRate> = HashMap::new();
+    let mut first_error = None;
+    for rate in rates {
+        match rate {
+            Ok(list) => funding.extend(list.into_iter().map(|f| (f.inst_id.to_uppercase(), f))),
+            Err(e) => {
+                warn!("okx: funding rate unavailable: {}", e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    // a few missing rates only shrink the result; none at all is an outage
+    if let (true, Some(e)) = (funding.is_empty(), first_error) {
+        return Err(e);
+    }
+    let out: Vec<PerpQuote> = perps
+        .into_iter()
+        .filter_map(|p| {
+            let rate = funding.get(&p.symbol)?;
+            Some(with_funding(p, rate))
+        })
+        .collect();
+
+    info!("okx: swaps_listed={} returned={}", listed, out.len());
+    metrics::record_perps("okx", out.len());
+
+    Ok(out)
+}
+
+/// Live linear swaps with a mark price and turnover; funding is added by
+/// [`with_funding`]
+fn perp_quotes(
+    instruments: Vec<SwapInstrument>,
+    tickers: Vec<Ticker>,
+    marks: Vec<MarkPrice>,
+    indexes: Vec<IndexTicker>,
+) -> Vec<PerpQuote> {
+    let turnover: HashMap<String, f64> = tickers
+        .into_iter()
+        // swap `volCcy24h` is in the base currency
+        .filter_map(|t| Some((t.inst_id.to_uppercase(), t.vol_ccy_24h? * t.last?)))
+        .collect();
+    let marks: HashMap<String, f64> = marks
+        .into_iter()
+        .map(|m| (m.inst_id.to_uppercase(), m.mark_px))
+        .collect();
+    let indexes: HashMap<String, f64> = indexes
+        .into_iter()
+        .map(|i| (i.inst_id.to_uppercase(), i.idx_px))
+        .collect();
+    instruments
+        .into_iter()
+        .filter(|i| i.state == "live" && i.ct_type == "linear")
+        .filter_map(|i| {
+            let symbol = i.inst_id.to_uppercase();
+            let underlying = i.uly.to_uppercase();
+            let (base, quote) = underlying.split_once('-')?;
+            let (mark, liquidity) = (*marks.get(&symbol)?, *turnover.get(&symbol)?);
+            let perp = perp_quote(base, quote, &symbol, mark, liquidity)?;
+            Some(PerpQuote {
+                index_price: indexes.get(&underlying).copied(),
+                ..perp
+            })
+        })
+        .collect()
+}
+
+/// Interval from the gap between the next two settlements; OKX moves some
+/// contracts to 4h or 1h funding
+fn with_funding(perp: PerpQuote, rate: &FundingRate) -> PerpQuote {
+    let next = rate.funding_time.filter(|t| *t > 0.0);
+    let interval = match (next, rate.next_funding_time) {
+        (Some(now), Some(after)) if after > now => (after - now) / 3_600_000.0,
+        _ => perp.funding_interval_hours,
+    };
+    PerpQuote {
+        funding_rate: rate.funding_rate,
+        next_funding_time: next.map(|t| t as u64),
+        funding_interval_hours: interval,
+        ..perp
+    }
+}
+
+/// `subscribe` frames covering every instrument in `symbol_map`
+fn subscribe_frames(symbol_map: &HashMap<String, (String, String)>) -> Vec<String> {
+    let mut ids: Vec<&String> = symbol_map.keys().collect();
+    ids.sort();
+    ids.chunks(SUBSCRIBE_BATCH)
+        .map(|chunk| {
+            let args: Vec<_> = chunk
+                .iter()
+                .map(|id| serde_json::json!({ "channel": "tickers", "instId": id }))
+                .collect();
+            serde_json::json!({ "op": "subscribe", "args": args }).to_string()
+        })
+        .collect()
+}
+
+/// Subscribe to the `tickers` channel and collect pushes for `ws_window`;
+/// returns (frames received, frames that didn't decode)
+async fn sample_ws(
+    symbol_map: &HashMap<String, (String, String)>,
+    feeds: &FeedRegistry,
+    ws_window: Duration,
+    pairs: &mut HashMap<String, PairPrice>,
+) -> Result<(usize, usize), ExchangeError> {
+    let (ws_stream, _) = match connect_async(STREAM_URL).await {
+        Ok(conn) => conn,
+        Err(e) => {
+            feeds.set_ws_connected("okx", false);
+            return Err(ExchangeError::websocket("okx", STREAM_URL, e));
+        }
+    };
+    feeds.set_ws_connected("okx", true);
+    let (mut write, mut read) = ws_stream.split();
+
+    for frame in subscribe_frames(symbol_map) {
+        if let Err(e) = write.send(Message::Text(frame)).await {
+            feeds.set_ws_connected("okx", false);
+            return Err(ExchangeError::websocket("okx", STREAM_URL, e));
+        }
+    }
+
+    let mut ws_messages = 0usize;
+    let mut ws_decode_errors = 0usize;
+    let mut ping = interval(PING_EVERY);
+    ping.tick().await;
+
+    let ws_result = timeout(ws_window, async {
+        loop {
+            let msg = tokio::select! {
+                msg = read.next() => match msg {
+                    Some(msg) => msg,
+                    None => break,
+                },
+                _ = ping.tick() => {
+                    if write.send(Message::Text("ping".to_string())).await.is_err() {
+                        break;
+                    }
+                    continue;
+                }
+            };
+            ws_messages += 1;
+            let Ok(msg) = msg else { continue };
+            let Ok(text) = msg.to_text() else { continue };
+            if text.is_empty() || text == "pong" {
+                continue;
+            }
+            match serde_json::from_str::<WsFrame>(text) {
+                Ok(WsFrame::Push { data }) => {
+                    for t in data {
+                        if let Some(p) = t.to_pair(symbol_map) {
+                            pairs.insert(t.inst_id.to_uppercase(), p);
+                        }
+                    }
+                }
+                Ok(WsFrame::Event { event, code, msg }) => {
+                    if event == "error" {
+                        warn!("okx ws error {}: {}", code, msg);
+                    }
+                }
+                Err(e) => {
+                    if ws_decode_errors == 0 {
+                        warn!("okx ws frame did not match ticker schema: {}", e);
+                    }
+                    ws_decode_errors += 1;
+                }
+            }
+        }
+    })
+    .await;
+
+    if ws_result.is_err() {
+        info!(
+            "okx ws collection stopped after {}s timeout",
+            ws_window.as_secs()
+        );
+    }
+
+    Ok((ws_messages, ws_decode_errors))
+}
+
+/// Top of one instrument's book, `inst_id` like `BTC-USDT`
+pub async fn fetch_book(http: &HttpClient, inst_id: &str) -> Result<OrderBook, ExchangeError> {
+    let url = format!("{}?instId={}&sz={}", BOOKS_URL, inst_id, BOOK_DEPTH);
+    http.get_json::<Envelope<Levels>>("okx", &url)
+        .await?
+        .into_data(&url)?
+        .into_iter()
+        .next()
+        .map(OrderBook::from)
+        .ok_or_else(|| ExchangeError::decode("okx", &url, "empty `data`", ""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INSTRUMENTS: &str = include_str!("../../tests/fixtures/okx/instruments.json");
+    const TICKERS: &str = include_str!("../../tests/fixtures/okx/tickers.json");
+    const WS_TICKERS: &str = include_str!("../../tests/fixtures/okx/ws_tickers.json");
+    const SWAP_INSTRUMENTS: &str = include_str!("../../tests/fixtures/okx/swap_instruments.json");
+    const SWAP_TICKERS: &str = include_str!("../../tests/fixtures/okx/swap_tickers.json");
+    const MARK_PRICE: &str = include_str!("../../tests/fixtures/okx/mark_price.json");
+    const INDEX_TICKERS: &str = include_str!("../../tests/fixtures/okx/index_tickers.json");
+    const FUNDING_RATE: &str = include_str!("../../tests/fixtures/okx/funding_rate.json");
+    const RATE_LIMITED: &str = include_str!("../../tests/fixtures/okx/error_50011.json");
+    const BOOKS: &str = include_str!("../../tests/fixtures/okx/books.json");
+
+    fn markets() -> HashMap<String, (String, String)> {
+        let list = serde_json::from_str::<Envelope<Instrument>>(INSTRUMENTS)
+            .unwrap()
+            .into_data(INSTRUMENTS_URL)
+            .unwrap();
+        symbol_map(list)
+    }
+
+    #[test]
+    fn instruments_keep_only_live() {
+        let map = markets();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map["ETH-BTC"], ("ETH".to_string(), "BTC".to_string()));
+        assert!(!map.contains_key("NEW-USDT"), "preopen is dropped");
+        assert!(!map.contains_key("OLD-USDT"), "suspend is dropped");
+    }
+
+    #[test]
+    fn tickers_carry_bid_ask_and_quote_volume() {
+        let map = markets();
+        let tickers = serde_json::from_str::<Envelope<Ticker>>(TICKERS)
+            .unwrap()
+            .into_data(TICKERS_URL)
+            .unwrap();
+        assert_eq!(tickers.len(), 4);
+
+        let pairs: Vec<PairPrice> = tickers.iter().filter_map(|t| t.to_pair(&map)).collect();
+        assert_eq!(pairs.len(), 3, "unlisted OLD-USDT is skipped");
+        let btc = &pairs[0];
+        assert_eq!((btc.base.as_str(), btc.quote.as_str()), ("BTC", "USDT"));
+        assert_eq!(btc.bid, Some(112498.3));
+        assert_eq!(btc.ask, Some(112498.4));
+        assert_eq!(btc.liquidity, 1_023_482_211.81);
+
+        // an empty book side decodes as None rather than failing
+        assert_eq!(tickers[3].bid_px, None);
+    }
+
+    #[test]
+    fn ws_push_and_events_decode() {
+        let Ok(WsFrame::Push { data }) = serde_json::from_str::<WsFrame>(WS_TICKERS) else {
+            panic!("ticker push did not decode");
+        };
+        let pair = data[0].to_pair(&markets()).unwrap();
+        assert_eq!(pair.price, 0.05415);
+        assert_eq!(pair.ask, Some(0.05416));
+
+        let ack = r#"{"event":"subscribe","arg":{"channel":"tickers","instId":"ETH-BTC"},"connId":"a4d3ae55"}"#;
+        assert!(matches!(
+            serde_json::from_str::<WsFrame>(ack),
+            Ok(WsFrame::Event { .. })
+        ));
+    }
+
+    #[test]
+    fn subscriptions_are_batched() {
+        let map: HashMap<String, (String, String)> = (0..250)
+            .map(|i| {
+                (
+                    format!("C{}-USDT", i),
+                    (format!("C{}", i), "USDT".to_string()),
+                )
+            })
+            .collect();
+        let frames = subscribe_frames(&map);
+        assert_eq!(frames.len(), 3);
+        assert!(frames.iter().all(|f| f.len() < 64 * 1024));
+    }
+
+    #[test]
+    fn code_50011_maps_to_rate_limited() {
+        let err = serde_json::from_str::<Envelope<Ticker>>(RATE_LIMITED)
+            .unwrap()
+            .into_data(TICKERS_URL)
+            .unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::RateLimited { .. }));
+    }
+
+    fn data<T: serde::de::DeserializeOwned>(raw: &str) -> Vec<T> {
+        serde_json::from_str::<Envelope<T>>(raw)
+            .unwrap()
+            .into_data("")
+            .unwrap()
+    }
+
+    #[test]
+    fn linear_swaps_join_mark_index_and_funding() {
+        let perps = perp_quotes(
+            data(SWAP_INSTRUMENTS),
+            data(SWAP_TICKERS),
+            data(MARK_PRICE),
+            data(INDEX_TICKERS),
+        );
+        assert_eq!(perps.len(), 2, "inverse swaps are dropped");
+        let eth = perps.iter().find(|p| p.symbol == "ETH-USDT-SWAP").unwrap();
+        assert_eq!((eth.base.as_str(), eth.quote.as_str()), ("ETH", "USDT"));
+        assert_eq!(eth.mark_price, 6065.4);
+        assert_eq!(eth.index_price, Some(6066.12));
+        // base-currency volume times the last price
+        assert!((eth.liquidity - 1_204_511.2 * 6065.31).abs() < 1e-3);
+
+        let rate = data::<FundingRate>(FUNDING_RATE).remove(0);
+        let eth = with_funding(eth.clone(), &rate);
+        assert_eq!(eth.funding_rate, 0.0000812);
+        assert_eq!(eth.next_funding_time, Some(1_760_832_000_000));
+        assert_eq!(eth.funding_interval_hours, 8.0);
+    }
+
+    #[test]
+    fn books_ignore_order_counts() {
+        let levels: Vec<Levels> = data(BOOKS);
+        let book = OrderBook::from(levels.into_iter().next().unwrap());
+        assert_eq!(book.asks[0], (6021.46, 0.9215));
+        assert_eq!(book.bids[2], (6020.8, 10.0712));
+    }
+}
@@ -0,0 +1,943 @@
+//! One attempt at a triangle, as a state machine with leg-failure recovery.
+//!
+//! Legs run in order, each trading what the previous one received. When a
+//! leg fills only partly or not at all, the configured policy decides what
+//! happens to the asset left in hand: `retry` resends the remainder at a
+//! progressively worse limit, `unwind` sells it back into the start asset at
+//! market, and `hold` keeps it and raises an alert. Every transition is
+//! recorded, so an attempt can be reconstructed order by order afterwards.
+//! Once the kill switch is engaged, no further order is sent and whatever is
+//! in hand is held.
+//!
+//! In `inventory` mode the legs don't wait on each other: all three are sent
+//! at once from balances already held, so there is nothing stranded to
+//! recover, only inventory that shifts when a leg falls short.
+
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    unix_ms, Balances, Execution, ExecutionContext, LegExecution, OrderError, OrderReport,
+    OrderRequest, OrderStatus, OrderType, Venue,
+};
+use crate::exchanges::OrderRules;
+use crate::metrics;
+use crate::models::{Side, TradeLeg, TradeStatus};
+use crate::utils::{round2, round4};
+
+/// Recovery for an asset a leg left stranded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecoveryPolicy {
+    /// Sell the asset back into the start asset at market
+    Unwind,
+    /// Resend the leg's remainder at a worse price, then apply the fallback
+    Retry,
+    /// Keep the asset and alert
+    Hold,
+}
+
+impl RecoveryPolicy {
+    fn label(self) -> &'static str {
+        match self {
+            RecoveryPolicy::Unwind => "unwind",
+            RecoveryPolicy::Retry => "retry",
+            RecoveryPolicy::Hold => "hold",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttemptState {
+    Started,
+    /// A leg's order was sent
+    Submitted,
+    Filled,
+    PartiallyFilled,
+    /// The order failed or traded nothing
+    Rejected,
+    /// The remainder of a leg is being resent
+    Retrying,
+    /// A stranded asset is being sold back into the start asset
+    Unwinding,
+    UnwindFailed,
+    /// Terminal: all three legs traded and nothing is held
+    Completed,
+    /// Terminal: the triangle broke off and what was bought was sold back
+    Unwound,
+    /// Terminal: assets other than the start asset were kept; in inventory
+    /// mode, the legs traded unevenly and the inventory shifted
+    Held,
+    /// Terminal: the first leg didn't trade, nothing changed hands
+    Aborted,
+}
+
+/// One transition of an attempt
+#[derive(Debug, Clone, Serialize)]
+pub struct AttemptEvent {
+    /// Unix milliseconds
+    pub at_ms: u64,
+    pub state: AttemptState,
+    /// Leg the event concerns, 1-based
+    pub leg: Option<usize>,
+    pub detail: String,
+}
+
+/// Amount of one asset: left over after an attempt, or gained (negative:
+/// lost) by an inventory-mode attempt
+#[derive(Debug, Clone, Serialize)]
+pub struct Holding {
+    pub asset: String,
+    pub amount: f64,
+}
+
+impl LegExecution {
+    fn new(leg: &TradeLeg, client_id: String, requested: f64) -> Self {
+        Self {
+            symbol: leg.symbol.clone(),
+            side: leg.side,
+            from: leg.from.clone(),
+            to: leg.to.clone(),
+            order_ids: Vec::new(),
+            client_id,
+            status: OrderStatus::Unfilled,
+            requested,
+            spent: 0.0,
+            received: 0.0,
+            filled_base: 0.0,
+            filled_quote: 0.0,
+            expected_price: leg.price,
+            avg_price: 0.0,
+            slippage_bps: 0.0,
+            retries: 0,
+            error: None,
+        }
+    }
+
+    /// Add an order's fills to the leg
+    fn record(&mut self, report: &OrderReport, fee_mult: f64) {
+        self.order_ids.push(report.order_id.clone());
+        self.error = None;
+        self.filled_base += report.filled_base;
+        self.filled_quote += report.filled_quote;
+        let (spent, received) = match self.side {
+            Side::Sell => (self.filled_base, self.filled_quote),
+            Side::Buy => (self.filled_quote, self.filled_base),
+        };
+        self.spent = spent;
+        self.received = received * fee_mult;
+        self.status = match report.status {
+            OrderStatus::Filled => OrderStatus::Filled,
+            _ => OrderStatus::finished(self.filled_base, false),
+        };
+        if self.filled_base > 0.0 {
+            self.avg_price = self.filled_quote / self.filled_base;
+            let slippage = match self.side {
+                Side::Sell => self.expected_price - self.avg_price,
+                Side::Buy => self.avg_price - self.expected_price,
+            };
+            self.slippage_bps = round2(slippage / self.expected_price * 10_000.0);
+        }
+    }
+
+    /// Amount of `from` the leg didn't trade
+    fn remainder(&self) -> f64 {
+        (self.requested - self.spent).max(0.0)
+    }
+}
+
+/// Why an attempt stopped sending orders midway
+const HALTED: &str = "kill switch engaged";
+
+/// The order for `amount` of the leg's `from` asset, fitted to the symbol's
+/// `rules`; an IOC limit sits `offset_bps` past the leg's scanned price and
+/// an IOC buy is sized at it
+pub(super) fn order(
+    leg: &TradeLeg,
+    amount: f64,
+    order_type: OrderType,
+    offset_bps: f64,
+    client_id: &str,
+    rules: &OrderRules,
+) -> Result<OrderRequest, String> {
+    let offset = offset_bps / 10_000.0;
+    let limit = match leg.side {
+        Side::Buy => leg.price * (1.0 + offset),
+        Side::Sell => leg.price * (1.0 - offset),
+    };
+    let order = OrderRequest {
+        symbol: leg.symbol.clone(),
+        side: leg.side,
+        order_type,
+        amount: match (order_type, leg.side) {
+            (OrderType::Ioc, Side::Buy) => amount / limit,
+            _ => amount,
+        },
+        price: (order_type == OrderType::Ioc).then_some(limit),
+        client_id: client_id.to_string(),
+    };
+    order.fit(rules, leg.price)
+}
+
+struct Attempt<'a> {
+    venue: &'a Venue<'a>,
+    ctx: &'a ExecutionContext,
+    legs: &'a [TradeLeg],
+    fee_mult: f64,
+    /// Shared prefix of the attempt's client order ids
+    tag: String,
+    /// Path as traded, e.g. `USDT → BTC → ETH → USDT`
+    triangle: String,
+    started_at: u64,
+    state: AttemptState,
+    events: Vec<AttemptEvent>,
+    unwinds: Vec<LegExecution>,
+    /// Start asset recovered by unwinds
+    unwound: f64,
+    held: Vec<Holding>,
+    /// An order's fate is unknown; recovery must not guess
+    outcome_unknown: bool,
+}
+
+impl<'a> Attempt<'a> {
+    fn new(venue: &'a Venue<'a>, ctx: &'a ExecutionContext, legs: &'a [TradeLeg]) -> Self {
+        let path: Vec<&str> = legs.iter().map(|l| l.from.as_str()).collect();
+        Attempt {
+            venue,
+            ctx,
+            legs,
+            fee_mult: 1.0 - ctx.fee_per_leg / 100.0,
+            // Gate wants a `t-` prefix and at most 28 characters
+            tag: format!("t-arb{}", unix_ms()),
+            triangle: format!("{} → {}", path.join(" → "), legs[0].from),
+            started_at: unix_ms() / 1000,
+            state: AttemptState::Started,
+            events: Vec::new(),
+            unwinds: Vec::new(),
+            unwound: 0.0,
+            held: Vec::new(),
+            outcome_unknown: false,
+        }
+    }
+
+    fn transition(&mut self, state: AttemptState, leg: Option<usize>, detail: String) {
+        tracing::debug!(
+            "{} {}: {:?} (leg {:?}) {}",
+            self.venue.exchange,
+            self.tag,
+            state,
+            leg,
+            detail
+        );
+        self.state = state;
+        self.events.push(AttemptEvent {
+            at_ms: unix_ms(),
+            state,
+            leg,
+            detail,
+        });
+    }
+
+    /// Send one order for `amount` of the leg's `from` asset; an IOC limit
+    /// sits `offset_bps` past the leg's scanned price
+    async fn send(
+        &self,
+        leg: &TradeLeg,
+        amount: f64,
+        order_type: OrderType,
+        offset_bps: f64,
+        client_id: &str,
+    ) -> Result<OrderReport, OrderError> {
+        let rules = self
+            .ctx
+            .markets
+            .as_ref()
+            .and_then(|m| m.rules.get(&leg.symbol))
+            .copied()
+            .unwrap_or_default();
+        let order = order(leg, amount, order_type, offset_bps, client_id, &rules)
+            .map_err(OrderError::NotSent)?;
+        self.venue.execute(&order).await
+    }
+
+    /// Trade leg `i` with `amount`, resending the remainder under `retry`
+    async fn trade(&mut self, i: usize, amount: f64) -> LegExecution {
+        let cfg = &self.ctx.config;
+        let leg = &self.legs[i];
+        let mut fill = LegExecution::new(leg, format!("{}-{}", self.tag, i + 1), amount);
+        self.submitted(i, amount);
+        match self
+            .send(
+                leg,
+                amount,
+                cfg.order_type,
+                cfg.ioc_slippage_bps,
+                &fill.client_id,
+            )
+            .await
+        {
+            Ok(report) => fill.record(&report, self.fee_mult),
+            Err(e) => self.failed(&mut fill, e),
+        }
+
+        if cfg.recovery.policy == RecoveryPolicy::Retry {
+            while fill.status != OrderStatus::Filled
+                && fill.retries < cfg.recovery.retries
+                && !self.outcome_unknown
+                && !self.ctx.risk.is_halted()
+            {
+                fill.retries += 1;
+                let offset =
+                    cfg.ioc_slippage_bps + cfg.recovery.retry_step_bps * f64::from(fill.retries);
+                let remainder = fill.remainder();
+                self.transition(
+                    AttemptState::Retrying,
+                    Some(i + 1),
+                    format!(
+                        "retry {} for {} {} at {} bps",
+                        fill.retries, remainder, leg.from, offset
+                    ),
+                );
+                metrics::EXECUTION_RECOVERIES
+                    .with_label_values(&[self.venue.exchange, "retry"])
+                    .inc();
+                let client_id = format!("{}r{}", fill.client_id, fill.retries);
+                match self
+                    .send(leg, remainder, cfg.order_type, offset, &client_id)
+                    .await
+                {
+                    Ok(report) => fill.record(&report, self.fee_mult),
+                    Err(e @ OrderError::NotSent(_)) => {
+                        // the remainder stays too small however often it's
+                        // resent
+                        self.failed(&mut fill, e);
+                        break;
+                    }
+                    Err(e) => self.failed(&mut fill, e),
+                }
+            }
+        }
+
+        self.settled(i, &fill);
+        fill
+    }
+
+    fn submitted(&mut self, i: usize, amount: f64) {
+        let leg = &self.legs[i];
+        let detail = format!(
+            "{} {} {} {}",
+            leg.side_label(),
+            leg.symbol,
+            amount,
+            leg.from
+        );
+        self.transition(AttemptState::Submitted, Some(i + 1), detail);
+    }
+
+    /// Record how leg `i` ended up
+    fn settled(&mut self, i: usize, fill: &LegExecution) {
+        let leg = &self.legs[i];
+        let (state, detail) = match fill.status {
+            OrderStatus::Filled => (
+                AttemptState::Filled,
+                format!("received {} {}", fill.received, leg.to),
+            ),
+            _ if fill.filled_base > 0.0 => (
+                AttemptState::PartiallyFilled,
+                format!(
+                    "received {} {}, {} {} left",
+                    fill.received,
+                    leg.to,
+                    fill.remainder(),
+                    leg.from
+                ),
+            ),
+            _ => (
+                AttemptState::Rejected,
+                fill.error
+                    .clone()
+                    .unwrap_or_else(|| "nothing traded".to_string()),
+            ),
+        };
+        self.transition(state, Some(i + 1), detail);
+    }
+
+    /// An order that may have traded without the venue saying so leaves
+    /// the attempt's position unknown, so nothing more is sent once one is
+    /// seen
+    fn failed(&mut self, fill: &mut LegExecution, e: OrderError) {
+        if matches!(e, OrderError::Unknown(_)) {
+            self.outcome_unknown = true;
+        }
+        fill.error = Some(e.to_string());
+    }
+
+    /// Deal with `amount` of leg `i`'s `from` asset that the leg didn't trade
+    async fn recover(&mut self, i: usize, amount: f64) {
+        let recovery = &self.ctx.config.recovery;
+        let policy = match recovery.policy {
+            RecoveryPolicy::Retry => recovery.fallback,
+            policy => policy,
+        };
+        let asset = self.legs[i].from.clone();
+        metrics::EXECUTION_RECOVERIES
+            .with_label_values(&[self.venue.exchange, policy.label()])
+            .inc();
+        if policy == RecoveryPolicy::Hold {
+            self.hold(i, asset, amount, "held by policy");
+            return;
+        }
+        if self.ctx.risk.is_halted() {
+            self.hold(i, asset, amount, HALTED);
+            return;
+        }
+
+        // leg 3 already trades into the start asset; after leg 2 the way
+        // back is leg 1 reversed
+        let back = if i + 1 == self.legs.len() {
+            self.legs[i].clone()
+        } else {
+            self.legs[i - 1].reversed()
+        };
+        self.transition(
+            AttemptState::Unwinding,
+            Some(i + 1),
+            format!("{} {} {} at market", back.side_label(), back.symbol, amount),
+        );
+        let mut unwind = LegExecution::new(&back, format!("{}-{}u", self.tag, i + 1), amount);
+        match self
+            .send(&back, amount, OrderType::Market, 0.0, &unwind.client_id)
+            .await
+        {
+            Ok(report) => unwind.record(&report, self.fee_mult),
+            Err(e) => unwind.error = Some(e.to_string()),
+        }
+        self.unwound += unwind.received;
+        let left = unwind.remainder();
+        let traded = unwind.filled_base > 0.0;
+        let reason = unwind
+            .error
+            .clone()
+            .unwrap_or_else(|| "unwind did not fill completely".to_string());
+        self.unwinds.push(unwind);
+        if left > 0.0 {
+            if !traded {
+                self.transition(AttemptState::UnwindFailed, Some(i + 1), reason.clone());
+            }
+            self.hold(i, asset, left, &reason);
+        }
+    }
+
+    fn hold(&mut self, i: usize, asset: String, amount: f64, reason: &str) {
+        tracing::error!(
+            target: "alert",
+            exchange = %self.venue.exchange,
+            attempt = %self.tag,
+            "holding {} {} after leg {}: {}",
+            amount,
+            asset,
+            i + 1,
+            reason
+        );
+        self.transition(
+            AttemptState::Held,
+            Some(i + 1),
+            format!("holding {} {}: {}", amount, asset, reason),
+        );
+        self.held.push(Holding { asset, amount });
+    }
+
+    /// Report of the finished attempt; `end_amount` is the start asset
+    /// back in hand
+    fn finish(
+        self,
+        expected_profit: Option<f64>,
+        status: TradeStatus,
+        fills: Vec<LegExecution>,
+        end_amount: f64,
+    ) -> Execution {
+        let cfg = &self.ctx.config;
+        let start_amount = fills[0].spent;
+        let realized_pnl = end_amount - start_amount;
+        let error = fills.iter().rev().find_map(|f| f.error.clone());
+        Execution {
+            id: 0,
+            exchange: self.venue.exchange.to_string(),
+            triangle: self.triangle,
+            mode: cfg.mode,
+            order_type: cfg.order_type,
+            recovery: cfg.recovery.policy,
+            started_at: self.started_at,
+            finished_at: unix_ms() / 1000,
+            expected_profit,
+            status,
+            state: self.state,
+            start_asset: self.legs[0].from.clone(),
+            start_amount,
+            end_amount,
+            realized_pnl,
+            realized_pnl_pct: if start_amount > 0.0 {
+                round4(realized_pnl / start_amount * 100.0)
+            } else {
+                0.0
+            },
+            legs: fills,
+            unwinds: self.unwinds,
+            held: self.held,
+            inventory_change: Vec::new(),
+            events: self.events,
+            error,
+        }
+    }
+}
+
+impl TradeLeg {
+    /// The same market traded the other way
+    fn reversed(&self) -> TradeLeg {
+        TradeLeg {
+            symbol: self.symbol.clone(),
+            side: match self.side {
+                Side::Buy => Side::Sell,
+                Side::Sell => Side::Buy,
+            },
+            from: self.to.clone(),
+            to: self.from.clone(),
+            price: self.price,
+        }
+    }
+
+    fn side_label(&self) -> &'static str {
+        match self.side {
+            Side::Buy => "buy",
+            Side::Sell => "sell",
+        }
+    }
+
+    /// Units of `to` per unit of `from` at the scanned price
+    pub(super) fn rate(&self) -> f64 {
+        match self.side {
+            Side::Sell => self.price,
+            Side::Buy => 1.0 / self.price,
+        }
+    }
+}
+
+/// Amount of its `from` asset each leg trades when the triangle starts
+/// with `amount` and every leg fills at the scanned price
+pub(super) fn leg_amounts(legs: &[TradeLeg], amount: f64, fee_mult: f64) -> Vec<f64> {
+    let mut size = amount;
+    legs.iter()
+        .map(|leg| {
+            let spent = size;
+            size *= leg.rate() * fee_mult;
+            spent
+        })
+        .collect()
+}
+
+/// Largest start amount, up to `amount`, that every leg can trade from the
+/// balances held: each leg spends what the legs before it would deliver
+pub(super) fn inventory_size(
+    legs: &[TradeLeg],
+    amount: f64,
+    balances: &Balances,
+    fee_mult: f64,
+) -> f64 {
+    let mut size = amount;
+    // amount of the leg's `from` per unit of the start asset
+    let mut scale = 1.0;
+    for leg in legs {
+        size = size.min(balances.get(&leg.from) / scale);
+        scale *= leg.rate() * fee_mult;
+    }
+    size
+}
+
+/// Run the rotated legs of a triangle starting with `amount` of the first
+/// leg's `from` asset
+pub(super) async fn run(
+    venue: &Venue<'_>,
+    legs: &[TradeLeg],
+    amount: f64,
+    expected_profit: Option<f64>,
+    ctx: &ExecutionContext,
+) -> Execution {
+    let mut attempt = Attempt::new(venue, ctx, legs);
+    let detail = format!("{} {} through {}", amount, legs[0].from, attempt.triangle);
+    attempt.transition(AttemptState::Started, None, detail);
+
+    let mut in_hand = amount;
+    let mut fills: Vec<LegExecution> = Vec::new();
+    let mut completed = true;
+    for (i, leg) in legs.iter().enumerate() {
+        if i > 0 && ctx.risk.is_halted() {
+            attempt.hold(i, leg.from.clone(), in_hand, HALTED);
+            completed = false;
+            break;
+        }
+        let fill = attempt.trade(i, in_hand).await;
+        let traded = fill.filled_base > 0.0;
+        // a filled order's leftover is rounding dust, not a stranded asset
+        let stranded = if fill.status == OrderStatus::Filled {
+            0.0
+        } else {
+            fill.remainder()
+        };
+        in_hand = fill.received;
+        fills.push(fill);
+        if attempt.outcome_unknown {
+            attempt.hold(
+                i,
+                leg.from.clone(),
+                stranded,
+                "order outcome unknown, check the venue",
+            );
+            completed = false;
+            break;
+        }
+        if i > 0 && stranded > 0.0 {
+            attempt.recover(i, stranded).await;
+        }
+        if !traded {
+            completed = false;
+            break;
+        }
+    }
+
+    let status = if !completed {
+        TradeStatus::Failed
+    } else if fills.iter().all(|f| f.status == OrderStatus::Filled) {
+        TradeStatus::Filled
+    } else {
+        TradeStatus::Partial
+    };
+    let start_amount = fills[0].spent;
+    let end_amount = if completed { in_hand } else { 0.0 } + attempt.unwound;
+    let realized_pnl = end_amount - start_amount;
+    let (state, detail) = if !attempt.held.is_empty() {
+        (AttemptState::Held, "finished holding assets".to_string())
+    } else if completed {
+        (
+            AttemptState::Completed,
+            format!("pnl {} {}", realized_pnl, legs[0].from),
+        )
+    } else if start_amount > 0.0 {
+        (
+            AttemptState::Unwound,
+            format!("pnl {} {}", realized_pnl, legs[0].from),
+        )
+    } else {
+        (AttemptState::Aborted, "first leg did not trade".to_string())
+    };
+    attempt.transition(state, None, detail);
+    attempt.finish(expected_profit, status, fills, end_amount)
+}
+
+/// Send all three legs at once from held balances, starting with `amount`
+/// of the first leg's `from` asset. Each leg is sized for what the legs
+/// before it would deliver at the scanned prices, so a triangle that fills
+/// leaves every balance but the start asset's where it was.
+pub(super) async fn run_inventory(
+    venue: &Venue<'_>,
+    legs: &[TradeLeg],
+    amount: f64,
+    expected_profit: Option<f64>,
+    ctx: &ExecutionContext,
+) -> Execution {
+    let cfg = &ctx.config;
+    let mut attempt = Attempt::new(venue, ctx, legs);
+    let detail = format!(
+        "{} {} through {} from inventory",
+        amount, legs[0].from, attempt.triangle
+    );
+    attempt.transition(AttemptState::Started, None, detail);
+
+    let mut fills = Vec::new();
+    let sizes = leg_amounts(legs, amount, attempt.fee_mult);
+    for (i, (leg, size)) in legs.iter().zip(sizes).enumerate() {
+        let client_id = format!("{}-{}", attempt.tag, i + 1);
+        fills.push(LegExecution::new(leg, client_id, size));
+        attempt.submitted(i, size);
+    }
+    let reports = join_all(legs.iter().zip(&fills).map(|(leg, fill)| {
+        attempt.send(
+            leg,
+            fill.requested,
+            cfg.order_type,
+            cfg.ioc_slippage_bps,
+            &fill.client_id,
+        )
+    }))
+    .await;
+    for (i, (fill, report)) in fills.iter_mut().zip(reports).enumerate() {
+        match report {
+            Ok(report) => fill.record(&report, attempt.fee_mult),
+            Err(e) => attempt.failed(fill, e),
+        }
+        attempt.settled(i, fill);
+    }
+
+    let mut change: Vec<Holding> = legs
+        .iter()
+        .map(|l| Holding {
+            asset: l.from.clone(),
+            amount: 0.0,
+        })
+        .collect();
+    for (i, fill) in fills.iter().enumerate() {
+        change[i].amount -= fill.spent;
+        change[(i + 1) % legs.len()].amount += fill.received;
+    }
+
+    let status = if fills.iter().any(|f| f.filled_base <= 0.0) {
+        TradeStatus::Failed
+    } else if fills.iter().all(|f| f.status == OrderStatus::Filled) {
+        TradeStatus::Filled
+    } else {
+        TradeStatus::Partial
+    };
+    let end_amount = fills[legs.len() - 1].received;
+    let (state, detail) = if status == TradeStatus::Filled {
+        (
+            AttemptState::Completed,
+            format!("pnl {} {}", end_amount - fills[0].spent, legs[0].from),
+        )
+    } else if fills.iter().all(|f| f.filled_base <= 0.0) {
+        (AttemptState::Aborted, "no leg traded".to_string())
+    } else {
+        tracing::warn!(
+            exchange = %venue.exchange,
+            attempt = %attempt.tag,
+            "inventory legs traded unevenly: {:?}",
+            change
+        );
+        (
+            AttemptState::Held,
+            "legs traded unevenly, inventory shifted".to_string(),
+        )
+    };
+    if attempt.outcome_unknown {
+        tracing::error!(
+            target: "alert",
+            exchange = %venue.exchange,
+            attempt = %attempt.tag,
+            "inventory order outcome unknown, check the venue"
+        );
+    }
+    attempt.transition(state, None, detail);
+    let mut execution = attempt.finish(expected_profit, status, fills, end_amount);
+    execution.inventory_change = change;
+    execution
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::mock::{close, context, http, symbol, triangle, venue, MockExchange};
+    use super::super::{ExecutionEngine, ExecutionError, ExecutionMode};
+    use super::*;
+    use crate::exchanges::OrderBook;
+
+    const EXCHANGE: &str = "binance";
+
+    async fn run_with(
+        mock: &MockExchange,
+        order_type: OrderType,
+        policy: RecoveryPolicy,
+    ) -> Execution {
+        let mut ctx = context(&http(), mock, EXCHANGE, order_type);
+        ctx.config.recovery.policy = policy;
+        ExecutionEngine::default()
+            .execute(EXCHANGE, &triangle(EXCHANGE), None, &ctx)
+            .await
+            .unwrap()
+    }
+
+    /// Trade the triangle in inventory mode holding `balances`
+    async fn inventory(mock: &MockExchange, balances: &[(&str, f64)]) -> Execution {
+        let mut ctx = context(&http(), mock, EXCHANGE, OrderType::Market);
+        ctx.config.mode = ExecutionMode::Inventory;
+        mock.set_balances(balances);
+        ctx.balances.refresh(EXCHANGE, &ctx).await.unwrap();
+        ExecutionEngine::default()
+            .execute(EXCHANGE, &triangle(EXCHANGE), None, &ctx)
+            .await
+            .unwrap()
+    }
+
+    fn change(execution: &Execution) -> Vec<f64> {
+        execution
+            .inventory_change
+            .iter()
+            .map(|h| h.amount)
+            .collect()
+    }
+
+    fn states(execution: &Execution) -> Vec<AttemptState> {
+        execution.events.iter().map(|e| e.state).collect()
+    }
+
+    #[tokio::test]
+    async fn completed_attempt_logs_every_leg() {
+        let mock = venue(EXCHANGE, vec![(50_000.0, 5.0)]).await;
+        let done = run_with(&mock, OrderType::Market, RecoveryPolicy::Unwind).await;
+        use AttemptState::*;
+        assert_eq!(
+            states(&done),
+            vec![Started, Submitted, Filled, Submitted, Filled, Submitted, Filled, Completed]
+        );
+        assert_eq!(done.events[2].leg, Some(1));
+        assert!(done.unwinds.is_empty());
+    }
+
+    #[tokio::test]
+    async fn hold_keeps_the_stranded_asset() {
+        let mock = venue(EXCHANGE, vec![(50_000.0, 5.0)]).await;
+        mock.reject(&symbol(EXCHANGE, "ETH", "BTC"));
+        let done = run_with(&mock, OrderType::Market, RecoveryPolicy::Hold).await;
+        assert_eq!(done.status, TradeStatus::Failed);
+        assert_eq!(done.state, AttemptState::Held);
+        assert_eq!(done.held.len(), 1);
+        assert_eq!(done.held[0].asset, "BTC");
+        assert!(close(done.held[0].amount, 0.02));
+        assert!(done.unwinds.is_empty());
+        assert_eq!(mock.orders().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn retry_fills_the_remainder_at_a_worse_price() {
+        let mock = venue(EXCHANGE, vec![(50_000.0, 5.0)]).await;
+        // 0.1 ETH at the scanned price, the rest 14 bps higher: past the
+        // 5 bps IOC limit, within the first retry's 15 bps
+        mock.set_book(
+            &symbol(EXCHANGE, "ETH", "BTC"),
+            OrderBook::new(vec![(0.0499, 100.0)], vec![(0.05, 0.1), (0.05007, 100.0)]),
+        );
+        let done = run_with(&mock, OrderType::Ioc, RecoveryPolicy::Retry).await;
+        assert_eq!(done.legs[1].retries, 1);
+        assert_eq!(done.legs[1].status, OrderStatus::Filled);
+        assert_eq!(done.legs[1].order_ids.len(), 2);
+        assert_eq!(done.status, TradeStatus::Filled);
+        assert_eq!(done.state, AttemptState::Completed);
+        assert!(states(&done).contains(&AttemptState::Retrying));
+        assert!(mock.orders()[2].client_id.ends_with("-2r1"));
+    }
+
+    #[tokio::test]
+    async fn exhausted_retries_fall_back_to_unwinding() {
+        let mock = venue(EXCHANGE, vec![(50_000.0, 5.0)]).await;
+        // only 0.1 ETH is ever offered within reach
+        mock.set_book(
+            &symbol(EXCHANGE, "ETH", "BTC"),
+            OrderBook::new(vec![(0.0499, 100.0)], vec![(0.05, 0.1), (0.06, 100.0)]),
+        );
+        let done = run_with(&mock, OrderType::Ioc, RecoveryPolicy::Retry).await;
+        let leg = &done.legs[1];
+        assert_eq!(leg.retries, 2);
+        assert_eq!(leg.status, OrderStatus::PartiallyFilled);
+        assert!(close(leg.spent, 0.005));
+
+        // the BTC leg 2 couldn't spend is sold back; the triangle still
+        // closes with the ETH it did get
+        assert_eq!(done.status, TradeStatus::Partial);
+        assert_eq!(done.state, AttemptState::Completed);
+        assert_eq!(done.unwinds.len(), 1);
+        assert_eq!(done.unwinds[0].side, Side::Sell);
+        let btc_left = done.legs[0].received - 0.005;
+        assert!(close(done.unwinds[0].spent, btc_left));
+        let end = 0.1 * 2_600.0 + btc_left * 49_990.0;
+        assert!(close(done.end_amount, end));
+    }
+
+    #[tokio::test]
+    async fn failed_unwind_holds_and_alerts() {
+        let mock = venue(EXCHANGE, vec![(50_000.0, 5.0)]).await;
+        mock.reject(&symbol(EXCHANGE, "ETH", "BTC"));
+        // nobody bids for the BTC
+        mock.set_book(
+            &symbol(EXCHANGE, "BTC", "USDT"),
+            OrderBook::new(Vec::new(), vec![(50_000.0, 5.0)]),
+        );
+        let done = run_with(&mock, OrderType::Market, RecoveryPolicy::Unwind).await;
+        assert_eq!(done.state, AttemptState::Held);
+        assert!(states(&done).contains(&AttemptState::UnwindFailed));
+        assert_eq!(done.unwinds[0].status, OrderStatus::Unfilled);
+        assert!(close(done.held[0].amount, 0.02));
+        assert!(close(done.realized_pnl, -1_000.0));
+    }
+
+    #[tokio::test]
+    async fn unknown_outcome_is_held_not_retried() {
+        let mock = venue(EXCHANGE, vec![(50_000.0, 5.0)]).await;
+        let mut ctx = context(&http(), &mock, EXCHANGE, OrderType::Market);
+        ctx.config.recovery.policy = RecoveryPolicy::Retry;
+        // nothing listens there: the request may or may not have arrived
+        ctx.config
+            .base_urls
+            .insert(EXCHANGE.to_string(), "http://127.0.0.1:1".to_string());
+        let done = ExecutionEngine::default()
+            .execute(EXCHANGE, &triangle(EXCHANGE), None, &ctx)
+            .await
+            .unwrap();
+        assert_eq!(done.legs[0].retries, 0);
+        assert_eq!(done.state, AttemptState::Held);
+        assert_eq!(done.held[0].asset, "USDT");
+        assert!(mock.orders().is_empty());
+    }
+
+    #[tokio::test]
+    async fn rejected_first_leg_aborts() {
+        let mock = venue(EXCHANGE, vec![(50_000.0, 5.0)]).await;
+        mock.reject(&symbol(EXCHANGE, "BTC", "USDT"));
+        let done = run_with(&mock, OrderType::Market, RecoveryPolicy::Unwind).await;
+        assert_eq!(done.state, AttemptState::Aborted);
+        assert_eq!(done.start_amount, 0.0);
+        assert!(done.unwinds.is_empty() && done.held.is_empty());
+    }
+
+    #[tokio::test]
+    async fn inventory_mode_sends_every_leg_from_held_balances() {
+        let mock = venue(EXCHANGE, vec![(50_000.0, 5.0)]).await;
+        // the 0.1 ETH held only covers a 250 USDT triangle
+        let done = inventory(&mock, &[("USDT", 1_000.0), ("BTC", 0.01), ("ETH", 0.1)]).await;
+        assert_eq!(done.mode, ExecutionMode::Inventory);
+        assert_eq!(done.status, TradeStatus::Filled);
+        assert_eq!(done.state, AttemptState::Completed);
+        use AttemptState::*;
+        assert_eq!(
+            states(&done)[..4],
+            [Started, Submitted, Submitted, Submitted]
+        );
+        let spent: Vec<f64> = done.legs.iter().map(|l| l.spent).collect();
+        assert!(close(spent[0], 250.0) && close(spent[1], 0.005) && close(spent[2], 0.1));
+        let change = change(&done);
+        assert!(close(change[0], 10.0) && close(change[1], 0.0) && close(change[2], 0.0));
+        assert!(close(done.realized_pnl, 10.0));
+        assert_eq!(mock.orders().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn short_inventory_leg_shifts_the_balances() {
+        let mock = venue(EXCHANGE, vec![(50_000.0, 5.0)]).await;
+        mock.reject(&symbol(EXCHANGE, "ETH", "BTC"));
+        let done = inventory(&mock, &[("USDT", 1_000.0), ("BTC", 0.01), ("ETH", 0.1)]).await;
+        assert_eq!(done.status, TradeStatus::Failed);
+        assert_eq!(done.state, AttemptState::Held);
+        // nothing is recovered: the other two legs went through regardless
+        assert!(done.unwinds.is_empty());
+        let change = change(&done);
+        assert!(close(change[0], 10.0) && close(change[1], 0.005) && close(change[2], -0.1));
+    }
+
+    #[tokio::test]
+    async fn inventory_mode_needs_known_balances() {
+        let mock = venue(EXCHANGE, vec![(50_000.0, 5.0)]).await;
+        let mut ctx = context(&http(), &mock, EXCHANGE, OrderType::Market);
+        ctx.config.mode = ExecutionMode::Inventory;
+        let refused = ExecutionEngine::default()
+            .execute(EXCHANGE, &triangle(EXCHANGE), None, &ctx)
+            .await;
+        assert!(matches!(refused, Err(ExecutionError::NoBalances(_))));
+        assert!(mock.orders().is_empty());
+    }
+}
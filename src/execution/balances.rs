@@ -0,0 +1,121 @@
+//! Account balances on the venues orders are sent to.
+//!
+//! Balances are fetched with the trading keys, mapped onto canonical asset
+//! names and kept per venue, so the scanner can size opportunities by what
+//! is actually held and the engine never commits more than that. They are
+//! refreshed on a timer and after every execution on the venue.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use tokio::time::Duration;
+
+use super::{unix_ms, ExecutionContext, Venue, EXECUTION_EXCHANGES};
+use crate::exchanges::ExchangeError;
+use crate::models::AppState;
+
+/// Available balances of one venue account
+#[derive(Debug, Clone, Serialize)]
+pub struct Balances {
+    /// Free amount per canonical asset; assets with nothing free are left out
+    pub assets: BTreeMap<String, f64>,
+    /// Unix seconds
+    pub fetched_at: u64,
+}
+
+impl Balances {
+    pub fn get(&self, asset: &str) -> f64 {
+        self.assets.get(asset).copied().unwrap_or(0.0)
+    }
+}
+
+/// Last fetched balances per venue, as served by `GET /balances`
+#[derive(Debug, Default)]
+pub struct BalanceBook {
+    accounts: Mutex<HashMap<String, Balances>>,
+}
+
+impl BalanceBook {
+    pub fn get(&self, exchange: &str) -> Option<Balances> {
+        self.accounts
+            .lock()
+            .expect("balance book poisoned")
+            .get(exchange)
+            .cloned()
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<String, Balances> {
+        self.accounts
+            .lock()
+            .expect("balance book poisoned")
+            .iter()
+            .map(|(ex, b)| (ex.clone(), b.clone()))
+            .collect()
+    }
+
+    /// Fetch `exchange`'s balances; on failure the previous ones are kept
+    pub async fn refresh(
+        &self,
+        exchange: &str,
+        ctx: &ExecutionContext,
+    ) -> Result<(), ExchangeError> {
+        let Some(creds) = ctx.credentials.get(exchange) else {
+            return Ok(());
+        };
+        let venue = Venue {
+            exchange,
+            base_url: ctx.config.base_url(exchange),
+            creds,
+            client: ctx.http.client(exchange),
+            recv_window_ms: ctx.config.recv_window_ms,
+        };
+        let mut assets = BTreeMap::new();
+        for (ticker, free) in venue.balances().await? {
+            if free > 0.0 {
+                // several venue tickers may map onto one asset
+                *assets
+                    .entry(ctx.assets.canonical(exchange, &ticker))
+                    .or_insert(0.0) += free;
+            }
+        }
+        let balances = Balances {
+            assets,
+            fetched_at: unix_ms() / 1000,
+        };
+        self.accounts
+            .lock()
+            .expect("balance book poisoned")
+            .insert(exchange.to_string(), balances);
+        Ok(())
+    }
+}
+
+/// Refresh the balances of every venue with trading keys every
+/// `execution.balance_refresh_secs`. Settings and keys are re-read each
+/// round, so a SIGHUP reload takes effect on the next tick.
+pub async fn background_refresh(state: Arc<tokio::sync::Mutex<AppState>>) {
+    loop {
+        let (interval, contexts) = {
+            let shared_state = state.lock().await;
+            let contexts: Vec<(&str, ExecutionContext)> = EXECUTION_EXCHANGES
+                .iter()
+                .filter(|ex| shared_state.trading_credentials.contains_key(**ex))
+                .map(|ex| (*ex, shared_state.execution_context(ex)))
+                .collect();
+            (shared_state.config.execution.balance_refresh_secs, contexts)
+        };
+
+        if interval == 0 || contexts.is_empty() {
+            // nothing to fetch; poll again later in case a reload adds keys
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            continue;
+        }
+
+        for (exchange, ctx) in &contexts {
+            if let Err(e) = ctx.balances.refresh(exchange, ctx).await {
+                tracing::warn!("balance refresh failed for {}: {}", exchange, e);
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+    }
+}
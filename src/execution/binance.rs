@@ -0,0 +1,203 @@
+//! Binance spot orders, balances and key permissions: HMAC-SHA256 over the
+//! query string, key in `X-MBX-APIKEY`.
+
+use serde::Deserialize;
+
+use super::sign::hmac_sha256_hex;
+use super::{
+    decimal, unix_ms, KeyPermissions, OrderReport, OrderRequest, OrderStatus, OrderType, Venue,
+};
+use crate::exchanges::{de, ErrorKind, ExchangeError};
+use crate::models::Side;
+
+const ORDER: &str = "/api/v3/order";
+const ACCOUNT: &str = "/api/v3/account";
+const API_RESTRICTIONS: &str = "/sapi/v1/account/apiRestrictions";
+/// Error code of a status query for an order Binance never booked
+const ORDER_DOES_NOT_EXIST: &str = "-2013";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Order {
+    order_id: u64,
+    status: String,
+    #[serde(deserialize_with = "de::f64_str")]
+    executed_qty: f64,
+    #[serde(deserialize_with = "de::f64_str")]
+    cummulative_quote_qty: f64,
+}
+
+impl From<Order> for OrderReport {
+    fn from(o: Order) -> Self {
+        let status = match o.status.as_str() {
+            "NEW" | "PARTIALLY_FILLED" | "PENDING_NEW" => OrderStatus::Open,
+            other => OrderStatus::finished(o.executed_qty, other == "FILLED"),
+        };
+        OrderReport {
+            order_id: o.order_id.to_string(),
+            status,
+            filled_base: o.executed_qty,
+            filled_quote: o.cummulative_quote_qty,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Account {
+    balances: Vec<Balance>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Balance {
+    asset: String,
+    #[serde(deserialize_with = "de::f64_str")]
+    free: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiRestrictions {
+    enable_spot_and_margin_trading: bool,
+    enable_withdrawals: bool,
+}
+
+/// Append the timestamp, receive window and signature to `params`
+fn signed_query(venue: &Venue<'_>, mut params: Vec<(&str, String)>) -> String {
+    params.push(("recvWindow", venue.recv_window_ms.to_string()));
+    params.push(("timestamp", unix_ms().to_string()));
+    let query = params
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>()
+        .join("&");
+    let signature = hmac_sha256_hex(venue.creds.secret.expose(), &query);
+    format!("{}&signature={}", query, signature)
+}
+
+pub async fn place(venue: &Venue<'_>, order: &OrderRequest) -> Result<OrderReport, ExchangeError> {
+    let mut params = vec![
+        ("symbol", order.symbol.clone()),
+        (
+            "side",
+            match order.side {
+                Side::Buy => "BUY",
+                Side::Sell => "SELL",
+            }
+            .to_string(),
+        ),
+        ("newClientOrderId", order.client_id.clone()),
+        ("newOrderRespType", "FULL".to_string()),
+    ];
+    match (order.order_type, order.side) {
+        (OrderType::Market, Side::Sell) => {
+            params.push(("type", "MARKET".to_string()));
+            params.push(("quantity", decimal(order.amount)));
+        }
+        (OrderType::Market, Side::Buy) => {
+            params.push(("type", "MARKET".to_string()));
+            params.push(("quoteOrderQty", decimal(order.amount)));
+        }
+        (OrderType::Ioc, _) => {
+            params.push(("type", "LIMIT".to_string()));
+            params.push(("timeInForce", "IOC".to_string()));
+            params.push(("quantity", decimal(order.amount)));
+            params.push(("price", decimal(order.price.unwrap_or_default())));
+        }
+    }
+    let url = format!("{}?{}", venue.url(ORDER), signed_query(venue, params));
+    let request = venue
+        .client
+        .post(url)
+        .header("X-MBX-APIKEY", &venue.creds.api_key);
+    let body = venue.send(request, ORDER).await?;
+    Ok(venue.decode::<Order>(ORDER, &body)?.into())
+}
+
+pub async fn query(
+    venue: &Venue<'_>,
+    symbol: &str,
+    order_id: &str,
+) -> Result<OrderReport, ExchangeError> {
+    get_order(venue, symbol, ("orderId", order_id)).await
+}
+
+/// Ask Binance to cancel a working order
+pub async fn cancel(venue: &Venue<'_>, symbol: &str, order_id: &str) -> Result<(), ExchangeError> {
+    let params = vec![
+        ("symbol", symbol.to_string()),
+        ("orderId", order_id.to_string()),
+    ];
+    let url = format!("{}?{}", venue.url(ORDER), signed_query(venue, params));
+    let request = venue
+        .client
+        .delete(url)
+        .header("X-MBX-APIKEY", &venue.creds.api_key);
+    venue.send(request, ORDER).await.map(drop)
+}
+
+/// The order sent as `client_id`, or `None` if Binance has no such order
+pub async fn find(
+    venue: &Venue<'_>,
+    symbol: &str,
+    client_id: &str,
+) -> Result<Option<OrderReport>, ExchangeError> {
+    match get_order(venue, symbol, ("origClientOrderId", client_id)).await {
+        Ok(report) => Ok(Some(report)),
+        Err(ExchangeError {
+            kind: ErrorKind::Api { code, .. },
+            ..
+        }) if code == ORDER_DOES_NOT_EXIST => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+async fn get_order(
+    venue: &Venue<'_>,
+    symbol: &str,
+    (key, id): (&'static str, &str),
+) -> Result<OrderReport, ExchangeError> {
+    let params = vec![("symbol", symbol.to_string()), (key, id.to_string())];
+    let url = format!("{}?{}", venue.url(ORDER), signed_query(venue, params));
+    let request = venue
+        .client
+        .get(url)
+        .header("X-MBX-APIKEY", &venue.creds.api_key);
+    let body = venue.send(request, ORDER).await?;
+    Ok(venue.decode::<Order>(ORDER, &body)?.into())
+}
+
+/// Free balance per venue ticker
+pub async fn balances(venue: &Venue<'_>) -> Result<Vec<(String, f64)>, ExchangeError> {
+    let params = vec![("omitZeroBalances", "true".to_string())];
+    let url = format!("{}?{}", venue.url(ACCOUNT), signed_query(venue, params));
+    let request = venue
+        .client
+        .get(url)
+        .header("X-MBX-APIKEY", &venue.creds.api_key);
+    let body = venue.send(request, ACCOUNT).await?;
+    let account = venue.decode::<Account>(ACCOUNT, &body)?;
+    Ok(account
+        .balances
+        .into_iter()
+        .map(|b| (b.asset, b.free))
+        .collect())
+}
+
+/// What the key may do, from its API restrictions
+pub async fn permissions(venue: &Venue<'_>) -> Result<Option<KeyPermissions>, ExchangeError> {
+    let url = format!(
+        "{}?{}",
+        venue.url(API_RESTRICTIONS),
+        signed_query(venue, Vec::new())
+    );
+    let request = venue
+        .client
+        .get(url)
+        .header("X-MBX-APIKEY", &venue.creds.api_key);
+    let body = venue.send(request, API_RESTRICTIONS).await?;
+    let restrictions = venue.decode::<ApiRestrictions>(API_RESTRICTIONS, &body)?;
+    Ok(Some(KeyPermissions {
+        trade: restrictions.enable_spot_and_margin_trading,
+        withdraw: restrictions.enable_withdrawals,
+    }))
+}
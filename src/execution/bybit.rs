@@ -0,0 +1,266 @@
+//! Bybit v5 spot orders, balances and key permissions. The signature is
+//! HMAC-SHA256 over timestamp, key, receive window and the JSON body or query
+//! string, sent in `X-BAPI-*` headers. Order creation only acknowledges the
+//! id, so fills come from a follow-up status query.
+
+use reqwest::RequestBuilder;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+
+use super::sign::hmac_sha256_hex;
+use super::{
+    decimal, unix_ms, KeyPermissions, OrderReport, OrderRequest, OrderStatus, OrderType, Venue,
+};
+use crate::exchanges::{de, ErrorKind, ExchangeError};
+use crate::models::Side;
+
+const CREATE: &str = "/v5/order/create";
+const REALTIME: &str = "/v5/order/realtime";
+const CANCEL: &str = "/v5/order/cancel";
+const WALLET: &str = "/v5/account/wallet-balance";
+const API_KEY_INFO: &str = "/v5/user/query-api";
+
+/// `retCode` is 0 on success; errors still come back as HTTP 200
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Envelope<T> {
+    ret_code: i64,
+    #[serde(default)]
+    ret_msg: String,
+    result: Option<T>,
+}
+
+impl<T> Envelope<T> {
+    fn into_result(self, endpoint: &str) -> Result<T, ExchangeError> {
+        match self.result {
+            Some(result) if self.ret_code == 0 => Ok(result),
+            _ => Err(ExchangeError::new(
+                "bybit",
+                endpoint,
+                ErrorKind::Api {
+                    code: self.ret_code.to_string(),
+                    message: self.ret_msg,
+                },
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Created {
+    order_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OrderList {
+    list: Vec<Order>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Order {
+    order_id: String,
+    order_status: String,
+    #[serde(deserialize_with = "de::f64_str")]
+    cum_exec_qty: f64,
+    #[serde(deserialize_with = "de::f64_str")]
+    cum_exec_value: f64,
+}
+
+impl From<Order> for OrderReport {
+    fn from(o: Order) -> Self {
+        let status = match o.order_status.as_str() {
+            "Created" | "New" | "PartiallyFilled" | "Untriggered" => OrderStatus::Open,
+            other => OrderStatus::finished(o.cum_exec_qty, other == "Filled"),
+        };
+        OrderReport {
+            order_id: o.order_id,
+            status,
+            filled_base: o.cum_exec_qty,
+            filled_quote: o.cum_exec_value,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct WalletList {
+    list: Vec<Wallet>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Wallet {
+    coin: Vec<Coin>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Coin {
+    coin: String,
+    #[serde(deserialize_with = "de::f64_str")]
+    wallet_balance: f64,
+    /// Reserved by open orders; `""` when none
+    #[serde(default, deserialize_with = "de::opt_f64_str")]
+    locked: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiKeyInfo {
+    /// 1 for read-only keys
+    read_only: u8,
+    /// Granted permissions per product, e.g. `"Spot": ["SpotTrade"]`
+    permissions: HashMap<String, Vec<String>>,
+}
+
+impl ApiKeyInfo {
+    fn grants(&self, product: &str, permission: &str) -> bool {
+        self.permissions
+            .get(product)
+            .is_some_and(|p| p.iter().any(|p| p == permission))
+    }
+}
+
+/// Add the auth headers for `payload` (JSON body or query string)
+fn signed(venue: &Venue<'_>, request: RequestBuilder, payload: &str) -> RequestBuilder {
+    let timestamp = unix_ms().to_string();
+    let recv_window = venue.recv_window_ms.to_string();
+    let signature = hmac_sha256_hex(
+        venue.creds.secret.expose(),
+        &format!(
+            "{}{}{}{}",
+            timestamp, venue.creds.api_key, recv_window, payload
+        ),
+    );
+    request
+        .header("X-BAPI-API-KEY", &venue.creds.api_key)
+        .header("X-BAPI-TIMESTAMP", timestamp)
+        .header("X-BAPI-RECV-WINDOW", recv_window)
+        .header("X-BAPI-SIGN", signature)
+}
+
+pub async fn place(venue: &Venue<'_>, order: &OrderRequest) -> Result<OrderReport, ExchangeError> {
+    let mut body = json!({
+        "category": "spot",
+        "symbol": order.symbol,
+        "side": match order.side {
+            Side::Buy => "Buy",
+            Side::Sell => "Sell",
+        },
+        "orderLinkId": order.client_id,
+    });
+    match order.order_type {
+        OrderType::Market => {
+            body["orderType"] = json!("Market");
+            body["qty"] = json!(decimal(order.amount));
+            body["marketUnit"] = json!(match order.side {
+                Side::Buy => "quoteCoin",
+                Side::Sell => "baseCoin",
+            });
+        }
+        OrderType::Ioc => {
+            body["orderType"] = json!("Limit");
+            body["timeInForce"] = json!("IOC");
+            body["qty"] = json!(decimal(order.amount));
+            body["price"] = json!(decimal(order.price.unwrap_or_default()));
+        }
+    }
+    let body = body.to_string();
+    let request = venue
+        .client
+        .post(venue.url(CREATE))
+        .header("Content-Type", "application/json");
+    let raw = venue
+        .send(signed(venue, request, &body).body(body), CREATE)
+        .await?;
+    let created = venue
+        .decode::<Envelope<Created>>(CREATE, &raw)?
+        .into_result(CREATE)?;
+    query(venue, &order.symbol, &created.order_id).await
+}
+
+pub async fn query(
+    venue: &Venue<'_>,
+    symbol: &str,
+    order_id: &str,
+) -> Result<OrderReport, ExchangeError> {
+    let found = get_order(venue, symbol, ("orderId", order_id)).await?;
+    // not yet visible to the query endpoint
+    Ok(found.unwrap_or_else(|| OrderReport {
+        order_id: order_id.to_string(),
+        status: OrderStatus::Open,
+        filled_base: 0.0,
+        filled_quote: 0.0,
+    }))
+}
+
+/// Ask Bybit to cancel a working order
+pub async fn cancel(venue: &Venue<'_>, symbol: &str, order_id: &str) -> Result<(), ExchangeError> {
+    let body = json!({ "category": "spot", "symbol": symbol, "orderId": order_id }).to_string();
+    let request = venue
+        .client
+        .post(venue.url(CANCEL))
+        .header("Content-Type", "application/json");
+    let raw = venue
+        .send(signed(venue, request, &body).body(body), CANCEL)
+        .await?;
+    venue
+        .decode::<Envelope<serde_json::Value>>(CANCEL, &raw)?
+        .into_result(CANCEL)
+        .map(drop)
+}
+
+/// The order sent as `client_id`, or `None` if Bybit lists no such order
+pub async fn find(
+    venue: &Venue<'_>,
+    symbol: &str,
+    client_id: &str,
+) -> Result<Option<OrderReport>, ExchangeError> {
+    get_order(venue, symbol, ("orderLinkId", client_id)).await
+}
+
+async fn get_order(
+    venue: &Venue<'_>,
+    symbol: &str,
+    (key, id): (&str, &str),
+) -> Result<Option<OrderReport>, ExchangeError> {
+    let query = format!("category=spot&symbol={}&{}={}", symbol, key, id);
+    let request = venue
+        .client
+        .get(format!("{}?{}", venue.url(REALTIME), query));
+    let raw = venue.send(signed(venue, request, &query), REALTIME).await?;
+    let orders = venue
+        .decode::<Envelope<OrderList>>(REALTIME, &raw)?
+        .into_result(REALTIME)?;
+    Ok(orders.list.into_iter().next().map(OrderReport::from))
+}
+
+/// Unified-account balance per venue ticker, less what open orders lock
+pub async fn balances(venue: &Venue<'_>) -> Result<Vec<(String, f64)>, ExchangeError> {
+    let query = "accountType=UNIFIED";
+    let request = venue.client.get(format!("{}?{}", venue.url(WALLET), query));
+    let raw = venue.send(signed(venue, request, query), WALLET).await?;
+    let wallets = venue
+        .decode::<Envelope<WalletList>>(WALLET, &raw)?
+        .into_result(WALLET)?;
+    Ok(wallets
+        .list
+        .into_iter()
+        .flat_map(|w| w.coin)
+        .map(|c| (c.coin, c.wallet_balance - c.locked.unwrap_or_default()))
+        .collect())
+}
+
+/// What the key may do, from its API key info
+pub async fn permissions(venue: &Venue<'_>) -> Result<Option<KeyPermissions>, ExchangeError> {
+    let request = venue.client.get(venue.url(API_KEY_INFO));
+    let raw = venue.send(signed(venue, request, ""), API_KEY_INFO).await?;
+    let info = venue
+        .decode::<Envelope<ApiKeyInfo>>(API_KEY_INFO, &raw)?
+        .into_result(API_KEY_INFO)?;
+    Ok(Some(KeyPermissions {
+        trade: info.read_only == 0 && info.grants("Spot", "SpotTrade"),
+        withdraw: info.grants("Wallet", "Withdraw"),
+    }))
+}
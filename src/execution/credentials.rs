@@ -0,0 +1,509 @@
+//! Trading keys: where they are kept and how they stay out of logs.
+//!
+//! Keys are read from a sealed file, encrypted with AES-256-GCM under a key
+//! derived from a passphrase with Argon2id, and from env vars, which win
+//! over the file. A venue may have keys for its main account and for any
+//! number of subaccounts; `execution.accounts` picks the one that trades.
+//! Secrets are wiped from memory when dropped and never formatted.
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+use zeroize::{Zeroize, Zeroizing};
+
+use super::EXECUTION_EXCHANGES;
+use crate::config::ExecutionConfig;
+use crate::exchanges::canonical_exchange;
+
+/// Account traded on venues `execution.accounts` doesn't list
+pub const MAIN_ACCOUNT: &str = "main";
+
+/// Shortest passphrase a key file may be sealed with
+const MIN_PASSPHRASE_LEN: usize = 12;
+
+const SEALED_VERSION: u32 = 1;
+const NONCE_LEN: usize = 12;
+const SALT_LEN: usize = 16;
+
+/// A secret string; wiped when dropped and redacted when formatted
+#[derive(Clone, Deserialize)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: String) -> Self {
+        Self(value)
+    }
+
+    /// The secret itself, for signing; never log it
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[redacted]")
+    }
+}
+
+/// Where a trading key was read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeySource {
+    File,
+    Env,
+}
+
+/// Trading API key of one venue account. Never logged or serialized.
+#[derive(Clone)]
+pub struct Credentials {
+    pub api_key: String,
+    pub secret: Secret,
+    /// KuCoin only
+    pub passphrase: Option<Secret>,
+    /// Subaccount name, or `main`
+    pub account: String,
+    pub source: KeySource,
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("account", &self.account)
+            .field("source", &self.source)
+            .finish_non_exhaustive()
+    }
+}
+
+/// One key as written in the plaintext file before sealing
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StoredKey {
+    api_key: String,
+    secret: Secret,
+    #[serde(default)]
+    passphrase: Option<Secret>,
+}
+
+/// Plaintext file layout: exchange → account → key
+type KeyFile = BTreeMap<String, BTreeMap<String, StoredKey>>;
+
+/// On-disk form of a sealed key file; binary fields are base64
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Sealed {
+    version: u32,
+    /// Argon2id memory (KiB), iterations and lanes
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    params: Params,
+) -> Result<Zeroizing<[u8; 32]>, String> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, key.as_mut())
+        .map_err(|e| format!("cannot derive the key file's key: {}", e))?;
+    Ok(key)
+}
+
+/// Encrypt `plaintext` under `passphrase`; returns the sealed file's JSON
+fn seal(plaintext: &[u8], passphrase: &str, params: Params) -> Result<String, String> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let key = derive_key(passphrase, &salt, params.clone())?;
+    let ciphertext = Aes256Gcm::new(key.as_ref().into())
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|_| "cannot encrypt the key file".to_string())?;
+    let sealed = Sealed {
+        version: SEALED_VERSION,
+        m_cost: params.m_cost(),
+        t_cost: params.t_cost(),
+        p_cost: params.p_cost(),
+        salt: STANDARD.encode(salt),
+        nonce: STANDARD.encode(nonce),
+        ciphertext: STANDARD.encode(ciphertext),
+    };
+    serde_json::to_string_pretty(&sealed).map_err(|e| e.to_string())
+}
+
+/// Decrypt a sealed file's JSON; a wrong passphrase and a tampered file
+/// look the same
+fn open(sealed: &str, passphrase: &str) -> Result<Zeroizing<Vec<u8>>, String> {
+    let sealed: Sealed =
+        serde_json::from_str(sealed).map_err(|e| format!("not a sealed key file: {}", e))?;
+    if sealed.version != SEALED_VERSION {
+        return Err(format!("unsupported key file version {}", sealed.version));
+    }
+    let bytes = |field: &str, value: &str| {
+        STANDARD
+            .decode(value)
+            .map_err(|_| format!("key file field `{}` is not base64", field))
+    };
+    let salt = bytes("salt", &sealed.salt)?;
+    let nonce = bytes("nonce", &sealed.nonce)?;
+    if nonce.len() != NONCE_LEN {
+        return Err("key file nonce has the wrong length".to_string());
+    }
+    let params = Params::new(sealed.m_cost, sealed.t_cost, sealed.p_cost, None)
+        .map_err(|e| format!("invalid key file parameters: {}", e))?;
+    let key = derive_key(passphrase, &salt, params)?;
+    Aes256Gcm::new(key.as_ref().into())
+        .decrypt(
+            Nonce::from_slice(&nonce),
+            bytes("ciphertext", &sealed.ciphertext)?.as_slice(),
+        )
+        .map(Zeroizing::new)
+        .map_err(|_| "cannot decrypt: wrong passphrase or damaged file".to_string())
+}
+
+/// Parse plaintext key file JSON into keys per (exchange, account)
+fn parse_key_file(raw: &[u8]) -> Result<HashMap<(String, String), Credentials>, String> {
+    let file: KeyFile =
+        serde_json::from_slice(raw).map_err(|e| format!("invalid key file: {}", e))?;
+    let mut keys = HashMap::new();
+    for (name, accounts) in file {
+        let exchange = canonical_exchange(&name)
+            .filter(|ex| EXECUTION_EXCHANGES.contains(ex))
+            .ok_or_else(|| {
+                format!(
+                    "key file: orders can't be sent to `{}` (supported: {})",
+                    name,
+                    EXECUTION_EXCHANGES.join(", ")
+                )
+            })?;
+        for (account, key) in accounts {
+            let creds = Credentials {
+                api_key: key.api_key,
+                secret: key.secret,
+                passphrase: key.passphrase,
+                account: account.clone(),
+                source: KeySource::File,
+            };
+            keys.insert((exchange.to_string(), account), creds);
+        }
+    }
+    Ok(keys)
+}
+
+/// Looks up an env var; the process environment outside of tests
+type Env<'a> = &'a dyn Fn(&str) -> Option<String>;
+
+fn process_env(var: &str) -> Option<String> {
+    std::env::var(var).ok()
+}
+
+/// The passphrase `credentials_file` is sealed with, from its env var
+fn passphrase(cfg: &ExecutionConfig, env: Env) -> Result<Zeroizing<String>, String> {
+    let var = &cfg.credentials_passphrase_env;
+    env(var).map(Zeroizing::new).ok_or_else(|| {
+        format!(
+            "execution.credentials_file: env var {} holding its passphrase is not set",
+            var
+        )
+    })
+}
+
+/// Encrypt the plaintext key file at `plain` into `cfg.credentials_file`
+/// (`--seal-credentials`); returns a line saying what was written
+pub fn seal_file(plain: &Path, cfg: &ExecutionConfig) -> Result<String, String> {
+    let Some(out) = &cfg.credentials_file else {
+        return Err("execution.credentials_file is not set".to_string());
+    };
+    let passphrase = passphrase(cfg, &process_env)?;
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(format!(
+            "the passphrase must be at least {} characters",
+            MIN_PASSPHRASE_LEN
+        ));
+    }
+    let raw = Zeroizing::new(
+        std::fs::read(plain).map_err(|e| format!("cannot read {}: {}", plain.display(), e))?,
+    );
+    let keys = parse_key_file(&raw)?;
+    let sealed = seal(&raw, &passphrase, Params::default())?;
+
+    write_private(out, sealed.as_bytes())
+        .map_err(|e| format!("cannot write {}: {}", out.display(), e))?;
+    Ok(format!(
+        "sealed {} keys into {}; the plaintext file can now be deleted",
+        keys.len(),
+        out.display()
+    ))
+}
+
+/// Write via a temp file readable by the owner only, so a crash never leaves
+/// a truncated file behind
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("tmp");
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(&tmp)?.write_all(contents)?;
+    std::fs::rename(&tmp, path)
+}
+
+/// Read a key, secret and passphrase from the named env vars
+fn from_env(
+    label: &str,
+    account: &str,
+    api_key_env: &str,
+    secret_env: &str,
+    passphrase_env: Option<&str>,
+    env: Env,
+) -> Result<Credentials, String> {
+    let var =
+        |name: &str| env(name).ok_or_else(|| format!("{}: env var {} is not set", label, name));
+    Ok(Credentials {
+        api_key: var(api_key_env)?,
+        secret: Secret::new(var(secret_env)?),
+        passphrase: passphrase_env.map(var).transpose()?.map(Secret::new),
+        account: account.to_string(),
+        source: KeySource::Env,
+    })
+}
+
+/// Keys of the accounts that trade, keyed by exchange: on each venue, the
+/// account `execution.accounts` names, or `main`. Env vars override keys of
+/// the same account in `credentials_file`.
+pub fn load_credentials(
+    cfg: &ExecutionConfig,
+) -> Result<HashMap<String, Credentials>, Vec<String>> {
+    load_from(cfg, &process_env)
+}
+
+/// [`load_credentials`] with env vars read from `env`
+fn load_from(cfg: &ExecutionConfig, env: Env) -> Result<HashMap<String, Credentials>, Vec<String>> {
+    let mut problems = Vec::new();
+    let mut keys = HashMap::new();
+
+    if let Some(path) = &cfg.credentials_file {
+        let read = || {
+            let passphrase = passphrase(cfg, env)?;
+            let sealed = std::fs::read_to_string(path)
+                .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+            let raw = open(&sealed, &passphrase)
+                .map_err(|e| format!("execution.credentials_file {}: {}", path.display(), e))?;
+            parse_key_file(&raw)
+        };
+        match read() {
+            Ok(file_keys) => keys.extend(file_keys),
+            // accounts can't be matched without the file's keys
+            Err(e) => return Err(vec![e]),
+        }
+    }
+
+    for (exchange, c) in &cfg.credentials {
+        let label = format!("execution.credentials.{}", exchange);
+        match (&c.api_key_env, &c.secret_env) {
+            (Some(key), Some(secret)) => {
+                match from_env(
+                    &label,
+                    MAIN_ACCOUNT,
+                    key,
+                    secret,
+                    c.passphrase_env.as_deref(),
+                    env,
+                ) {
+                    Ok(creds) => {
+                        keys.insert((exchange.clone(), MAIN_ACCOUNT.to_string()), creds);
+                    }
+                    Err(e) => problems.push(e),
+                }
+            }
+            (None, None) => {}
+            _ => problems.push(format!(
+                "{}: api_key_env and secret_env must be set together",
+                label
+            )),
+        }
+        for (account, sub) in &c.subaccounts {
+            let label = format!("{}.subaccounts.{}", label, account);
+            match from_env(
+                &label,
+                account,
+                &sub.api_key_env,
+                &sub.secret_env,
+                sub.passphrase_env.as_deref(),
+                env,
+            ) {
+                Ok(creds) => {
+                    keys.insert((exchange.clone(), account.clone()), creds);
+                }
+                Err(e) => problems.push(e),
+            }
+        }
+    }
+
+    let mut trading = HashMap::new();
+    for exchange in EXECUTION_EXCHANGES {
+        let account = cfg
+            .accounts
+            .get(*exchange)
+            .map(String::as_str)
+            .unwrap_or(MAIN_ACCOUNT);
+        match keys.remove(&(exchange.to_string(), account.to_string())) {
+            Some(creds) if *exchange == "kucoin" && creds.passphrase.is_none() => problems.push(
+                format!("kucoin `{}` account: the key needs its passphrase", account),
+            ),
+            Some(creds) => {
+                trading.insert(exchange.to_string(), creds);
+            }
+            None if cfg.accounts.contains_key(*exchange) => problems.push(format!(
+                "execution.accounts.{}: no key for account `{}`",
+                exchange, account
+            )),
+            None => {}
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(trading)
+    } else {
+        Err(problems)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{CredentialsConfig, KeyEnvConfig};
+    use std::path::PathBuf;
+
+    /// Fast enough for debug-build tests; files are sealed with the defaults
+    fn cheap() -> Params {
+        Params::new(256, 1, 1, None).unwrap()
+    }
+
+    fn key_file(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("arb-credentials-{}-{}", name, std::process::id()))
+            .join("credentials.sealed")
+    }
+
+    const PLAINTEXT: &str = r#"{
+        "binance": {
+            "main": { "api_key": "file-main", "secret": "file-main-secret" },
+            "arb": { "api_key": "file-arb", "secret": "file-arb-secret" }
+        },
+        "gate": {
+            "main": { "api_key": "file-gate", "secret": "file-gate-secret" }
+        }
+    }"#;
+
+    #[test]
+    fn sealed_files_open_only_with_their_passphrase() {
+        let sealed = seal(PLAINTEXT.as_bytes(), "correct horse battery", cheap()).unwrap();
+        assert!(!sealed.contains("file-main-secret"));
+
+        let opened = open(&sealed, "correct horse battery").unwrap();
+        assert_eq!(opened.as_slice(), PLAINTEXT.as_bytes());
+        assert!(open(&sealed, "wrong horse battery").is_err());
+
+        let mut tampered: Sealed = serde_json::from_str(&sealed).unwrap();
+        tampered.ciphertext = STANDARD.encode(b"not what was sealed");
+        let tampered = serde_json::to_string(&tampered).unwrap();
+        assert!(open(&tampered, "correct horse battery").is_err());
+    }
+
+    #[test]
+    fn selected_subaccounts_trade_and_env_keys_override_the_file() {
+        let path = key_file("load");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let sealed = seal(PLAINTEXT.as_bytes(), "correct horse battery", cheap()).unwrap();
+        std::fs::write(&path, sealed).unwrap();
+        let env_with = |passphrase: &'static str| {
+            move |var: &str| {
+                let value = match var {
+                    "ARB_TEST_CREDENTIALS_PASSPHRASE" => passphrase,
+                    "ARB_TEST_GATE_KEY" => "env-gate",
+                    "ARB_TEST_GATE_SECRET" => "env-gate-secret",
+                    _ => return None,
+                };
+                Some(value.to_string())
+            }
+        };
+        let env = env_with("correct horse battery");
+
+        let mut cfg = ExecutionConfig {
+            credentials_file: Some(path.clone()),
+            credentials_passphrase_env: "ARB_TEST_CREDENTIALS_PASSPHRASE".to_string(),
+            accounts: HashMap::from([("binance".to_string(), "arb".to_string())]),
+            credentials: HashMap::from([(
+                "gateio".to_string(),
+                CredentialsConfig {
+                    api_key_env: Some("ARB_TEST_GATE_KEY".to_string()),
+                    secret_env: Some("ARB_TEST_GATE_SECRET".to_string()),
+                    ..CredentialsConfig::default()
+                },
+            )]),
+            ..ExecutionConfig::default()
+        };
+        let keys = load_from(&cfg, &env).unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys["binance"].api_key, "file-arb");
+        assert_eq!(keys["binance"].account, "arb");
+        assert_eq!(keys["gateio"].api_key, "env-gate");
+        assert_eq!(keys["gateio"].source, KeySource::Env);
+        assert_eq!(keys["gateio"].secret.expose(), "env-gate-secret");
+
+        // nothing secret is ever formatted
+        let debug = format!("{:?}", keys);
+        assert!(debug.contains("account: \"arb\""));
+        assert!(!debug.contains("file-arb") && !debug.contains("env-gate"));
+
+        cfg.accounts
+            .insert("binance".to_string(), "missing".to_string());
+        cfg.credentials.get_mut("gateio").unwrap().subaccounts = HashMap::from([(
+            "unset".to_string(),
+            KeyEnvConfig {
+                api_key_env: "ARB_TEST_UNSET_KEY".to_string(),
+                secret_env: "ARB_TEST_UNSET_SECRET".to_string(),
+                passphrase_env: None,
+            },
+        )]);
+        let problems = load_from(&cfg, &env).unwrap_err();
+        assert_eq!(problems.len(), 2);
+        assert!(problems
+            .iter()
+            .any(|p| p.contains("no key for account `missing`")));
+        assert!(problems
+            .iter()
+            .any(|p| p.contains("subaccounts.unset: env var ARB_TEST_UNSET_KEY")));
+
+        cfg.accounts.clear();
+        cfg.credentials.clear();
+        let problems = load_from(&cfg, &env_with("wrong horse battery")).unwrap_err();
+        assert!(problems[0].contains("wrong passphrase"));
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
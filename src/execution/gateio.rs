@@ -0,0 +1,175 @@
+//! Gate v4 spot orders and balances. The signature is hex HMAC-SHA512 over
+//! method, path, query, the SHA-512 of the body and a timestamp in seconds,
+//! sent in the `KEY`, `Timestamp` and `SIGN` headers. Gate doesn't report
+//! what a key may do, so keys are only checked to authenticate.
+
+use reqwest::{Method, RequestBuilder};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::sign::{hmac_sha512_hex, sha512_hex};
+use super::{
+    decimal, unix_ms, KeyPermissions, OrderReport, OrderRequest, OrderStatus, OrderType, Venue,
+};
+use crate::exchanges::{de, ErrorKind, ExchangeError};
+use crate::models::Side;
+
+const ORDERS: &str = "/api/v4/spot/orders";
+const ACCOUNTS: &str = "/api/v4/spot/accounts";
+const ACCOUNT_DETAIL: &str = "/api/v4/account/detail";
+
+#[derive(Debug, Deserialize)]
+struct Order {
+    id: String,
+    /// `open`, `closed` or `cancelled`
+    status: String,
+    /// Why a closed order ended: `filled`, `ioc`, `cancelled`, ...
+    #[serde(default)]
+    finish_as: String,
+    #[serde(deserialize_with = "de::f64_str")]
+    filled_amount: f64,
+    #[serde(deserialize_with = "de::f64_str")]
+    filled_total: f64,
+}
+
+impl From<Order> for OrderReport {
+    fn from(o: Order) -> Self {
+        let status = if o.status == "open" {
+            OrderStatus::Open
+        } else {
+            OrderStatus::finished(o.filled_amount, o.finish_as == "filled")
+        };
+        OrderReport {
+            order_id: o.id,
+            status,
+            filled_base: o.filled_amount,
+            filled_quote: o.filled_total,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Account {
+    currency: String,
+    #[serde(deserialize_with = "de::f64_str")]
+    available: f64,
+}
+
+/// Add the auth headers for a request to `path?query`
+fn signed(
+    venue: &Venue<'_>,
+    method: Method,
+    path: &str,
+    query: &str,
+    body: &str,
+) -> RequestBuilder {
+    let timestamp = (unix_ms() / 1000).to_string();
+    let payload = format!(
+        "{}\n{}\n{}\n{}\n{}",
+        method.as_str(),
+        path,
+        query,
+        sha512_hex(body),
+        timestamp
+    );
+    let mut url = venue.url(path);
+    if !query.is_empty() {
+        url = format!("{}?{}", url, query);
+    }
+    venue
+        .client
+        .request(method, url)
+        .header("KEY", &venue.creds.api_key)
+        .header("Timestamp", timestamp)
+        .header(
+            "SIGN",
+            hmac_sha512_hex(venue.creds.secret.expose(), &payload),
+        )
+}
+
+pub async fn place(venue: &Venue<'_>, order: &OrderRequest) -> Result<OrderReport, ExchangeError> {
+    let mut body = json!({
+        "text": order.client_id,
+        "currency_pair": order.symbol,
+        "account": "spot",
+        "side": match order.side {
+            Side::Buy => "buy",
+            Side::Sell => "sell",
+        },
+        "time_in_force": "ioc",
+    });
+    match order.order_type {
+        // a market buy's amount is quote
+        OrderType::Market => {
+            body["type"] = json!("market");
+            body["amount"] = json!(decimal(order.amount));
+        }
+        OrderType::Ioc => {
+            body["type"] = json!("limit");
+            body["amount"] = json!(decimal(order.amount));
+            body["price"] = json!(decimal(order.price.unwrap_or_default()));
+        }
+    }
+    let body = body.to_string();
+    let request = signed(venue, Method::POST, ORDERS, "", &body)
+        .header("Content-Type", "application/json")
+        .body(body);
+    let raw = venue.send(request, ORDERS).await?;
+    Ok(venue.decode::<Order>(ORDERS, &raw)?.into())
+}
+
+pub async fn query(
+    venue: &Venue<'_>,
+    symbol: &str,
+    order_id: &str,
+) -> Result<OrderReport, ExchangeError> {
+    let path = format!("{}/{}", ORDERS, order_id);
+    let query = format!("currency_pair={}", symbol);
+    let request = signed(venue, Method::GET, &path, &query, "");
+    let raw = venue.send(request, ORDERS).await?;
+    Ok(venue.decode::<Order>(ORDERS, &raw)?.into())
+}
+
+/// Ask Gate to cancel a working order
+pub async fn cancel(venue: &Venue<'_>, symbol: &str, order_id: &str) -> Result<(), ExchangeError> {
+    let path = format!("{}/{}", ORDERS, order_id);
+    let query = format!("currency_pair={}", symbol);
+    let request = signed(venue, Method::DELETE, &path, &query, "");
+    venue.send(request, ORDERS).await.map(drop)
+}
+
+/// The order sent as `client_id`, or `None` if Gate has no such order.
+/// Orders can be looked up by their `text` in place of the id.
+pub async fn find(
+    venue: &Venue<'_>,
+    symbol: &str,
+    client_id: &str,
+) -> Result<Option<OrderReport>, ExchangeError> {
+    match query(venue, symbol, client_id).await {
+        Ok(report) => Ok(Some(report)),
+        Err(ExchangeError {
+            kind: ErrorKind::Api { code, .. },
+            ..
+        }) if code == "ORDER_NOT_FOUND" => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Available spot balance per venue ticker
+pub async fn balances(venue: &Venue<'_>) -> Result<Vec<(String, f64)>, ExchangeError> {
+    let request = signed(venue, Method::GET, ACCOUNTS, "", "");
+    let raw = venue.send(request, ACCOUNTS).await?;
+    let accounts = venue.decode::<Vec<Account>>(ACCOUNTS, &raw)?;
+    Ok(accounts
+        .into_iter()
+        .map(|a| (a.currency, a.available))
+        .collect())
+}
+
+/// Always `None`: the account details only prove the key authenticates
+pub async fn permissions(venue: &Venue<'_>) -> Result<Option<KeyPermissions>, ExchangeError> {
+    let request = signed(venue, Method::GET, ACCOUNT_DETAIL, "", "");
+    let raw = venue.send(request, ACCOUNT_DETAIL).await?;
+    venue.decode::<serde_json::Value>(ACCOUNT_DETAIL, &raw)?;
+    Ok(None)
+}
@@ -0,0 +1,223 @@
+//! KuCoin spot orders, balances and key permissions. The signature is base64
+//! HMAC-SHA256 over timestamp, method, path and body; with key version 2 the
+//! passphrase is sent signed with the same secret. Order creation only
+//! returns the id, so fills come from a follow-up status query.
+
+use reqwest::{Method, RequestBuilder};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::sign::hmac_sha256_base64;
+use super::{
+    decimal, unix_ms, KeyPermissions, OrderReport, OrderRequest, OrderStatus, OrderType, Secret,
+    Venue,
+};
+use crate::exchanges::{de, ErrorKind, ExchangeError};
+use crate::models::Side;
+
+const ORDERS: &str = "/api/v1/orders";
+const CLIENT_ORDER: &str = "/api/v1/order/client-order";
+const SUCCESS: &str = "200000";
+const ACCOUNTS: &str = "/api/v1/accounts";
+const API_KEY_INFO: &str = "/api/v1/user/api-key";
+
+/// `code` is `"200000"` on success
+#[derive(Debug, Deserialize)]
+struct Envelope<T> {
+    code: String,
+    #[serde(default)]
+    msg: String,
+    data: Option<T>,
+}
+
+impl<T> Envelope<T> {
+    fn into_data(self, endpoint: &str) -> Result<T, ExchangeError> {
+        match self.data {
+            Some(data) if self.code == SUCCESS => Ok(data),
+            _ => Err(ExchangeError::new(
+                "kucoin",
+                endpoint,
+                ErrorKind::Api {
+                    code: self.code,
+                    message: self.msg,
+                },
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Created {
+    order_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Order {
+    id: String,
+    is_active: bool,
+    /// Part of the order was cancelled (IOC remainder, market order out of
+    /// liquidity)
+    cancel_exist: bool,
+    #[serde(deserialize_with = "de::f64_str")]
+    deal_size: f64,
+    #[serde(deserialize_with = "de::f64_str")]
+    deal_funds: f64,
+}
+
+impl From<Order> for OrderReport {
+    fn from(o: Order) -> Self {
+        let status = if o.is_active {
+            OrderStatus::Open
+        } else {
+            OrderStatus::finished(o.deal_size, !o.cancel_exist)
+        };
+        OrderReport {
+            order_id: o.id,
+            status,
+            filled_base: o.deal_size,
+            filled_quote: o.deal_funds,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Account {
+    currency: String,
+    #[serde(deserialize_with = "de::f64_str")]
+    available: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiKeyInfo {
+    /// Comma-separated, e.g. `General,Spot`
+    permission: String,
+}
+
+/// Add the auth headers for a request to `path` (with its query string)
+fn signed(venue: &Venue<'_>, method: Method, path: &str, body: &str) -> RequestBuilder {
+    let secret = venue.creds.secret.expose();
+    let timestamp = unix_ms().to_string();
+    let signature = hmac_sha256_base64(
+        secret,
+        &format!("{}{}{}{}", timestamp, method.as_str(), path, body),
+    );
+    let passphrase = hmac_sha256_base64(
+        secret,
+        venue.creds.passphrase.as_ref().map_or("", Secret::expose),
+    );
+    venue
+        .client
+        .request(method, venue.url(path))
+        .header("KC-API-KEY", &venue.creds.api_key)
+        .header("KC-API-SIGN", signature)
+        .header("KC-API-TIMESTAMP", timestamp)
+        .header("KC-API-PASSPHRASE", passphrase)
+        .header("KC-API-KEY-VERSION", "2")
+}
+
+pub async fn place(venue: &Venue<'_>, order: &OrderRequest) -> Result<OrderReport, ExchangeError> {
+    let mut body = json!({
+        "clientOid": order.client_id,
+        "symbol": order.symbol,
+        "side": match order.side {
+            Side::Buy => "buy",
+            Side::Sell => "sell",
+        },
+    });
+    match (order.order_type, order.side) {
+        (OrderType::Market, Side::Sell) => {
+            body["type"] = json!("market");
+            body["size"] = json!(decimal(order.amount));
+        }
+        (OrderType::Market, Side::Buy) => {
+            body["type"] = json!("market");
+            body["funds"] = json!(decimal(order.amount));
+        }
+        (OrderType::Ioc, _) => {
+            body["type"] = json!("limit");
+            body["timeInForce"] = json!("IOC");
+            body["size"] = json!(decimal(order.amount));
+            body["price"] = json!(decimal(order.price.unwrap_or_default()));
+        }
+    }
+    let body = body.to_string();
+    let request = signed(venue, Method::POST, ORDERS, &body)
+        .header("Content-Type", "application/json")
+        .body(body);
+    let raw = venue.send(request, ORDERS).await?;
+    let created = venue
+        .decode::<Envelope<Created>>(ORDERS, &raw)?
+        .into_data(ORDERS)?;
+    query(venue, &created.order_id).await
+}
+
+pub async fn query(venue: &Venue<'_>, order_id: &str) -> Result<OrderReport, ExchangeError> {
+    let path = format!("{}/{}", ORDERS, order_id);
+    let request = signed(venue, Method::GET, &path, "");
+    let raw = venue.send(request, ORDERS).await?;
+    Ok(venue
+        .decode::<Envelope<Order>>(ORDERS, &raw)?
+        .into_data(ORDERS)?
+        .into())
+}
+
+/// Ask KuCoin to cancel a working order
+pub async fn cancel(venue: &Venue<'_>, order_id: &str) -> Result<(), ExchangeError> {
+    let path = format!("{}/{}", ORDERS, order_id);
+    let request = signed(venue, Method::DELETE, &path, "");
+    let raw = venue.send(request, ORDERS).await?;
+    venue
+        .decode::<Envelope<serde_json::Value>>(ORDERS, &raw)?
+        .into_data(ORDERS)
+        .map(drop)
+}
+
+/// The order sent as `client_id`; KuCoin answers with no `data` when it
+/// has no such order
+pub async fn find(
+    venue: &Venue<'_>,
+    client_id: &str,
+) -> Result<Option<OrderReport>, ExchangeError> {
+    let path = format!("{}/{}", CLIENT_ORDER, client_id);
+    let request = signed(venue, Method::GET, &path, "");
+    let raw = venue.send(request, CLIENT_ORDER).await?;
+    let envelope = venue.decode::<Envelope<Order>>(CLIENT_ORDER, &raw)?;
+    if envelope.code == SUCCESS && envelope.data.is_none() {
+        return Ok(None);
+    }
+    Ok(Some(envelope.into_data(CLIENT_ORDER)?.into()))
+}
+
+/// Available balance of the trading account per venue ticker
+pub async fn balances(venue: &Venue<'_>) -> Result<Vec<(String, f64)>, ExchangeError> {
+    let path = format!("{}?type=trade", ACCOUNTS);
+    let request = signed(venue, Method::GET, &path, "");
+    let raw = venue.send(request, ACCOUNTS).await?;
+    let accounts = venue
+        .decode::<Envelope<Vec<Account>>>(ACCOUNTS, &raw)?
+        .into_data(ACCOUNTS)?;
+    Ok(accounts
+        .into_iter()
+        .map(|a| (a.currency, a.available))
+        .collect())
+}
+
+/// What the key may do, from its API key info
+pub async fn permissions(venue: &Venue<'_>) -> Result<Option<KeyPermissions>, ExchangeError> {
+    let request = signed(venue, Method::GET, API_KEY_INFO, "");
+    let raw = venue.send(request, API_KEY_INFO).await?;
+    let info = venue
+        .decode::<Envelope<ApiKeyInfo>>(API_KEY_INFO, &raw)?
+        .into_data(API_KEY_INFO)?;
+    let granted: Vec<String> = info
+        .permission
+        .split(',')
+        .map(|p| p.trim().to_lowercase())
+        .collect();
+    Ok(Some(KeyPermissions {
+        trade: granted.iter().any(|p| p == "spot"),
+        withdraw: granted.iter().any(|p| p.starts_with("withdraw")),
+    }))
+}
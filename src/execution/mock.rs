@@ -0,0 +1,723 @@
+//! Local stand-in for the trading APIs of every venue in
+//! `EXECUTION_EXCHANGES`, used by the execution tests.
+//!
+//! It checks each venue's signature the way the venue documents it, fills
+//! orders against fixed order books (honoring IOC limit prices), reports
+//! fixed account balances and key permissions, and answers in the venue's
+//! response shape, including its error format. Orders can be looked up by
+//! venue id or client id and cancelled, a symbol's orders can be left
+//! working, and its order responses can be lost.
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, Method, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Json, Router,
+};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+
+use super::sign::{hmac_sha256_base64, hmac_sha256_hex, hmac_sha512_hex, sha512_hex};
+use super::{BalanceBook, Credentials, ExecutionContext, KeySource, OrderType, RiskGuard, Secret};
+use crate::assets::AssetRegistry;
+use crate::config::{AssetsConfig, ExecutionConfig, HttpConfig};
+use crate::exchanges::{HttpClient, Markets, OrderBook, OrderRules, RateBudgets};
+use crate::models::{Side, TradeLeg};
+
+pub const API_KEY: &str = "mock-key";
+pub const SECRET: &str = "mock-secret";
+pub const PASSPHRASE: &str = "mock-passphrase";
+
+pub fn credentials() -> Credentials {
+    Credentials {
+        api_key: API_KEY.to_string(),
+        secret: Secret::new(SECRET.to_string()),
+        passphrase: Some(Secret::new(PASSPHRASE.to_string())),
+        account: "main".to_string(),
+        source: KeySource::Env,
+    }
+}
+
+/// An order as the mock booked it
+#[derive(Debug, Clone)]
+pub struct MockOrder {
+    pub id: String,
+    pub client_id: String,
+    pub symbol: String,
+    pub filled_base: f64,
+    pub filled_quote: f64,
+    pub complete: bool,
+    /// Reported as still working until cancelled
+    pub open: bool,
+}
+
+/// How the response to an order on a lossy symbol goes wrong
+#[derive(Debug, Clone, Copy)]
+pub enum Lost {
+    /// The order is booked, then a 502 comes back
+    Booked,
+    /// The order is booked, then a 200 that isn't JSON comes back
+    Garbled,
+    /// The order never reaches the book; a 502 comes back
+    Dropped,
+}
+
+#[derive(Default)]
+struct Inner {
+    books: HashMap<String, OrderBook>,
+    /// Symbols whose orders are rejected as if the balance were short
+    rejected: HashSet<String>,
+    /// Symbols whose order responses are lost
+    lost: HashMap<String, Lost>,
+    /// Symbols whose orders stay open, and whether a cancel closes them
+    working: HashMap<String, bool>,
+    orders: Vec<MockOrder>,
+    /// Free balance per venue ticker
+    balances: Vec<(String, f64)>,
+    /// The key reports it may withdraw
+    withdrawals: bool,
+}
+
+#[derive(Clone)]
+pub struct MockExchange {
+    pub base_url: String,
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl MockExchange {
+    /// Serve `books` (keyed by venue symbol) on an ephemeral local port
+    pub async fn start(books: HashMap<String, OrderBook>) -> Self {
+        let inner = Arc::new(Mutex::new(Inner {
+            books,
+            ..Inner::default()
+        }));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let app = Router::new().fallback(handle).with_state(inner.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        Self { base_url, inner }
+    }
+
+    pub fn set_book(&self, symbol: &str, book: OrderBook) {
+        self.inner
+            .lock()
+            .unwrap()
+            .books
+            .insert(symbol.to_string(), book);
+    }
+
+    pub fn reject(&self, symbol: &str) {
+        self.inner
+            .lock()
+            .unwrap()
+            .rejected
+            .insert(symbol.to_string());
+    }
+
+    pub fn lose(&self, symbol: &str, how: Lost) {
+        self.inner
+            .lock()
+            .unwrap()
+            .lost
+            .insert(symbol.to_string(), how);
+    }
+
+    /// Leave orders on `symbol` working after they fill, until cancelled
+    /// if `cancellable`
+    pub fn keep_open(&self, symbol: &str, cancellable: bool) {
+        self.inner
+            .lock()
+            .unwrap()
+            .working
+            .insert(symbol.to_string(), cancellable);
+    }
+
+    pub fn set_balances(&self, balances: &[(&str, f64)]) {
+        self.inner.lock().unwrap().balances = balances
+            .iter()
+            .map(|(ticker, free)| (ticker.to_string(), *free))
+            .collect();
+    }
+
+    pub fn allow_withdrawals(&self) {
+        self.inner.lock().unwrap().withdrawals = true;
+    }
+
+    pub fn orders(&self) -> Vec<MockOrder> {
+        self.inner.lock().unwrap().orders.clone()
+    }
+}
+
+/// Order as parsed from any venue's request
+struct Placed {
+    symbol: String,
+    client_id: String,
+    buy: bool,
+    /// Base to trade, or quote to spend for a market buy
+    amount: f64,
+    amount_is_quote: bool,
+    limit: Option<f64>,
+}
+
+/// Take liquidity within the limit price; returns (base, quote, complete)
+fn fill(book: &mut OrderBook, order: &Placed) -> (f64, f64, bool) {
+    let levels = if order.buy {
+        &mut book.asks
+    } else {
+        &mut book.bids
+    };
+    let (mut base, mut quote, mut left) = (0.0, 0.0, order.amount);
+    for (price, size) in levels.iter_mut() {
+        let (price, available) = (*price, *size);
+        let beyond = order.limit.is_some_and(|limit| {
+            if order.buy {
+                price > limit
+            } else {
+                price < limit
+            }
+        });
+        if left <= 1e-12 || beyond {
+            break;
+        }
+        let take = if order.amount_is_quote {
+            (left / price).min(available)
+        } else {
+            left.min(available)
+        };
+        *size -= take;
+        base += take;
+        quote += take * price;
+        left -= if order.amount_is_quote {
+            take * price
+        } else {
+            take
+        };
+    }
+    levels.retain(|&(_, size)| size > 1e-12);
+    (base, quote, left <= order.amount * 1e-9)
+}
+
+fn num(v: &Value, field: &str) -> f64 {
+    v[field]
+        .as_str()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0.0)
+}
+
+fn text(v: &Value, field: &str) -> String {
+    v[field].as_str().unwrap_or_default().to_string()
+}
+
+fn header(headers: &HeaderMap, name: &str) -> String {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn form(query: &str) -> Value {
+    let map: serde_json::Map<String, Value> = query
+        .split('&')
+        .filter_map(|kv| kv.split_once('='))
+        .map(|(k, v)| (k.to_string(), json!(v)))
+        .collect();
+    Value::Object(map)
+}
+
+fn signed_ok(method: &Method, uri: &Uri, headers: &HeaderMap, body: &str) -> bool {
+    let path = uri.path();
+    let query = uri.query().unwrap_or_default();
+    if path.starts_with("/api/v3/") || path.starts_with("/sapi/") {
+        let Some((payload, signature)) = query.split_once("&signature=") else {
+            return false;
+        };
+        header(headers, "X-MBX-APIKEY") == API_KEY && hmac_sha256_hex(SECRET, payload) == signature
+    } else if path.starts_with("/v5/") {
+        let payload = if *method == Method::GET { query } else { body };
+        let expected = hmac_sha256_hex(
+            SECRET,
+            &format!(
+                "{}{}{}{}",
+                header(headers, "X-BAPI-TIMESTAMP"),
+                API_KEY,
+                header(headers, "X-BAPI-RECV-WINDOW"),
+                payload
+            ),
+        );
+        header(headers, "X-BAPI-API-KEY") == API_KEY && header(headers, "X-BAPI-SIGN") == expected
+    } else if path.starts_with("/api/v1/") {
+        let expected = hmac_sha256_base64(
+            SECRET,
+            &format!(
+                "{}{}{}{}",
+                header(headers, "KC-API-TIMESTAMP"),
+                method.as_str(),
+                uri.path_and_query().map(|p| p.as_str()).unwrap_or(path),
+                body
+            ),
+        );
+        header(headers, "KC-API-KEY") == API_KEY
+            && header(headers, "KC-API-SIGN") == expected
+            && header(headers, "KC-API-PASSPHRASE") == hmac_sha256_base64(SECRET, PASSPHRASE)
+            && header(headers, "KC-API-KEY-VERSION") == "2"
+    } else {
+        let expected = hmac_sha512_hex(
+            SECRET,
+            &format!(
+                "{}\n{}\n{}\n{}\n{}",
+                method.as_str(),
+                path,
+                query,
+                sha512_hex(body),
+                header(headers, "Timestamp")
+            ),
+        );
+        header(headers, "KEY") == API_KEY && header(headers, "SIGN") == expected
+    }
+}
+
+/// Parse an order from the venue-specific request
+fn parse(path: &str, query: &str, body: &str) -> Option<Placed> {
+    let v = if path.starts_with("/api/v3/") {
+        form(query)
+    } else {
+        serde_json::from_str(body).ok()?
+    };
+    let placed = match path {
+        "/api/v3/order" => {
+            let quote = num(&v, "quoteOrderQty");
+            Placed {
+                symbol: text(&v, "symbol"),
+                client_id: text(&v, "newClientOrderId"),
+                buy: text(&v, "side") == "BUY",
+                amount: if quote > 0.0 {
+                    quote
+                } else {
+                    num(&v, "quantity")
+                },
+                amount_is_quote: quote > 0.0,
+                limit: (text(&v, "timeInForce") == "IOC").then(|| num(&v, "price")),
+            }
+        }
+        "/v5/order/create" => Placed {
+            symbol: text(&v, "symbol"),
+            client_id: text(&v, "orderLinkId"),
+            buy: text(&v, "side") == "Buy",
+            amount: num(&v, "qty"),
+            amount_is_quote: text(&v, "marketUnit") == "quoteCoin",
+            limit: (text(&v, "orderType") == "Limit").then(|| num(&v, "price")),
+        },
+        "/api/v1/orders" => {
+            let funds = num(&v, "funds");
+            Placed {
+                symbol: text(&v, "symbol"),
+                client_id: text(&v, "clientOid"),
+                buy: text(&v, "side") == "buy",
+                amount: if funds > 0.0 { funds } else { num(&v, "size") },
+                amount_is_quote: funds > 0.0,
+                limit: (text(&v, "type") == "limit").then(|| num(&v, "price")),
+            }
+        }
+        "/api/v4/spot/orders" => {
+            let market = text(&v, "type") == "market";
+            let buy = text(&v, "side") == "buy";
+            Placed {
+                symbol: text(&v, "currency_pair"),
+                client_id: text(&v, "text"),
+                buy,
+                amount: num(&v, "amount"),
+                amount_is_quote: market && buy,
+                limit: (!market).then(|| num(&v, "price")),
+            }
+        }
+        _ => return None,
+    };
+    Some(placed)
+}
+
+/// Error body in the venue's own format
+fn venue_error(path: &str, status: StatusCode, message: &str) -> Response {
+    let (status, body) = if path.starts_with("/api/v3/") {
+        (status, json!({ "code": -2010, "msg": message }))
+    } else if path.starts_with("/v5/") {
+        (
+            StatusCode::OK,
+            json!({ "retCode": 170131, "retMsg": message, "result": {} }),
+        )
+    } else if path.starts_with("/api/v1/") {
+        (StatusCode::OK, json!({ "code": "200004", "msg": message }))
+    } else {
+        (
+            status,
+            json!({ "label": "BALANCE_NOT_ENOUGH", "message": message }),
+        )
+    };
+    (status, Json(body)).into_response()
+}
+
+/// Answer to a status query for an order the venue doesn't know
+fn not_found(path: &str) -> Response {
+    let (status, body) = if path.starts_with("/api/v3/") {
+        (
+            StatusCode::BAD_REQUEST,
+            json!({ "code": -2013, "msg": "Order does not exist." }),
+        )
+    } else if path.starts_with("/v5/") {
+        (
+            StatusCode::OK,
+            json!({ "retCode": 0, "retMsg": "OK", "result": { "list": [] } }),
+        )
+    } else if path.starts_with("/api/v1/") {
+        (StatusCode::OK, json!({ "code": "200000", "data": null }))
+    } else {
+        (
+            StatusCode::NOT_FOUND,
+            json!({ "label": "ORDER_NOT_FOUND", "message": "Order not found" }),
+        )
+    };
+    (status, Json(body)).into_response()
+}
+
+fn bad_gateway() -> Response {
+    (StatusCode::BAD_GATEWAY, "<html>502 Bad Gateway</html>").into_response()
+}
+
+/// Order in the venue's response shape
+fn order_json(path: &str, order: &MockOrder) -> Value {
+    let (base, quote) = (
+        order.filled_base.to_string(),
+        order.filled_quote.to_string(),
+    );
+    let traded = order.filled_base > 0.0;
+    if path.starts_with("/api/v3/") {
+        let status = match (order.open, order.complete) {
+            (true, _) => "NEW",
+            (false, true) => "FILLED",
+            (false, false) => "EXPIRED",
+        };
+        json!({
+            "symbol": order.symbol,
+            "orderId": order.id.parse::<u64>().unwrap(),
+            "clientOrderId": order.client_id,
+            "status": status,
+            "executedQty": base,
+            "cummulativeQuoteQty": quote,
+        })
+    } else if path.starts_with("/v5/") {
+        let status = match (order.open, order.complete, traded) {
+            (true, _, _) => "New",
+            (false, true, _) => "Filled",
+            (false, false, true) => "PartiallyFilledCanceled",
+            (false, false, false) => "Cancelled",
+        };
+        json!({ "retCode": 0, "retMsg": "OK", "result": { "list": [{
+            "orderId": order.id,
+            "orderLinkId": order.client_id,
+            "orderStatus": status,
+            "cumExecQty": base,
+            "cumExecValue": quote,
+        }]}})
+    } else if path.starts_with("/api/v1/") {
+        json!({ "code": "200000", "data": {
+            "id": order.id,
+            "clientOid": order.client_id,
+            "isActive": order.open,
+            "cancelExist": !order.complete,
+            "dealSize": base,
+            "dealFunds": quote,
+        }})
+    } else {
+        json!({
+            "id": order.id,
+            "text": order.client_id,
+            "status": match (order.open, order.complete) {
+                (true, _) => "open",
+                (false, true) => "closed",
+                (false, false) => "cancelled",
+            },
+            "finish_as": if order.complete { "filled" } else { "ioc" },
+            "filled_amount": base,
+            "filled_total": quote,
+        })
+    }
+}
+
+/// Balances in the venue's response shape, or `None` for other paths
+fn balances_json(path: &str, balances: &[(String, f64)]) -> Option<Value> {
+    let rows = balances
+        .iter()
+        .map(|(ticker, free)| (ticker, free.to_string()));
+    let body = match path {
+        "/api/v3/account" => json!({ "balances": rows
+            .map(|(asset, free)| json!({ "asset": asset, "free": free, "locked": "0" }))
+            .collect::<Vec<_>>() }),
+        "/v5/account/wallet-balance" => json!({ "retCode": 0, "retMsg": "OK", "result": {
+            "list": [{ "accountType": "UNIFIED", "coin": rows
+                .map(|(coin, free)| json!({ "coin": coin, "walletBalance": free, "locked": "" }))
+                .collect::<Vec<_>>() }],
+        }}),
+        "/api/v1/accounts" => json!({ "code": "200000", "data": rows
+            .map(|(currency, free)| json!({
+                "currency": currency, "type": "trade", "balance": free, "available": free,
+            }))
+            .collect::<Vec<_>>() }),
+        "/api/v4/spot/accounts" => json!(rows
+            .map(
+                |(currency, free)| json!({ "currency": currency, "available": free, "locked": "0" })
+            )
+            .collect::<Vec<_>>()),
+        _ => return None,
+    };
+    Some(body)
+}
+
+/// A trade-only key's info in the venue's response shape, or `None` for
+/// other paths
+fn permissions_json(path: &str, withdrawals: bool) -> Option<Value> {
+    let body = match path {
+        "/sapi/v1/account/apiRestrictions" => json!({
+            "ipRestrict": false,
+            "enableReading": true,
+            "enableSpotAndMarginTrading": true,
+            "enableWithdrawals": withdrawals,
+        }),
+        "/v5/user/query-api" => {
+            let wallet = if withdrawals {
+                vec!["AccountTransfer", "Withdraw"]
+            } else {
+                vec!["AccountTransfer"]
+            };
+            json!({ "retCode": 0, "retMsg": "OK", "result": {
+                "apiKey": API_KEY,
+                "readOnly": 0,
+                "permissions": { "Spot": ["SpotTrade"], "Wallet": wallet },
+            }})
+        }
+        "/api/v1/user/api-key" => json!({ "code": "200000", "data": {
+            "apiKey": API_KEY,
+            "permission": if withdrawals { "General,Spot,Withdrawal" } else { "General,Spot" },
+        }}),
+        "/api/v4/account/detail" => json!({ "user_id": 1, "key": { "mode": 1 } }),
+        _ => return None,
+    };
+    Some(body)
+}
+
+async fn handle(
+    State(inner): State<Arc<Mutex<Inner>>>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let body = String::from_utf8_lossy(&body).to_string();
+    let path = uri.path().to_string();
+    if !signed_ok(&method, &uri, &headers, &body) {
+        return venue_error(&path, StatusCode::UNAUTHORIZED, "invalid signature");
+    }
+    let mut inner = inner.lock().unwrap();
+
+    if method == Method::DELETE || path == "/v5/order/cancel" {
+        return cancel(&mut inner, &path, uri.query().unwrap_or_default(), &body);
+    }
+    if method == Method::POST {
+        let Some(placed) = parse(&path, uri.query().unwrap_or_default(), &body) else {
+            return StatusCode::NOT_FOUND.into_response();
+        };
+        if inner.rejected.contains(&placed.symbol) {
+            return venue_error(&path, StatusCode::BAD_REQUEST, "insufficient balance");
+        }
+        let lost = inner.lost.get(&placed.symbol).copied();
+        if let Some(Lost::Dropped) = lost {
+            return bad_gateway();
+        }
+        let Some(book) = inner.books.get_mut(&placed.symbol) else {
+            return venue_error(&path, StatusCode::BAD_REQUEST, "unknown symbol");
+        };
+        let (filled_base, filled_quote, complete) = fill(book, &placed);
+        let open = inner.working.contains_key(&placed.symbol);
+        let order = MockOrder {
+            id: (inner.orders.len() + 1000).to_string(),
+            client_id: placed.client_id,
+            symbol: placed.symbol,
+            filled_base,
+            filled_quote,
+            complete,
+            open,
+        };
+        inner.orders.push(order.clone());
+        match lost {
+            Some(Lost::Booked) => return bad_gateway(),
+            Some(Lost::Garbled) => return (StatusCode::OK, "{\"orderId\":").into_response(),
+            _ => {}
+        }
+        let ack = match path.as_str() {
+            "/v5/order/create" => {
+                json!({ "retCode": 0, "retMsg": "OK", "result": { "orderId": order.id } })
+            }
+            "/api/v1/orders" => json!({ "code": "200000", "data": { "orderId": order.id } }),
+            _ => order_json(&path, &order),
+        };
+        return Json(ack).into_response();
+    }
+
+    if let Some(balances) = balances_json(&path, &inner.balances) {
+        return Json(balances).into_response();
+    }
+    if let Some(permissions) = permissions_json(&path, inner.withdrawals) {
+        return Json(permissions).into_response();
+    }
+
+    // status query: the venue or client id is a param or the last path
+    // segment
+    let params = form(uri.query().unwrap_or_default());
+    let id = ["orderId", "origClientOrderId", "orderLinkId"]
+        .iter()
+        .find_map(|key| params[*key].as_str())
+        .unwrap_or_else(|| path.rsplit('/').next().unwrap_or_default())
+        .to_string();
+    match inner
+        .orders
+        .iter()
+        .find(|o| o.id == id || o.client_id == id)
+    {
+        Some(order) => Json(order_json(&path, order)).into_response(),
+        None => not_found(&path),
+    }
+}
+
+/// Cancel the order a request names by its id, answering in the venue's
+/// shape; an order on a symbol that can't be cancelled stays open
+fn cancel(inner: &mut Inner, path: &str, query: &str, body: &str) -> Response {
+    let id = if path == "/v5/order/cancel" {
+        serde_json::from_str::<Value>(body)
+            .map(|v| text(&v, "orderId"))
+            .unwrap_or_default()
+    } else {
+        match form(query)["orderId"].as_str() {
+            Some(id) => id.to_string(),
+            None => path.rsplit('/').next().unwrap_or_default().to_string(),
+        }
+    };
+    let working = &inner.working;
+    let Some(order) = inner.orders.iter_mut().find(|o| o.id == id) else {
+        return not_found(path);
+    };
+    if working.get(&order.symbol).copied().unwrap_or(true) {
+        order.open = false;
+    }
+    let body = if path.starts_with("/v5/") {
+        json!({ "retCode": 0, "retMsg": "OK", "result": { "orderId": order.id } })
+    } else if path.starts_with("/api/v1/") {
+        json!({ "code": "200000", "data": { "cancelledOrderIds": [order.id] } })
+    } else {
+        order_json(path, order)
+    };
+    Json(body).into_response()
+}
+
+pub fn http() -> Arc<HttpClient> {
+    let budgets = Arc::new(RateBudgets::new(&[]));
+    Arc::new(HttpClient::new(&HttpConfig::default(), budgets).unwrap())
+}
+
+/// Venue symbol of `base`/`quote` in the venue's own format
+pub fn symbol(exchange: &str, base: &str, quote: &str) -> String {
+    match exchange {
+        "kucoin" => format!("{}-{}", base, quote),
+        "gateio" => format!("{}_{}", base, quote),
+        _ => format!("{}{}", base, quote),
+    }
+}
+
+/// USDT → BTC → ETH → USDT: 1000 USDT buys 0.02 BTC, then 0.4 ETH,
+/// sold for 1040 USDT
+pub fn triangle(exchange: &str) -> [TradeLeg; 3] {
+    let leg = |base: &str, quote: &str, side, from: &str, to: &str, price| TradeLeg {
+        symbol: symbol(exchange, base, quote),
+        side,
+        from: from.to_string(),
+        to: to.to_string(),
+        price,
+    };
+    [
+        leg("BTC", "USDT", Side::Buy, "USDT", "BTC", 50_000.0),
+        leg("ETH", "BTC", Side::Buy, "BTC", "ETH", 0.05),
+        leg("ETH", "USDT", Side::Sell, "ETH", "USDT", 2_600.0),
+    ]
+}
+
+pub async fn venue(exchange: &str, btc_asks: Vec<(f64, f64)>) -> MockExchange {
+    let books = [
+        (
+            "BTC",
+            "USDT",
+            OrderBook::new(vec![(49_990.0, 5.0)], btc_asks),
+        ),
+        (
+            "ETH",
+            "BTC",
+            OrderBook::new(vec![(0.0499, 100.0)], vec![(0.05, 100.0)]),
+        ),
+        (
+            "ETH",
+            "USDT",
+            OrderBook::new(vec![(2_600.0, 100.0)], vec![(2_601.0, 100.0)]),
+        ),
+    ];
+    MockExchange::start(
+        books
+            .into_iter()
+            .map(|(base, quote, book)| (symbol(exchange, base, quote), book))
+            .collect(),
+    )
+    .await
+}
+
+/// Trade `exchange` on the mock with 1000 USDT per triangle and no fee
+pub fn context(
+    http: &Arc<HttpClient>,
+    mock: &MockExchange,
+    exchange: &str,
+    order_type: OrderType,
+) -> ExecutionContext {
+    ExecutionContext {
+        http: http.clone(),
+        credentials: Arc::new(HashMap::from([(exchange.to_string(), credentials())])),
+        config: ExecutionConfig {
+            enabled: true,
+            order_type,
+            start_amounts: HashMap::from([("USDT".to_string(), 1_000.0)]),
+            base_urls: HashMap::from([(exchange.to_string(), mock.base_url.clone())]),
+            ..ExecutionConfig::default()
+        },
+        fee_per_leg: 0.0,
+        assets: Arc::new(AssetRegistry::new(&AssetsConfig::default())),
+        balances: Arc::new(BalanceBook::default()),
+        risk: Arc::new(RiskGuard::new(None)),
+        markets: Some(markets(exchange, OrderRules::default())),
+    }
+}
+
+/// Listing of the triangle's three symbols, all with `rules`
+pub fn markets(exchange: &str, rules: OrderRules) -> Arc<Markets> {
+    let pairs = [("BTC", "USDT"), ("ETH", "BTC"), ("ETH", "USDT")];
+    let symbols: HashMap<String, (String, String)> = pairs
+        .iter()
+        .map(|(base, quote)| {
+            let pair = (base.to_string(), quote.to_string());
+            (symbol(exchange, base, quote), pair)
+        })
+        .collect();
+    let rules = symbols.keys().map(|s| (s.clone(), rules)).collect();
+    Arc::new(Markets::new(symbols.len(), symbols).with_rules(rules))
+}
+
+/// Equal up to the 8 decimals orders are sent with
+pub fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-7 * b.abs().max(1.0)
+}
@@ -0,0 +1,1246 @@
+//! Live execution of detected triangles.
+//!
+//! The three legs are sent one after another as market or IOC-limit
+//! orders, and each leg trades what the previous one actually received, so
+//! a partial fill shrinks the rest of the triangle instead of overspending.
+//! Orders are signed per venue and sent on the venue's transport but never
+//! retried blindly: a timed-out order may still have reached the book, and
+//! sending it again could trade twice. Such an order is looked up by its
+//! client id first, and left alone when the venue can't say. Each order is
+//! rounded to its symbol's quantity step and price tick from the venue's
+//! listing, and a triangle with a leg below the symbol's minimum size or
+//! value is not sent.
+//!
+//! Once the account's balances are known, no triangle commits more of its
+//! start asset than is held. In `inventory` mode the three legs are sent
+//! at once, each from a balance already held, instead of one after another.
+//!
+//! Every triangle is checked against the risk limits first, and the kill
+//! switch stops new triangles as well as the remaining legs of those in
+//! flight.
+//!
+//! Trading keys come from a sealed file or env vars, and a key is only
+//! used once its venue confirmed it can trade but not withdraw.
+
+mod attempt;
+mod balances;
+mod binance;
+mod bybit;
+mod credentials;
+mod gateio;
+mod kucoin;
+#[cfg(test)]
+mod mock;
+mod permissions;
+mod risk;
+mod sign;
+
+pub use attempt::{AttemptEvent, AttemptState, Holding, RecoveryPolicy};
+pub use balances::{background_refresh, BalanceBook, Balances};
+pub use credentials::{load_credentials, seal_file, Credentials, KeySource, Secret};
+pub use permissions::{verify_keys, KeyChecks, KeyPermissions};
+pub use risk::RiskGuard;
+
+use reqwest::{header::HeaderMap, Client, RequestBuilder};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::time::{sleep, Duration};
+
+use crate::assets::AssetRegistry;
+use crate::config::ExecutionConfig;
+use crate::exchanges::{ErrorKind, ExchangeError, HttpClient, Markets, OrderRules};
+use crate::logic;
+use crate::metrics;
+use crate::models::{Side, TradeLeg, TradeStatus};
+
+/// Venues orders can be sent to
+pub const EXECUTION_EXCHANGES: &[&str] = &["binance", "bybit", "kucoin", "gateio"];
+
+/// Production REST endpoint of a venue in `EXECUTION_EXCHANGES`
+pub fn default_base_url(exchange: &str) -> &'static str {
+    match exchange {
+        "binance" => "https://api.binance.com",
+        "bybit" => "https://api.bybit.com",
+        "kucoin" => "https://api.kucoin.com",
+        "gateio" => "https://api.gateio.ws",
+        _ => "",
+    }
+}
+
+/// Status checks of an order the venue reported as still working
+const POLL_ATTEMPTS: u32 = 10;
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// Lookups by client id of an order whose send failed after it went out,
+/// while the venue has no record of it
+const LOOKUP_ATTEMPTS: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderType {
+    Market,
+    /// Limit order that cancels whatever doesn't fill at once
+    Ioc,
+}
+
+/// How the legs of a triangle are sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExecutionMode {
+    /// One after another, each trading what the previous one received
+    Sequential,
+    /// All at once, each from a balance already held
+    Inventory,
+}
+
+/// One order as the engine asks for it
+#[derive(Debug, Clone)]
+pub struct OrderRequest {
+    /// Market as the venue's API names it
+    pub symbol: String,
+    pub side: Side,
+    pub order_type: OrderType,
+    /// Base to trade, or quote to spend on a market buy
+    pub amount: f64,
+    /// Limit price of an IOC order
+    pub price: Option<f64>,
+    /// Our id for the order, so it can be found even if the response is lost
+    pub client_id: String,
+}
+
+impl OrderRequest {
+    /// Amount is in the quote asset
+    fn spends_quote(&self) -> bool {
+        self.order_type == OrderType::Market && self.side == Side::Buy
+    }
+
+    /// Round the order to its symbol's rules: the amount down to its step,
+    /// an IOC price to its tick away from the market. `price` values a
+    /// market order against the minimums. Errors when the order would be
+    /// below the symbol's minimum size or value.
+    fn fit(mut self, rules: &OrderRules, price: f64) -> Result<Self, String> {
+        if let (Some(limit), Some(tick)) = (self.price, rules.tick) {
+            let ticks = limit / tick;
+            let ticks = match self.side {
+                Side::Buy => (ticks + STEP_EPSILON).floor(),
+                Side::Sell => (ticks - STEP_EPSILON).ceil(),
+            };
+            self.price = Some(ticks * tick);
+        }
+        let step = if self.spends_quote() {
+            rules.quote_step
+        } else {
+            rules.qty_step
+        };
+        if let Some(step) = step {
+            self.amount = (self.amount / step + STEP_EPSILON).floor() * step;
+        }
+        let price = self.price.unwrap_or(price);
+        let (base, notional) = if self.spends_quote() {
+            (self.amount / price, self.amount)
+        } else {
+            (self.amount, self.amount * price)
+        };
+        if self.amount <= 0.0 {
+            return Err(format!("{} rounds down to nothing", self.symbol));
+        }
+        if let Some(min) = rules.min_qty.filter(|min| base < *min) {
+            return Err(format!(
+                "{} {} is below the minimum quantity {}",
+                self.symbol, base, min
+            ));
+        }
+        if let Some(min) = rules.min_notional.filter(|min| notional < *min) {
+            return Err(format!(
+                "{} order value {} is below the minimum {}",
+                self.symbol, notional, min
+            ));
+        }
+        Ok(self)
+    }
+}
+
+/// Slack for float noise when rounding to a step, so an amount that is
+/// already a multiple isn't cut by a whole step
+const STEP_EPSILON: f64 = 1e-9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderStatus {
+    Filled,
+    /// Done, with part of the order cancelled or expired
+    PartiallyFilled,
+    /// Done without trading
+    Unfilled,
+    /// Still working on the book
+    Open,
+}
+
+impl OrderStatus {
+    /// Status of a finished order from what it traded
+    fn finished(filled_base: f64, fully: bool) -> Self {
+        if fully {
+            OrderStatus::Filled
+        } else if filled_base > 0.0 {
+            OrderStatus::PartiallyFilled
+        } else {
+            OrderStatus::Unfilled
+        }
+    }
+}
+
+/// Why an order has no report
+#[derive(Debug)]
+enum OrderError {
+    /// Didn't fit its symbol's rules and wasn't sent
+    NotSent(String),
+    /// Refused by the venue, or unknown to it after a failed send
+    Rejected(ExchangeError),
+    /// May have traded, or still be working: the venue couldn't be asked
+    /// about the order, or couldn't confirm it finished
+    Unknown(String),
+}
+
+impl fmt::Display for OrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderError::NotSent(why) => write!(f, "not sent: {}", why),
+            OrderError::Rejected(e) => e.fmt(f),
+            OrderError::Unknown(why) => write!(f, "outcome unknown: {}", why),
+        }
+    }
+}
+
+/// The request may have reached the venue, and the order the book, even
+/// though the call failed: no response, a server error (Binance calls the
+/// outcome UNKNOWN) or a response that couldn't be read
+fn may_have_traded(e: &ExchangeError) -> bool {
+    match &e.kind {
+        ErrorKind::Timeout
+        | ErrorKind::Network { .. }
+        | ErrorKind::Decode { .. }
+        | ErrorKind::Maintenance => true,
+        ErrorKind::Http { status, .. } => *status >= 500,
+        // Binance: "Timeout waiting for response from backend server"
+        ErrorKind::Api { code, .. } => code == "-1007",
+        _ => false,
+    }
+}
+
+/// What the venue says an order did
+#[derive(Debug, Clone)]
+pub struct OrderReport {
+    pub order_id: String,
+    pub status: OrderStatus,
+    pub filled_base: f64,
+    pub filled_quote: f64,
+}
+
+/// Signed access to one venue's trading API
+pub struct Venue<'a> {
+    exchange: &'a str,
+    base_url: &'a str,
+    creds: &'a Credentials,
+    client: &'a Client,
+    recv_window_ms: u64,
+}
+
+impl Venue<'_> {
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url.trim_end_matches('/'), path)
+    }
+
+    /// Send a signed request once and return the body of a 2xx response
+    async fn send(&self, request: RequestBuilder, path: &str) -> Result<String, ExchangeError> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| ExchangeError::from_reqwest(self.exchange, path, e))?;
+        let status = response.status();
+        let headers: HeaderMap = response.headers().clone();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| ExchangeError::from_reqwest(self.exchange, path, e))?;
+        if status.is_success() {
+            return Ok(body);
+        }
+        // a server error keeps its status: the request may have been acted on
+        if !matches!(status.as_u16(), 418 | 429) && !status.is_server_error() {
+            if let Some(kind) = api_error(&body) {
+                return Err(ExchangeError::new(self.exchange, path, kind));
+            }
+        }
+        Err(ExchangeError::from_status(
+            self.exchange,
+            path,
+            status,
+            &headers,
+            &body,
+        ))
+    }
+
+    fn decode<T: DeserializeOwned>(&self, path: &str, body: &str) -> Result<T, ExchangeError> {
+        serde_json::from_str(body).map_err(|e| ExchangeError::decode(self.exchange, path, e, body))
+    }
+
+    async fn place(&self, order: &OrderRequest) -> Result<OrderReport, ExchangeError> {
+        match self.exchange {
+            "binance" => binance::place(self, order).await,
+            "bybit" => bybit::place(self, order).await,
+            "kucoin" => kucoin::place(self, order).await,
+            "gateio" => gateio::place(self, order).await,
+            other => Err(ExchangeError::unsupported(other)),
+        }
+    }
+
+    /// The order sent as `client_id`, or `None` if the venue has no record
+    /// of it
+    async fn find(
+        &self,
+        symbol: &str,
+        client_id: &str,
+    ) -> Result<Option<OrderReport>, ExchangeError> {
+        match self.exchange {
+            "binance" => binance::find(self, symbol, client_id).await,
+            "bybit" => bybit::find(self, symbol, client_id).await,
+            "kucoin" => kucoin::find(self, client_id).await,
+            "gateio" => gateio::find(self, symbol, client_id).await,
+            other => Err(ExchangeError::unsupported(other)),
+        }
+    }
+
+    async fn query(&self, symbol: &str, order_id: &str) -> Result<OrderReport, ExchangeError> {
+        match self.exchange {
+            "binance" => binance::query(self, symbol, order_id).await,
+            "bybit" => bybit::query(self, symbol, order_id).await,
+            "kucoin" => kucoin::query(self, order_id).await,
+            "gateio" => gateio::query(self, symbol, order_id).await,
+            other => Err(ExchangeError::unsupported(other)),
+        }
+    }
+
+    async fn cancel(&self, symbol: &str, order_id: &str) -> Result<(), ExchangeError> {
+        match self.exchange {
+            "binance" => binance::cancel(self, symbol, order_id).await,
+            "bybit" => bybit::cancel(self, symbol, order_id).await,
+            "kucoin" => kucoin::cancel(self, order_id).await,
+            "gateio" => gateio::cancel(self, symbol, order_id).await,
+            other => Err(ExchangeError::unsupported(other)),
+        }
+    }
+
+    /// Free balance per venue ticker
+    async fn balances(&self) -> Result<Vec<(String, f64)>, ExchangeError> {
+        match self.exchange {
+            "binance" => binance::balances(self).await,
+            "bybit" => bybit::balances(self).await,
+            "kucoin" => kucoin::balances(self).await,
+            "gateio" => gateio::balances(self).await,
+            other => Err(ExchangeError::unsupported(other)),
+        }
+    }
+
+    /// What the key may do, or `None` where the venue doesn't say
+    async fn permissions(&self) -> Result<Option<KeyPermissions>, ExchangeError> {
+        match self.exchange {
+            "binance" => binance::permissions(self).await,
+            "bybit" => bybit::permissions(self).await,
+            "kucoin" => kucoin::permissions(self).await,
+            "gateio" => gateio::permissions(self).await,
+            other => Err(ExchangeError::unsupported(other)),
+        }
+    }
+
+    /// Place an order and wait for it to finish. Market and IOC orders
+    /// settle at once on every venue, but some only acknowledge the order
+    /// id and need a status check. A send that failed after the request
+    /// went out is settled by looking the order up by its client id, and
+    /// an order still working after the checks is cancelled.
+    async fn execute(&self, order: &OrderRequest) -> Result<OrderReport, OrderError> {
+        let report = match self.place(order).await {
+            Ok(report) => report,
+            Err(e) if may_have_traded(&e) => self.look_up(order, e).await?,
+            Err(e) => return Err(OrderError::Rejected(e)),
+        };
+        let report = self.poll(&order.symbol, report, POLL_ATTEMPTS).await?;
+        if report.status != OrderStatus::Open {
+            return Ok(report);
+        }
+        tracing::warn!(
+            "{} order {} still open after {} checks, cancelling it",
+            self.exchange,
+            report.order_id,
+            POLL_ATTEMPTS
+        );
+        if let Err(e) = self.cancel(&order.symbol, &report.order_id).await {
+            // it may have finished meanwhile; its status says
+            tracing::warn!(
+                "cancelling {} order {}: {}",
+                self.exchange,
+                report.order_id,
+                e
+            );
+        }
+        let report = self.poll(&order.symbol, report, LOOKUP_ATTEMPTS).await?;
+        if report.status == OrderStatus::Open {
+            return Err(OrderError::Unknown(format!(
+                "order {} is still open after cancelling it",
+                report.order_id
+            )));
+        }
+        Ok(report)
+    }
+
+    /// Check an open order's status up to `attempts` times until it
+    /// finishes
+    async fn poll(
+        &self,
+        symbol: &str,
+        mut report: OrderReport,
+        attempts: u32,
+    ) -> Result<OrderReport, OrderError> {
+        let mut checked = 0;
+        while report.status == OrderStatus::Open && checked < attempts {
+            sleep(POLL_INTERVAL).await;
+            report = self
+                .query(symbol, &report.order_id)
+                .await
+                .map_err(|e| OrderError::Unknown(format!("order {}: {}", report.order_id, e)))?;
+            checked += 1;
+        }
+        Ok(report)
+    }
+
+    /// Find `order` after its send failed with `error`. It was rejected if
+    /// the venue still has no record of it after `LOOKUP_ATTEMPTS` tries,
+    /// and its outcome is unknown if the venue can't be asked.
+    async fn look_up(
+        &self,
+        order: &OrderRequest,
+        error: ExchangeError,
+    ) -> Result<OrderReport, OrderError> {
+        tracing::warn!(
+            "{} order {} may have been placed ({}), looking it up",
+            self.exchange,
+            order.client_id,
+            error
+        );
+        for _ in 0..LOOKUP_ATTEMPTS {
+            sleep(POLL_INTERVAL).await;
+            match self.find(&order.symbol, &order.client_id).await {
+                Ok(Some(report)) => return Ok(report),
+                Ok(None) => {}
+                Err(lookup) => {
+                    tracing::warn!(
+                        "{} order {} lookup failed: {}",
+                        self.exchange,
+                        order.client_id,
+                        lookup
+                    );
+                    return Err(OrderError::Unknown(error.to_string()));
+                }
+            }
+        }
+        Err(OrderError::Rejected(error))
+    }
+}
+
+/// Error code and message from a venue's JSON error body; field names
+/// differ per venue
+fn api_error(body: &str) -> Option<ErrorKind> {
+    let value: serde_json::Value = serde_json::from_str(body).ok()?;
+    let field = |names: &[&str]| {
+        names.iter().find_map(|n| match value.get(*n)? {
+            serde_json::Value::String(s) => Some(s.clone()),
+            serde_json::Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        })
+    };
+    Some(ErrorKind::Api {
+        code: field(&["code", "retCode", "label"])?,
+        message: field(&["msg", "retMsg", "message"]).unwrap_or_default(),
+    })
+}
+
+/// Decimal for an order field, truncated to 8 places so rounding never
+/// asks for more than is held; float noise below that doesn't cost a digit
+fn decimal(value: f64) -> String {
+    let truncated = (value * 1e8 + 1e-6).floor() / 1e8;
+    let s = format!("{:.8}", truncated);
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn unix_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// What an execution needs besides the triangle, cloned out of the shared
+/// state so no lock is held while orders are in flight
+#[derive(Clone)]
+pub struct ExecutionContext {
+    pub http: Arc<HttpClient>,
+    pub credentials: Arc<HashMap<String, Credentials>>,
+    pub config: ExecutionConfig,
+    /// Taker fee (%) of the venue being traded
+    pub fee_per_leg: f64,
+    /// Maps the tickers balances come back in
+    pub assets: Arc<AssetRegistry>,
+    pub balances: Arc<BalanceBook>,
+    pub risk: Arc<RiskGuard>,
+    /// Listing of the venue being traded, with each symbol's order rules;
+    /// `None` until it was downloaded
+    pub markets: Option<Arc<Markets>>,
+}
+
+/// Why a triangle was not sent at all
+#[derive(Debug)]
+pub enum ExecutionError {
+    Unsupported(String),
+    NoCredentials(String),
+    /// None of the triangle's assets has an `execution.start_amounts` entry
+    NoStartAsset,
+    /// Another triangle is still executing on the venue
+    Busy(String),
+    /// Inventory mode needs the venue's balances, and none were fetched yet
+    NoBalances(String),
+    /// Nothing is held to start the triangle with
+    InsufficientBalance(String),
+    /// The kill switch is engaged, for the given reason
+    Halted(String),
+    /// A limit under `execution.risk` would be exceeded
+    RiskLimit(String),
+    /// The venue's listing has no order rules for the symbol
+    NoOrderRules(String),
+    /// A leg would be below its symbol's minimum size or value
+    BelowMinimum(String),
+}
+
+impl fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutionError::Unsupported(ex) => {
+                write!(
+                    f,
+                    "orders can't be sent to `{}` (supported: {})",
+                    ex,
+                    EXECUTION_EXCHANGES.join(", ")
+                )
+            }
+            ExecutionError::NoCredentials(ex) => {
+                write!(f, "no trading credentials configured for `{}`", ex)
+            }
+            ExecutionError::NoStartAsset => {
+                write!(
+                    f,
+                    "no asset of the triangle has an execution.start_amounts entry"
+                )
+            }
+            ExecutionError::Busy(ex) => {
+                write!(f, "a triangle is already executing on `{}`", ex)
+            }
+            ExecutionError::NoBalances(ex) => {
+                write!(
+                    f,
+                    "balances of `{}` are not known yet; inventory mode needs them",
+                    ex
+                )
+            }
+            ExecutionError::InsufficientBalance(ex) => {
+                write!(f, "not enough held on `{}` to trade the triangle", ex)
+            }
+            ExecutionError::Halted(reason) => {
+                write!(f, "execution is halted by the kill switch: {}", reason)
+            }
+            ExecutionError::RiskLimit(why) => write!(f, "risk limit reached: {}", why),
+            ExecutionError::NoOrderRules(symbol) => {
+                write!(
+                    f,
+                    "no order rules loaded for `{}`; POST /admin/refresh-metadata to download them",
+                    symbol
+                )
+            }
+            ExecutionError::BelowMinimum(why) => {
+                write!(f, "order below the venue's minimum: {}", why)
+            }
+        }
+    }
+}
+
+/// One leg as executed, retries included
+#[derive(Debug, Clone, Serialize)]
+pub struct LegExecution {
+    pub symbol: String,
+    pub side: Side,
+    pub from: String,
+    pub to: String,
+    /// Venue ids of the orders sent for this leg, first one first
+    pub order_ids: Vec<String>,
+    /// Our id of the first order; retries append `r1`, `r2`, ...
+    pub client_id: String,
+    pub status: OrderStatus,
+    /// Amount of `from` the leg was sized for
+    pub requested: f64,
+    /// Amount of `from` traded
+    pub spent: f64,
+    /// Amount of `to` obtained, net of the configured taker fee
+    pub received: f64,
+    pub filled_base: f64,
+    pub filled_quote: f64,
+    pub expected_price: f64,
+    /// Volume-weighted fill price; 0 when nothing traded
+    pub avg_price: f64,
+    /// Fill price against the scanned one in basis points; positive is worse
+    pub slippage_bps: f64,
+    /// Orders resent for the unfilled remainder
+    pub retries: u32,
+    /// Why the last order failed, if it did
+    pub error: Option<String>,
+}
+
+/// A triangle sent to a venue, as listed by `GET /executions`
+#[derive(Debug, Clone, Serialize)]
+pub struct Execution {
+    pub id: u64,
+    pub exchange: String,
+    /// Path as traded, rotated to start at the asset committed
+    pub triangle: String,
+    pub mode: ExecutionMode,
+    pub order_type: OrderType,
+    pub recovery: RecoveryPolicy,
+    /// Unix seconds
+    pub started_at: u64,
+    pub finished_at: u64,
+    /// Post-fee profit (%) the scanner reported, if known
+    pub expected_profit: Option<f64>,
+    pub status: TradeStatus,
+    /// Where the attempt ended: `completed`, `unwound`, `held` or `aborted`
+    pub state: AttemptState,
+    pub start_asset: String,
+    /// Amount of `start_asset` the first leg traded
+    pub start_amount: f64,
+    /// Amount of `start_asset` back, from the last leg and any unwinds
+    pub end_amount: f64,
+    /// `end_amount - start_amount`; assets listed in `held` or
+    /// `inventory_change` are not valued
+    pub realized_pnl: f64,
+    pub realized_pnl_pct: f64,
+    pub legs: Vec<LegExecution>,
+    /// Orders that sold stranded assets back into `start_asset`
+    pub unwinds: Vec<LegExecution>,
+    /// Assets left over that recovery didn't convert back
+    pub held: Vec<Holding>,
+    /// Net change of each balance the triangle traded from, inventory mode
+    /// only; everything but the start asset stays near zero when all legs
+    /// fill
+    pub inventory_change: Vec<Holding>,
+    /// Every state change of the attempt, oldest first
+    pub events: Vec<AttemptEvent>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Ledger {
+    /// Newest last
+    executions: VecDeque<Execution>,
+    next_id: u64,
+    /// Venues with a triangle in progress; one at a time per venue
+    busy: HashSet<String>,
+}
+
+/// Marks a venue busy while held; freed when dropped, even if the attempt
+/// never finishes
+struct BusyVenue<'a> {
+    engine: &'a ExecutionEngine,
+    exchange: &'a str,
+}
+
+impl Drop for BusyVenue<'_> {
+    fn drop(&mut self) {
+        self.engine
+            .ledger
+            .lock()
+            .expect("execution ledger poisoned")
+            .busy
+            .remove(self.exchange);
+    }
+}
+
+/// Sends triangles and keeps their reports; the history survives reloads
+#[derive(Debug, Default)]
+pub struct ExecutionEngine {
+    ledger: Mutex<Ledger>,
+}
+
+impl ExecutionEngine {
+    /// Send the three orders of a triangle on `exchange`, starting at the
+    /// asset [`logic::start_asset`] picks among those with a configured
+    /// start amount, as the scan did. The amount is capped by the balances,
+    /// which are refreshed afterwards, and by `execution.risk.max_notional`;
+    /// the other risk limits refuse it, as does a leg below its symbol's
+    /// minimum order.
+    pub async fn execute(
+        &self,
+        exchange: &str,
+        legs: &[TradeLeg; 3],
+        expected_profit: Option<f64>,
+        ctx: &ExecutionContext,
+    ) -> Result<Execution, ExecutionError> {
+        let cfg = &ctx.config;
+        if !EXECUTION_EXCHANGES.contains(&exchange) {
+            return Err(ExecutionError::Unsupported(exchange.to_string()));
+        }
+        let creds = ctx
+            .credentials
+            .get(exchange)
+            .ok_or_else(|| ExecutionError::NoCredentials(exchange.to_string()))?;
+        let held = ctx.balances.get(exchange);
+        if cfg.mode == ExecutionMode::Inventory && held.is_none() {
+            return Err(ExecutionError::NoBalances(exchange.to_string()));
+        }
+        let candidates = [0, 1, 2].map(|i| {
+            cfg.start_amounts
+                .get(&legs[i].from)
+                .is_some_and(|a| *a > 0.0)
+        });
+        if !candidates.contains(&true) {
+            return Err(ExecutionError::NoStartAsset);
+        }
+        // balances in units of the first asset, as the scan sizes them
+        let scale = [1.0, legs[0].rate(), legs[0].rate() * legs[1].rate()];
+        let value = held
+            .as_ref()
+            .map(|held| [0, 1, 2].map(|i| held.get(&legs[i].from) / scale[i]));
+        let start = logic::start_asset(candidates, value)
+            .ok_or_else(|| ExecutionError::InsufficientBalance(exchange.to_string()))?;
+        let mut legs = legs.to_vec();
+        legs.rotate_left(start);
+        let fee_mult = 1.0 - ctx.fee_per_leg / 100.0;
+        let mut configured = cfg.start_amounts[&legs[0].from];
+        if let Some(cap) = cfg.risk.max_notional.get(&legs[0].from) {
+            configured = configured.min(*cap);
+        }
+        let amount = match (&held, cfg.mode) {
+            (Some(held), ExecutionMode::Inventory) => {
+                attempt::inventory_size(&legs, configured, held, fee_mult)
+            }
+            (Some(held), ExecutionMode::Sequential) => configured.min(held.get(&legs[0].from)),
+            (None, _) => configured,
+        };
+        if amount <= 0.0 {
+            return Err(ExecutionError::InsufficientBalance(exchange.to_string()));
+        }
+        let sizes = attempt::leg_amounts(&legs, amount, fee_mult);
+        for (i, (leg, size)) in legs.iter().zip(&sizes).enumerate() {
+            let rules = ctx
+                .markets
+                .as_ref()
+                .and_then(|m| m.rules.get(&leg.symbol))
+                .ok_or_else(|| ExecutionError::NoOrderRules(leg.symbol.clone()))?;
+            attempt::order(leg, *size, cfg.order_type, cfg.ioc_slippage_bps, "", rules)
+                .map_err(|why| ExecutionError::BelowMinimum(format!("leg {}: {}", i + 1, why)))?;
+        }
+        let exposure = legs.iter().map(|l| l.from.clone()).zip(sizes).collect();
+        let permit = ctx.risk.admit(exchange, exposure, &cfg.risk)?;
+
+        if !self
+            .ledger
+            .lock()
+            .expect("execution ledger poisoned")
+            .busy
+            .insert(exchange.to_string())
+        {
+            return Err(ExecutionError::Busy(exchange.to_string()));
+        }
+        let busy = BusyVenue {
+            engine: self,
+            exchange,
+        };
+        let venue = Venue {
+            exchange,
+            base_url: cfg.base_url(exchange),
+            creds,
+            client: ctx.http.client(exchange),
+            recv_window_ms: cfg.recv_window_ms,
+        };
+        let execution = match cfg.mode {
+            ExecutionMode::Sequential => {
+                attempt::run(&venue, &legs, amount, expected_profit, ctx).await
+            }
+            ExecutionMode::Inventory => {
+                attempt::run_inventory(&venue, &legs, amount, expected_profit, ctx).await
+            }
+        };
+        let execution = self.record(execution, cfg.max_executions);
+        let recorded = ctx.risk.record(
+            exchange,
+            &execution.start_asset,
+            execution.realized_pnl,
+            &execution.held,
+        );
+        if let Err(e) = recorded.await {
+            tracing::warn!(
+                "risk state not saved; today's PnL and holdings won't survive a restart: {}",
+                e
+            );
+        }
+        drop(permit);
+        drop(busy);
+        if let Err(e) = ctx.balances.refresh(exchange, ctx).await {
+            tracing::warn!("balance refresh after execution failed: {}", e);
+        }
+        Ok(execution)
+    }
+
+    fn record(&self, mut execution: Execution, max_executions: usize) -> Execution {
+        let mut ledger = self.ledger.lock().expect("execution ledger poisoned");
+        ledger.next_id += 1;
+        execution.id = ledger.next_id;
+        ledger.executions.push_back(execution.clone());
+        while ledger.executions.len() > max_executions {
+            ledger.executions.pop_front();
+        }
+        metrics::EXECUTIONS
+            .with_label_values(&[&execution.exchange, execution.status.label()])
+            .inc();
+        execution
+    }
+
+    /// Most recent executions first
+    pub fn executions(&self, limit: usize) -> Vec<Execution> {
+        let ledger = self.ledger.lock().expect("execution ledger poisoned");
+        ledger
+            .executions
+            .iter()
+            .rev()
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mock::{self, close, context, http, symbol, triangle, venue, Lost, MockExchange};
+    use super::*;
+
+    async fn execute(
+        http: &Arc<HttpClient>,
+        exchange: &str,
+        mock: &MockExchange,
+        order_type: OrderType,
+    ) -> Result<Execution, ExecutionError> {
+        let ctx = context(http, mock, exchange, order_type);
+        ExecutionEngine::default()
+            .execute(exchange, &triangle(exchange), Some(4.0), &ctx)
+            .await
+    }
+
+    #[tokio::test]
+    async fn market_triangle_fills_on_every_venue() {
+        let http = http();
+        for exchange in EXECUTION_EXCHANGES {
+            let mock = venue(exchange, vec![(50_000.0, 5.0)]).await;
+            let done = execute(&http, exchange, &mock, OrderType::Market)
+                .await
+                .unwrap();
+            assert_eq!(done.status, TradeStatus::Filled, "{}: {:?}", exchange, done);
+            assert_eq!(done.triangle, "USDT → BTC → ETH → USDT");
+            assert!(close(done.start_amount, 1_000.0), "{}", exchange);
+            assert!(close(done.end_amount, 1_040.0), "{}", exchange);
+            assert!(close(done.realized_pnl, 40.0), "{}", exchange);
+            assert_eq!(done.realized_pnl_pct, 4.0);
+            assert!(close(done.legs[1].received, 0.4), "{}", exchange);
+
+            let orders = mock.orders();
+            assert_eq!(orders.len(), 3, "{}", exchange);
+            assert!(orders.iter().all(|o| o.client_id.starts_with("t-arb")));
+        }
+    }
+
+    #[tokio::test]
+    async fn ioc_leg_past_the_limit_fills_partly() {
+        let http = http();
+        for exchange in EXECUTION_EXCHANGES {
+            // only 0.01 BTC is offered within the 5 bps limit
+            let mock = venue(exchange, vec![(50_000.0, 0.01), (50_100.0, 5.0)]).await;
+            let done = execute(&http, exchange, &mock, OrderType::Ioc)
+                .await
+                .unwrap();
+            assert_eq!(
+                done.status,
+                TradeStatus::Partial,
+                "{}: {:?}",
+                exchange,
+                done
+            );
+            assert_eq!(done.legs[0].status, OrderStatus::PartiallyFilled);
+            assert!(close(done.legs[0].received, 0.01), "{}", exchange);
+            assert!(close(done.start_amount, 500.0), "{}", exchange);
+            // IOC buys are sized at their limit price, so a sliver of BTC
+            // stays behind
+            let eth = 0.01 / (0.05 * 1.0005);
+            assert!(close(done.legs[1].received, eth), "{}", exchange);
+            assert!(close(done.end_amount, eth * 2_600.0), "{}", exchange);
+        }
+    }
+
+    #[tokio::test]
+    async fn rejected_order_fails_and_unwinds_the_triangle() {
+        let http = http();
+        for exchange in EXECUTION_EXCHANGES {
+            let mock = venue(exchange, vec![(50_000.0, 5.0)]).await;
+            mock.reject(&symbol(exchange, "ETH", "BTC"));
+            let done = execute(&http, exchange, &mock, OrderType::Market)
+                .await
+                .unwrap();
+            assert_eq!(done.status, TradeStatus::Failed, "{}", exchange);
+            assert_eq!(done.legs.len(), 2);
+            // the BTC bought by leg 1 is sold back at the bid
+            assert_eq!(done.state, AttemptState::Unwound, "{}", exchange);
+            assert!(close(done.end_amount, 999.8), "{}", exchange);
+            assert!(done.held.is_empty());
+            let error = done.error.unwrap();
+            assert!(
+                error.contains("insufficient balance"),
+                "{}: {}",
+                exchange,
+                error
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn lost_responses_are_settled_by_client_id() {
+        let http = http();
+        for exchange in EXECUTION_EXCHANGES {
+            for lost in [Lost::Booked, Lost::Garbled] {
+                let mock = venue(exchange, vec![(50_000.0, 5.0)]).await;
+                mock.lose(&symbol(exchange, "ETH", "BTC"), lost);
+                let done = execute(&http, exchange, &mock, OrderType::Market)
+                    .await
+                    .unwrap();
+                assert_eq!(done.status, TradeStatus::Filled, "{} {:?}", exchange, lost);
+                assert!(close(done.legs[1].received, 0.4), "{}", exchange);
+                assert_eq!(done.legs[1].order_ids, vec![mock.orders()[1].id.clone()]);
+            }
+
+            // the venue never booked it: a plain rejection, unwound as usual
+            let mock = venue(exchange, vec![(50_000.0, 5.0)]).await;
+            mock.lose(&symbol(exchange, "ETH", "BTC"), Lost::Dropped);
+            let done = execute(&http, exchange, &mock, OrderType::Market)
+                .await
+                .unwrap();
+            assert_eq!(done.status, TradeStatus::Failed, "{}", exchange);
+            assert_eq!(done.state, AttemptState::Unwound, "{}", exchange);
+            assert!(done.error.unwrap().contains("502"), "{}", exchange);
+        }
+    }
+
+    #[tokio::test]
+    async fn orders_left_open_are_cancelled() {
+        let http = http();
+        for exchange in EXECUTION_EXCHANGES {
+            let mock = venue(exchange, vec![(50_000.0, 5.0)]).await;
+            mock.keep_open(&symbol(exchange, "ETH", "BTC"), true);
+            let done = execute(&http, exchange, &mock, OrderType::Market)
+                .await
+                .unwrap();
+            assert_eq!(done.status, TradeStatus::Filled, "{}: {:?}", exchange, done);
+            assert!(!mock.orders()[1].open, "{}", exchange);
+        }
+
+        // a cancel that doesn't take leaves the order's outcome unknown
+        let mock = venue("gateio", vec![(50_000.0, 5.0)]).await;
+        mock.keep_open(&symbol("gateio", "ETH", "BTC"), false);
+        let done = execute(&http, "gateio", &mock, OrderType::Market)
+            .await
+            .unwrap();
+        assert_eq!(done.state, AttemptState::Held);
+        assert_eq!(done.legs.len(), 2);
+        assert!(done.error.unwrap().contains("still open after cancelling"));
+        assert!(done.unwinds.is_empty());
+    }
+
+    #[tokio::test]
+    async fn an_attempt_cut_short_frees_the_venue() {
+        use futures::FutureExt;
+
+        let mock = venue("binance", vec![(50_000.0, 5.0)]).await;
+        let ctx = context(&http(), &mock, "binance", OrderType::Market);
+        let engine = ExecutionEngine::default();
+        let legs = triangle("binance");
+        // the first poll stops at the first order; dropping it cuts it short
+        let attempt = engine.execute("binance", &legs, None, &ctx);
+        assert!(attempt.now_or_never().is_none());
+        let done = engine.execute("binance", &legs, None, &ctx).await.unwrap();
+        assert_eq!(done.status, TradeStatus::Filled);
+    }
+
+    #[tokio::test]
+    async fn wrong_secret_is_rejected_by_the_venue() {
+        let mock = venue("gateio", vec![(50_000.0, 5.0)]).await;
+        let ctx = context(&http(), &mock, "gateio", OrderType::Market);
+        let ctx = ExecutionContext {
+            credentials: Arc::new(HashMap::from([(
+                "gateio".to_string(),
+                Credentials {
+                    secret: Secret::new("wrong".to_string()),
+                    ..mock::credentials()
+                },
+            )])),
+            ..ctx
+        };
+        let done = ExecutionEngine::default()
+            .execute("gateio", &triangle("gateio"), None, &ctx)
+            .await
+            .unwrap();
+        assert_eq!(done.status, TradeStatus::Failed);
+        assert!(done.error.unwrap().contains("invalid signature"));
+        assert!(mock.orders().is_empty());
+    }
+
+    #[tokio::test]
+    async fn refuses_without_credentials_or_start_asset() {
+        let mock = venue("binance", vec![(50_000.0, 5.0)]).await;
+        let engine = ExecutionEngine::default();
+        let ctx = context(&http(), &mock, "binance", OrderType::Market);
+        let legs = triangle("binance");
+
+        let no_keys = ExecutionContext {
+            credentials: Arc::default(),
+            ..ctx.clone()
+        };
+        let refused = engine.execute("binance", &legs, None, &no_keys).await;
+        assert!(matches!(refused, Err(ExecutionError::NoCredentials(_))));
+
+        let mut no_start = ctx;
+        no_start.config.start_amounts = HashMap::from([("DOGE".to_string(), 10.0)]);
+        let refused = engine.execute("binance", &legs, None, &no_start).await;
+        assert!(matches!(refused, Err(ExecutionError::NoStartAsset)));
+        assert!(engine.executions(10).is_empty());
+    }
+
+    #[tokio::test]
+    async fn balances_are_fetched_from_every_venue() {
+        let http = http();
+        for exchange in EXECUTION_EXCHANGES {
+            let mock = venue(exchange, vec![(50_000.0, 5.0)]).await;
+            mock.set_balances(&[("USDT", 1_500.0), ("XBT", 0.1), ("DOGE", 0.0)]);
+            let ctx = context(&http, &mock, exchange, OrderType::Market);
+            ctx.balances.refresh(exchange, &ctx).await.unwrap();
+            let held = ctx.balances.get(exchange).unwrap();
+            // tickers are canonical and empty balances dropped
+            assert_eq!(held.assets.len(), 2, "{}: {:?}", exchange, held);
+            assert_eq!(held.get("USDT"), 1_500.0);
+            assert_eq!(held.get("BTC"), 0.1);
+        }
+    }
+
+    #[tokio::test]
+    async fn start_amount_is_capped_by_the_balance() {
+        let mock = venue("bybit", vec![(50_000.0, 5.0)]).await;
+        let ctx = context(&http(), &mock, "bybit", OrderType::Market);
+        let engine = ExecutionEngine::default();
+        let legs = triangle("bybit");
+
+        mock.set_balances(&[("BTC", 1.0)]);
+        ctx.balances.refresh("bybit", &ctx).await.unwrap();
+        let refused = engine.execute("bybit", &legs, None, &ctx).await;
+        assert!(matches!(
+            refused,
+            Err(ExecutionError::InsufficientBalance(_))
+        ));
+
+        mock.set_balances(&[("USDT", 400.0)]);
+        ctx.balances.refresh("bybit", &ctx).await.unwrap();
+        let done = engine.execute("bybit", &legs, None, &ctx).await.unwrap();
+        assert!(close(done.start_amount, 400.0));
+        assert!(close(done.end_amount, 416.0));
+    }
+
+    #[tokio::test]
+    async fn start_asset_is_the_held_one_worth_the_most() {
+        let mock = venue("binance", vec![(50_000.0, 5.0)]).await;
+        let mut ctx = context(&http(), &mock, "binance", OrderType::Market);
+        ctx.config.start_amounts =
+            HashMap::from([("USDT".to_string(), 1_000.0), ("ETH".to_string(), 1.0)]);
+        let engine = ExecutionEngine::default();
+        let legs = triangle("binance");
+
+        // 1 ETH is worth 2_600 USDT at the path's prices
+        mock.set_balances(&[("USDT", 1_000.0), ("ETH", 1.0)]);
+        ctx.balances.refresh("binance", &ctx).await.unwrap();
+        let done = engine.execute("binance", &legs, None, &ctx).await.unwrap();
+        assert_eq!(done.start_asset, "ETH");
+
+        let balances = &ctx.balances.get("binance").unwrap().assets;
+        let holdings = crate::logic::Holdings {
+            balances,
+            start_amounts: &ctx.config.start_amounts,
+            inventory: false,
+        };
+        let prices = [
+            ("BTC", "USDT", 50_000.0),
+            ("ETH", "BTC", 0.05),
+            ("ETH", "USDT", 2_600.0),
+        ]
+        .map(|(base, quote, price)| crate::models::PairPrice {
+            symbol: symbol("binance", base, quote),
+            base: base.to_string(),
+            quote: quote.to_string(),
+            native_base: base.to_string(),
+            native_quote: quote.to_string(),
+            price,
+            bid: None,
+            ask: None,
+            is_spot: true,
+            liquidity: 1e9,
+        });
+        let venue = crate::logic::VenuePrices {
+            exchange: "binance",
+            prices: &prices,
+            fee_per_leg: 0.0,
+            holdings: Some(holdings),
+        };
+        let (results, _) = crate::logic::scan_triangles(&[venue], -100.0);
+        assert!(
+            results[0].triangle.starts_with("ETH"),
+            "{}",
+            results[0].triangle
+        );
+    }
+
+    #[tokio::test]
+    async fn risk_limits_scale_down_or_refuse_triangles() {
+        let mock = venue("kucoin", vec![(50_000.0, 5.0)]).await;
+        mock.set_balances(&[("USDT", 1_000.0)]);
+        let mut ctx = context(&http(), &mock, "kucoin", OrderType::Market);
+        ctx.config.risk.max_notional = HashMap::from([("USDT".to_string(), 300.0)]);
+        let engine = ExecutionEngine::default();
+        let legs = triangle("kucoin");
+
+        let done = engine.execute("kucoin", &legs, None, &ctx).await.unwrap();
+        assert!(close(done.start_amount, 300.0));
+        assert_eq!(ctx.risk.status().open_attempts, 0);
+        assert!(close(ctx.risk.status().daily_pnl["USDT"], 12.0));
+
+        // 300 USDT buys 0.006 BTC
+        ctx.config.risk.max_exposure = HashMap::from([("BTC".to_string(), 0.005)]);
+        let refused = engine.execute("kucoin", &legs, None, &ctx).await;
+        assert!(matches!(refused, Err(ExecutionError::RiskLimit(_))));
+
+        ctx.risk
+            .halt("test".to_string(), "ops".to_string())
+            .await
+            .unwrap();
+        let refused = engine.execute("kucoin", &legs, None, &ctx).await;
+        assert!(matches!(refused, Err(ExecutionError::Halted(_))));
+        assert_eq!(mock.orders().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn orders_are_sized_to_the_symbol_step() {
+        let mock = venue("binance", vec![(50_000.0, 5.0)]).await;
+        let mut ctx = context(&http(), &mock, "binance", OrderType::Ioc);
+        let rules = OrderRules {
+            qty_step: Some(0.001),
+            tick: Some(0.1),
+            ..OrderRules::default()
+        };
+        ctx.markets = Some(mock::markets("binance", rules));
+        let done = ExecutionEngine::default()
+            .execute("binance", &triangle("binance"), None, &ctx)
+            .await
+            .unwrap();
+        // 1000 USDT at the 50_025 limit is 0.01999 BTC, cut to 0.019
+        let orders = mock.orders();
+        assert!(close(orders[0].filled_base, 0.019), "{:?}", orders);
+        assert!(close(done.start_amount, 950.0));
+        assert!(orders.iter().all(|o| {
+            let steps = o.filled_base / 0.001;
+            (steps - steps.round()).abs() < 1e-6
+        }));
+    }
+
+    #[tokio::test]
+    async fn legs_below_the_minimum_are_not_sent() {
+        let mock = venue("kucoin", vec![(50_000.0, 5.0)]).await;
+        let mut ctx = context(&http(), &mock, "kucoin", OrderType::Market);
+        let engine = ExecutionEngine::default();
+        let legs = triangle("kucoin");
+
+        // leg 2 spends 0.02 BTC
+        let rules = OrderRules {
+            min_notional: Some(1.0),
+            ..OrderRules::default()
+        };
+        ctx.markets = Some(mock::markets("kucoin", rules));
+        let refused = engine.execute("kucoin", &legs, None, &ctx).await;
+        let Err(ExecutionError::BelowMinimum(why)) = refused else {
+            panic!("{:?}", refused);
+        };
+        assert!(why.starts_with("leg 2:"), "{}", why);
+
+        ctx.markets = Some(Arc::new(Markets::new(0, HashMap::new())));
+        let refused = engine.execute("kucoin", &legs, None, &ctx).await;
+        assert!(matches!(refused, Err(ExecutionError::NoOrderRules(_))));
+        assert!(mock.orders().is_empty());
+        assert_eq!(ctx.risk.status().open_attempts, 0);
+    }
+
+    #[test]
+    fn orders_round_to_step_and_tick_away_from_the_market() {
+        let rules = OrderRules {
+            qty_step: Some(0.001),
+            quote_step: Some(0.01),
+            tick: Some(0.1),
+            min_qty: Some(0.002),
+            min_notional: Some(10.0),
+        };
+        let order = |side, order_type, amount, price| OrderRequest {
+            symbol: "BTCUSDT".to_string(),
+            side,
+            order_type,
+            amount,
+            price,
+            client_id: String::new(),
+        };
+
+        let buy = order(Side::Buy, OrderType::Ioc, 0.0199, Some(50_025.07))
+            .fit(&rules, 50_000.0)
+            .unwrap();
+        assert!(close(buy.amount, 0.019));
+        assert!(close(buy.price.unwrap(), 50_025.0));
+        let sell = order(Side::Sell, OrderType::Ioc, 0.003, Some(49_974.93))
+            .fit(&rules, 50_000.0)
+            .unwrap();
+        assert!(close(sell.amount, 0.003), "a multiple keeps its size");
+        assert!(close(sell.price.unwrap(), 49_975.0));
+        let market_buy = order(Side::Buy, OrderType::Market, 123.456, None)
+            .fit(&rules, 50_000.0)
+            .unwrap();
+        assert!(close(market_buy.amount, 123.45));
+
+        let small = order(Side::Sell, OrderType::Market, 0.0019, None).fit(&rules, 50_000.0);
+        assert!(small.unwrap_err().contains("minimum quantity"));
+        let cheap = order(Side::Sell, OrderType::Market, 0.002, None).fit(&rules, 4_000.0);
+        assert!(cheap.unwrap_err().contains("order value"));
+        let dust = order(Side::Buy, OrderType::Market, 0.004, None).fit(&rules, 1.0);
+        assert!(dust.unwrap_err().contains("rounds down to nothing"));
+    }
+
+    #[test]
+    fn decimals_are_truncated() {
+        assert_eq!(decimal(0.123456789), "0.12345678");
+        assert_eq!(decimal(1000.0), "1000");
+        assert_eq!(decimal(0.5), "0.5");
+        // 2.0999999999999996 in binary
+        assert_eq!(decimal(0.7 * 3.0), "2.1");
+    }
+}
@@ -0,0 +1,342 @@
+//! Startup check that trading keys can trade and can't withdraw.
+//!
+//! Each venue's API-key info endpoint reports what a key may do. A key
+//! that can't trade spot, or that allows withdrawals, is never used, and no
+//! key is used before its check succeeded. Gate.io doesn't report key
+//! permissions, so its keys are only checked to authenticate and only trade
+//! once `execution.unverifiable_keys` lists the venue.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tokio::time::Duration;
+
+use super::{unix_ms, Credentials, ExecutionContext, KeySource, Venue};
+use crate::models::AppState;
+
+/// Pause before asking again about keys whose venue couldn't be reached
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// What a key may do, as its venue reports it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct KeyPermissions {
+    /// Spot orders may be placed
+    pub trade: bool,
+    /// Funds may be withdrawn
+    pub withdraw: bool,
+}
+
+/// Outcome of a key's permission check
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum KeyStatus {
+    /// Not checked yet, or the venue couldn't be asked; retried
+    Pending { error: Option<String> },
+    /// Trades, can't withdraw
+    Verified { permissions: KeyPermissions },
+    /// Authenticates, but the venue doesn't report its permissions; only
+    /// used when `execution.unverifiable_keys` lists the venue
+    Unverifiable,
+    /// Never used
+    Refused { reason: String },
+    /// `execution.verify_keys` is off
+    Unchecked,
+}
+
+/// A venue's trading key as `GET /admin/credentials` shows it; no key
+/// material
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyCheck {
+    pub account: String,
+    pub source: KeySource,
+    #[serde(flatten)]
+    pub status: KeyStatus,
+    /// Unix seconds of the last answer from the venue
+    pub checked_at: Option<u64>,
+}
+
+/// Permission checks of the loaded trading keys, keyed by exchange;
+/// rebuilt with the keys on reload
+#[derive(Debug, Default)]
+pub struct KeyChecks {
+    checks: Mutex<BTreeMap<String, KeyCheck>>,
+    /// `execution.verify_keys`; unchecked keys only trade when it's off
+    verify: bool,
+    /// Venues whose unverifiable keys may trade
+    unverifiable: HashSet<String>,
+}
+
+impl KeyChecks {
+    /// Every key starts pending, or unchecked when `verify` is off.
+    /// Unverifiable keys of venues not in `unverifiable` are never used.
+    pub fn new(keys: &HashMap<String, Credentials>, verify: bool, unverifiable: &[String]) -> Self {
+        let status = if verify {
+            KeyStatus::Pending { error: None }
+        } else {
+            KeyStatus::Unchecked
+        };
+        let checks = keys
+            .iter()
+            .map(|(ex, creds)| {
+                let check = KeyCheck {
+                    account: creds.account.clone(),
+                    source: creds.source,
+                    status: status.clone(),
+                    checked_at: None,
+                };
+                (ex.clone(), check)
+            })
+            .collect();
+        Self {
+            checks: Mutex::new(checks),
+            verify,
+            unverifiable: unverifiable.iter().cloned().collect(),
+        }
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<String, KeyCheck> {
+        self.checks.lock().expect("key checks poisoned").clone()
+    }
+
+    fn pending(&self) -> Vec<String> {
+        self.checks
+            .lock()
+            .expect("key checks poisoned")
+            .iter()
+            .filter(|(_, c)| matches!(c.status, KeyStatus::Pending { .. }))
+            .map(|(ex, _)| ex.clone())
+            .collect()
+    }
+
+    fn set(&self, exchange: &str, status: KeyStatus) {
+        if let Some(check) = self
+            .checks
+            .lock()
+            .expect("key checks poisoned")
+            .get_mut(exchange)
+        {
+            if !matches!(status, KeyStatus::Pending { .. }) {
+                check.checked_at = Some(unix_ms() / 1000);
+            }
+            check.status = status;
+        }
+    }
+
+    /// The keys in `keys` whose check cleared them for trading
+    pub fn usable(&self, keys: &HashMap<String, Credentials>) -> HashMap<String, Credentials> {
+        let checks = self.checks.lock().expect("key checks poisoned");
+        keys.iter()
+            .filter(|(ex, _)| {
+                checks.get(*ex).is_some_and(|c| match c.status {
+                    KeyStatus::Verified { .. } => true,
+                    KeyStatus::Unverifiable => self.unverifiable.contains(*ex),
+                    KeyStatus::Unchecked => !self.verify,
+                    KeyStatus::Pending { .. } | KeyStatus::Refused { .. } => false,
+                })
+            })
+            .map(|(ex, creds)| (ex.clone(), creds.clone()))
+            .collect()
+    }
+}
+
+/// Ask `exchange`'s API-key info endpoint what the key in `ctx` may do
+pub async fn check(exchange: &str, ctx: &ExecutionContext) -> KeyStatus {
+    let Some(creds) = ctx.credentials.get(exchange) else {
+        return KeyStatus::Refused {
+            reason: "no key".to_string(),
+        };
+    };
+    let venue = Venue {
+        exchange,
+        base_url: ctx.config.base_url(exchange),
+        creds,
+        client: ctx.http.client(exchange),
+        recv_window_ms: ctx.config.recv_window_ms,
+    };
+    match venue.permissions().await {
+        Ok(Some(p)) if !p.trade => KeyStatus::Refused {
+            reason: "the key can't trade spot".to_string(),
+        },
+        Ok(Some(p)) if p.withdraw => KeyStatus::Refused {
+            reason: "the key allows withdrawals".to_string(),
+        },
+        Ok(Some(permissions)) => KeyStatus::Verified { permissions },
+        Ok(None) => KeyStatus::Unverifiable,
+        Err(e) => KeyStatus::Pending {
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+/// Check the loaded trading keys until every one has an answer, handing
+/// those cleared to trading after each round. Spawned at startup and after
+/// every reload; a round for keys a reload has since replaced is dropped.
+pub async fn verify_keys(state: Arc<tokio::sync::Mutex<AppState>>) {
+    loop {
+        let (checks, keys, contexts) = {
+            let shared_state = state.lock().await;
+            let checks = shared_state.key_checks.clone();
+            let keys = shared_state.credentials.clone();
+            let contexts: Vec<(String, ExecutionContext)> = checks
+                .pending()
+                .into_iter()
+                .map(|ex| {
+                    let ctx = ExecutionContext {
+                        credentials: keys.clone(),
+                        ..shared_state.execution_context(&ex)
+                    };
+                    (ex, ctx)
+                })
+                .collect();
+            (checks, keys, contexts)
+        };
+        if contexts.is_empty() {
+            return;
+        }
+
+        for (exchange, ctx) in &contexts {
+            let status = check(exchange, ctx).await;
+            let account = &keys[exchange].account;
+            match &status {
+                KeyStatus::Verified { .. } => tracing::info!(
+                    "trading key for {} ({} account) verified: trades, can't withdraw",
+                    exchange,
+                    account
+                ),
+                KeyStatus::Unverifiable if checks.unverifiable.contains(exchange) => {
+                    tracing::warn!(
+                        "{} doesn't report key permissions; make sure the {} \
+                         account's trading key can't withdraw",
+                        exchange,
+                        account
+                    )
+                }
+                KeyStatus::Unverifiable => tracing::error!(
+                    "trading key for {} ({} account) not used: the venue doesn't \
+                     report key permissions and execution.unverifiable_keys doesn't list it",
+                    exchange,
+                    account
+                ),
+                KeyStatus::Refused { reason } => tracing::error!(
+                    "trading key for {} ({} account) refused: {}",
+                    exchange,
+                    account,
+                    reason
+                ),
+                KeyStatus::Pending { error } => tracing::warn!(
+                    "cannot check the trading key for {} ({} account), retrying: {}",
+                    exchange,
+                    account,
+                    error.as_deref().unwrap_or_default()
+                ),
+                KeyStatus::Unchecked => {}
+            }
+            checks.set(exchange, status);
+        }
+
+        {
+            let mut shared_state = state.lock().await;
+            if !Arc::ptr_eq(&shared_state.key_checks, &checks) {
+                return;
+            }
+            shared_state.trading_credentials = Arc::new(checks.usable(&keys));
+        }
+        if checks.pending().is_empty() {
+            return;
+        }
+        tokio::time::sleep(RETRY_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::mock;
+    use crate::execution::{OrderType, EXECUTION_EXCHANGES};
+
+    #[tokio::test]
+    async fn trade_only_keys_pass_and_withdrawal_keys_are_refused() {
+        let http = mock::http();
+        for exchange in EXECUTION_EXCHANGES {
+            let mock = mock::venue(exchange, vec![(50_000.0, 1.0)]).await;
+            let ctx = mock::context(&http, &mock, exchange, OrderType::Ioc);
+            let expected = match *exchange {
+                "gateio" => KeyStatus::Unverifiable,
+                _ => KeyStatus::Verified {
+                    permissions: KeyPermissions {
+                        trade: true,
+                        withdraw: false,
+                    },
+                },
+            };
+            assert_eq!(check(exchange, &ctx).await, expected, "{}", exchange);
+
+            mock.allow_withdrawals();
+            let status = check(exchange, &ctx).await;
+            match *exchange {
+                "gateio" => assert_eq!(status, KeyStatus::Unverifiable),
+                _ => assert_eq!(
+                    status,
+                    KeyStatus::Refused {
+                        reason: "the key allows withdrawals".to_string()
+                    },
+                    "{}",
+                    exchange
+                ),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn keys_are_only_usable_once_cleared() {
+        let http = mock::http();
+        let mock = mock::venue("binance", vec![(50_000.0, 1.0)]).await;
+        let mut ctx = mock::context(&http, &mock, "binance", OrderType::Ioc);
+        let keys = (*ctx.credentials).clone();
+
+        let checks = KeyChecks::new(&keys, true, &[]);
+        assert!(checks.usable(&keys).is_empty());
+        assert_eq!(checks.pending(), vec!["binance".to_string()]);
+
+        // an unreachable venue leaves the key pending
+        ctx.config
+            .base_urls
+            .insert("binance".to_string(), "http://127.0.0.1:9".to_string());
+        let status = check("binance", &ctx).await;
+        assert!(matches!(status, KeyStatus::Pending { error: Some(_) }));
+        checks.set("binance", status);
+        assert!(checks.usable(&keys).is_empty());
+        assert!(checks.snapshot()["binance"].checked_at.is_none());
+
+        ctx.config
+            .base_urls
+            .insert("binance".to_string(), mock.base_url.clone());
+        checks.set("binance", check("binance", &ctx).await);
+        assert_eq!(checks.usable(&keys).len(), 1);
+        assert!(checks.pending().is_empty());
+
+        assert_eq!(KeyChecks::new(&keys, false, &[]).usable(&keys).len(), 1);
+        // unchecked keys don't trade while checks are on
+        let checks = KeyChecks::new(&keys, true, &[]);
+        checks.set("binance", KeyStatus::Unchecked);
+        assert!(checks.usable(&keys).is_empty());
+    }
+
+    #[tokio::test]
+    async fn unverifiable_keys_need_the_venue_listed() {
+        let http = mock::http();
+        let mock = mock::venue("gateio", vec![(50_000.0, 1.0)]).await;
+        let ctx = mock::context(&http, &mock, "gateio", OrderType::Ioc);
+        let keys = (*ctx.credentials).clone();
+        let status = check("gateio", &ctx).await;
+        assert_eq!(status, KeyStatus::Unverifiable);
+
+        let refused = KeyChecks::new(&keys, true, &[]);
+        refused.set("gateio", status.clone());
+        assert!(refused.usable(&keys).is_empty());
+
+        let listed = KeyChecks::new(&keys, true, &["gateio".to_string()]);
+        listed.set("gateio", status);
+        assert_eq!(listed.usable(&keys).len(), 1);
+    }
+}
@@ -0,0 +1,517 @@
+//! Guardrails every triangle passes before an order is sent, and the kill
+//! switch that stops all execution.
+//!
+//! The kill switch, today's PnL and what recovery left held are written to
+//! disk whenever they change, so a halted engine stays halted and the loss
+//! and exposure limits keep counting across restarts; a state file that
+//! can't be read keeps execution halted. Limits are read from the config on
+//! every check, so a reload applies them to the next triangle.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use super::{unix_ms, ExecutionError, Holding};
+use crate::config::RiskConfig;
+use crate::metrics;
+
+/// Why and since when execution is halted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Halt {
+    pub reason: String,
+    /// Name of the API key that engaged the switch
+    pub by: String,
+    /// Unix seconds
+    pub since: u64,
+}
+
+/// Contents of the state file. Files written before PnL and holdings were
+/// saved read as having neither.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SavedState {
+    halt: Option<Halt>,
+    #[serde(default)]
+    day: u64,
+    #[serde(default)]
+    daily_pnl: HashMap<String, f64>,
+    #[serde(default)]
+    held: HashMap<String, HashMap<String, f64>>,
+}
+
+#[derive(Debug, Default)]
+struct Book {
+    open_attempts: usize,
+    /// Amount per asset the attempts in flight trade
+    exposure: HashMap<String, f64>,
+    /// UTC day (days since the epoch) `daily_pnl` covers
+    day: u64,
+    /// Realized PnL per start asset of the executions finished that day
+    daily_pnl: HashMap<String, f64>,
+    /// Amount per exchange and asset that `hold` recovery left behind and
+    /// nobody has released yet
+    held: HashMap<String, HashMap<String, f64>>,
+}
+
+impl Book {
+    /// Start a new day's PnL at UTC midnight
+    fn roll(&mut self) {
+        let today = unix_ms() / 86_400_000;
+        if self.day != today {
+            self.day = today;
+            self.daily_pnl.clear();
+        }
+    }
+
+    /// Held amount of `asset` across every exchange
+    fn held(&self, asset: &str) -> f64 {
+        self.held
+            .values()
+            .filter_map(|assets| assets.get(asset))
+            .sum()
+    }
+}
+
+/// Limits and kill switch shared by every execution; survives reloads
+#[derive(Debug)]
+pub struct RiskGuard {
+    /// Where the state is persisted; `None` keeps it in memory
+    path: Option<PathBuf>,
+    /// Serializes writes so an older snapshot never replaces a newer one
+    saving: tokio::sync::Mutex<()>,
+    /// Mirrors `halt` so the hot path doesn't take a lock
+    halted: AtomicBool,
+    halt: Mutex<Option<Halt>>,
+    book: Mutex<Book>,
+}
+
+/// What `GET /risk` reports
+#[derive(Debug, Clone, Serialize)]
+pub struct RiskStatus {
+    pub halt: Option<Halt>,
+    pub open_attempts: usize,
+    pub exposure: BTreeMap<String, f64>,
+    pub daily_pnl: BTreeMap<String, f64>,
+    pub held: BTreeMap<String, BTreeMap<String, f64>>,
+}
+
+/// An admitted attempt; its exposure is released when dropped
+#[derive(Debug)]
+pub struct Permit<'a> {
+    guard: &'a RiskGuard,
+    exposure: Vec<(String, f64)>,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        let mut book = self.guard.book.lock().expect("risk book poisoned");
+        book.open_attempts -= 1;
+        for (asset, amount) in &self.exposure {
+            if let Some(open) = book.exposure.get_mut(asset) {
+                *open -= amount;
+                if *open <= 1e-12 {
+                    book.exposure.remove(asset);
+                }
+            }
+        }
+    }
+}
+
+impl RiskGuard {
+    /// Restore the kill switch, today's PnL and held amounts from `path`, if
+    /// they were saved there
+    pub fn new(path: Option<PathBuf>) -> Self {
+        let state = path.as_deref().map(load).unwrap_or_default();
+        if let Some(halt) = &state.halt {
+            tracing::warn!("execution halted by the kill switch: {}", halt.reason);
+        }
+        metrics::EXECUTION_HALTED.set(i64::from(state.halt.is_some()));
+        // a previous day's PnL is dropped by the first roll
+        let book = Book {
+            day: state.day,
+            daily_pnl: state.daily_pnl,
+            held: state.held,
+            ..Book::default()
+        };
+        Self {
+            path,
+            saving: tokio::sync::Mutex::new(()),
+            halted: AtomicBool::new(state.halt.is_some()),
+            halt: Mutex::new(state.halt),
+            book: Mutex::new(book),
+        }
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted.load(Ordering::SeqCst)
+    }
+
+    pub fn halt_state(&self) -> Option<Halt> {
+        self.halt.lock().expect("kill switch poisoned").clone()
+    }
+
+    /// Stop all execution at once, without waiting for a save in progress.
+    /// The halt applies even if it can't be saved; the error says it won't
+    /// survive a restart.
+    pub async fn halt(&self, reason: String, by: String) -> std::io::Result<Halt> {
+        let halt = Halt {
+            reason,
+            by,
+            since: unix_ms() / 1000,
+        };
+        self.engage(&halt);
+        tracing::error!(target: "alert", by = %halt.by, "execution halted: {}", halt.reason);
+        let _saving = self.saving.lock().await;
+        // a resume that held the lock meanwhile must not undo this halt
+        self.engage(&halt);
+        self.save(Some(halt.clone())).await?;
+        Ok(halt)
+    }
+
+    fn engage(&self, halt: &Halt) {
+        self.halted.store(true, Ordering::SeqCst);
+        *self.halt.lock().expect("kill switch poisoned") = Some(halt.clone());
+        metrics::EXECUTION_HALTED.set(1);
+    }
+
+    /// Allow execution again. Saved first: if that fails, the engine stays
+    /// halted rather than resuming only until the next restart.
+    pub async fn resume(&self) -> std::io::Result<()> {
+        let _saving = self.saving.lock().await;
+        self.save(None).await?;
+        *self.halt.lock().expect("kill switch poisoned") = None;
+        self.halted.store(false, Ordering::SeqCst);
+        metrics::EXECUTION_HALTED.set(0);
+        Ok(())
+    }
+
+    /// Write `halt` with the current book via a temp file, so a crash never
+    /// leaves a truncated state behind. Callers hold `saving`.
+    async fn save(&self, halt: Option<Halt>) -> std::io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let state = {
+            let book = self.book.lock().expect("risk book poisoned");
+            SavedState {
+                halt,
+                day: book.day,
+                daily_pnl: book.daily_pnl.clone(),
+                held: book.held.clone(),
+            }
+        };
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        tokio::fs::write(&tmp, serde_json::to_vec(&state)?).await?;
+        tokio::fs::rename(&tmp, path).await
+    }
+
+    /// Check an attempt on `exchange` trading `exposure` (amount per asset)
+    /// against the kill switch and `limits`, and count it as open until the
+    /// permit is dropped
+    pub fn admit(
+        &self,
+        exchange: &str,
+        exposure: Vec<(String, f64)>,
+        limits: &RiskConfig,
+    ) -> Result<Permit<'_>, ExecutionError> {
+        let refuse = |limit: &str, why: String| {
+            metrics::RISK_REJECTIONS
+                .with_label_values(&[exchange, limit])
+                .inc();
+            Err(ExecutionError::RiskLimit(why))
+        };
+        if let Some(halt) = self.halt_state() {
+            metrics::RISK_REJECTIONS
+                .with_label_values(&[exchange, "halted"])
+                .inc();
+            return Err(ExecutionError::Halted(halt.reason));
+        }
+
+        let mut book = self.book.lock().expect("risk book poisoned");
+        book.roll();
+        if book.open_attempts >= limits.max_open_attempts {
+            return refuse(
+                "open_attempts",
+                format!(
+                    "{} attempts already open (max_open_attempts = {})",
+                    book.open_attempts, limits.max_open_attempts
+                ),
+            );
+        }
+        // one asset over its loss limit stops every triangle for the day
+        for (asset, limit) in &limits.max_daily_loss {
+            let pnl = book.daily_pnl.get(asset).copied().unwrap_or(0.0);
+            if -pnl >= *limit {
+                return refuse(
+                    "daily_loss",
+                    format!(
+                        "{} {} lost today (max_daily_loss = {}); trading resumes at 00:00 UTC",
+                        -pnl, asset, limit
+                    ),
+                );
+            }
+        }
+        for (asset, amount) in &exposure {
+            let Some(cap) = limits.max_exposure.get(asset) else {
+                continue;
+            };
+            // leftovers still held count until someone releases them
+            let open = book.exposure.get(asset).copied().unwrap_or(0.0);
+            let held = book.held(asset);
+            if open + held + amount > cap * (1.0 + 1e-9) {
+                return refuse(
+                    "exposure",
+                    format!(
+                        "{} {} would be in flight or held \
+                         (max_exposure = {}, {} already open, {} held)",
+                        open + held + amount,
+                        asset,
+                        cap,
+                        open,
+                        held
+                    ),
+                );
+            }
+        }
+
+        book.open_attempts += 1;
+        for (asset, amount) in &exposure {
+            *book.exposure.entry(asset.clone()).or_insert(0.0) += amount;
+        }
+        Ok(Permit {
+            guard: self,
+            exposure,
+        })
+    }
+
+    /// Count a finished execution's realized PnL in `asset` towards today's
+    /// and what it left `held` towards `exchange`'s holdings, then save both
+    pub async fn record(
+        &self,
+        exchange: &str,
+        asset: &str,
+        pnl: f64,
+        held: &[Holding],
+    ) -> std::io::Result<()> {
+        {
+            let mut book = self.book.lock().expect("risk book poisoned");
+            book.roll();
+            *book.daily_pnl.entry(asset.to_string()).or_insert(0.0) += pnl;
+            for holding in held {
+                *book
+                    .held
+                    .entry(exchange.to_string())
+                    .or_default()
+                    .entry(holding.asset.clone())
+                    .or_insert(0.0) += holding.amount;
+            }
+        }
+        let _saving = self.saving.lock().await;
+        self.save(self.halt_state()).await
+    }
+
+    /// Stop counting what is held of `asset` on `exchange`, once it has been
+    /// unwound or accepted. Returns the amount released.
+    pub async fn release_held(&self, exchange: &str, asset: &str) -> std::io::Result<f64> {
+        let released = {
+            let mut book = self.book.lock().expect("risk book poisoned");
+            let Some(assets) = book.held.get_mut(exchange) else {
+                return Ok(0.0);
+            };
+            let released = assets.remove(asset).unwrap_or(0.0);
+            if assets.is_empty() {
+                book.held.remove(exchange);
+            }
+            released
+        };
+        let _saving = self.saving.lock().await;
+        self.save(self.halt_state()).await?;
+        Ok(released)
+    }
+
+    pub fn status(&self) -> RiskStatus {
+        let mut book = self.book.lock().expect("risk book poisoned");
+        book.roll();
+        RiskStatus {
+            halt: self.halt_state(),
+            open_attempts: book.open_attempts,
+            exposure: book.exposure.iter().map(|(a, v)| (a.clone(), *v)).collect(),
+            daily_pnl: book
+                .daily_pnl
+                .iter()
+                .map(|(a, v)| (a.clone(), *v))
+                .collect(),
+            held: book
+                .held
+                .iter()
+                .map(|(exchange, assets)| {
+                    let assets = assets.iter().map(|(a, v)| (a.clone(), *v)).collect();
+                    (exchange.clone(), assets)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Saved state, if any. An unreadable file halts: it may have recorded a
+/// halt.
+fn load(path: &Path) -> SavedState {
+    let raw = match std::fs::read(path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return SavedState::default(),
+        Err(e) => return unreadable(path, e.to_string()),
+    };
+    serde_json::from_slice::<SavedState>(&raw).unwrap_or_else(|e| unreadable(path, e.to_string()))
+}
+
+fn unreadable(path: &Path, error: String) -> SavedState {
+    SavedState {
+        halt: Some(Halt {
+            reason: format!(
+                "risk state {} is unreadable ({}); resume to clear",
+                path.display(),
+                error
+            ),
+            by: "startup".to_string(),
+            since: unix_ms() / 1000,
+        }),
+        ..SavedState::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state_file(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("arb-risk-{}-{}", name, std::process::id()))
+            .join("kill-switch.json")
+    }
+
+    fn limits() -> RiskConfig {
+        RiskConfig {
+            max_open_attempts: 2,
+            max_daily_loss: HashMap::from([("USDT".to_string(), 50.0)]),
+            max_exposure: HashMap::from([("BTC".to_string(), 0.03)]),
+            ..RiskConfig::default()
+        }
+    }
+
+    fn exposure(btc: f64) -> Vec<(String, f64)> {
+        vec![("USDT".to_string(), 1_000.0), ("BTC".to_string(), btc)]
+    }
+
+    #[tokio::test]
+    async fn kill_switch_survives_a_restart() {
+        let path = state_file("restart");
+        let guard = RiskGuard::new(Some(path.clone()));
+        guard
+            .halt("venue acting up".to_string(), "ops".to_string())
+            .await
+            .unwrap();
+        let refused = guard.admit("binance", exposure(0.01), &limits());
+        assert!(matches!(refused, Err(ExecutionError::Halted(_))));
+
+        let restarted = RiskGuard::new(Some(path.clone()));
+        assert!(restarted.is_halted());
+        assert_eq!(restarted.halt_state().unwrap().by, "ops");
+        restarted.resume().await.unwrap();
+        assert!(!RiskGuard::new(Some(path.clone())).is_halted());
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn halting_does_not_wait_for_a_save_in_progress() {
+        use futures::FutureExt;
+
+        let guard = RiskGuard::new(None);
+        let saving = guard.saving.lock().await;
+        let halt = guard.halt("now".to_string(), "ops".to_string());
+        assert!(halt.now_or_never().is_none());
+        assert!(guard.is_halted());
+        drop(saving);
+        let refused = guard.admit("binance", exposure(0.01), &limits());
+        assert!(matches!(refused, Err(ExecutionError::Halted(_))));
+    }
+
+    #[test]
+    fn unreadable_state_file_halts() {
+        let path = state_file("garbage");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "{ not json").unwrap();
+        let guard = RiskGuard::new(Some(path.clone()));
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert!(guard.is_halted());
+        assert!(!RiskGuard::new(Some(path)).is_halted());
+    }
+
+    #[test]
+    fn open_attempts_and_exposure_are_capped_until_released() {
+        let guard = RiskGuard::new(None);
+        let first = guard.admit("binance", exposure(0.02), &limits()).unwrap();
+        // 0.02 + 0.02 BTC is past the 0.03 cap
+        let refused = guard.admit("bybit", exposure(0.02), &limits());
+        assert!(matches!(refused, Err(ExecutionError::RiskLimit(_))));
+        let second = guard.admit("bybit", exposure(0.01), &limits()).unwrap();
+        let refused = guard.admit("kucoin", exposure(0.0), &limits());
+        assert!(matches!(refused, Err(ExecutionError::RiskLimit(_))));
+
+        drop(first);
+        assert_eq!(guard.status().open_attempts, 1);
+        assert!((guard.status().exposure["BTC"] - 0.01).abs() < 1e-12);
+        drop(second);
+        assert!(guard.status().exposure.is_empty());
+    }
+
+    #[tokio::test]
+    async fn daily_loss_limit_stops_trading() {
+        let path = state_file("daily-loss");
+        let guard = RiskGuard::new(Some(path.clone()));
+        guard.record("binance", "USDT", -30.0, &[]).await.unwrap();
+        assert!(guard.admit("binance", exposure(0.0), &limits()).is_ok());
+        guard.record("binance", "USDT", -25.0, &[]).await.unwrap();
+        let refused = guard.admit("binance", exposure(0.0), &limits());
+        let Err(ExecutionError::RiskLimit(why)) = refused else {
+            panic!("admitted past the loss limit");
+        };
+        assert!(why.contains("55 USDT lost today"), "{}", why);
+
+        // a restart doesn't reset today's loss
+        let restarted = RiskGuard::new(Some(path.clone()));
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert!(restarted
+            .admit("binance", exposure(0.0), &limits())
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn held_leftovers_count_toward_exposure_until_released() {
+        let path = state_file("held");
+        let guard = RiskGuard::new(Some(path.clone()));
+        let held = [Holding {
+            asset: "BTC".to_string(),
+            amount: 0.02,
+        }];
+        guard.record("bybit", "USDT", 0.0, &held).await.unwrap();
+        // 0.02 held + 0.02 in flight is past the 0.03 cap, on any venue
+        let refused = guard.admit("binance", exposure(0.02), &limits());
+        assert!(matches!(refused, Err(ExecutionError::RiskLimit(_))));
+
+        let restarted = RiskGuard::new(Some(path.clone()));
+        assert!((restarted.status().held["bybit"]["BTC"] - 0.02).abs() < 1e-12);
+        let refused = restarted.admit("binance", exposure(0.02), &limits());
+        assert!(matches!(refused, Err(ExecutionError::RiskLimit(_))));
+        let released = restarted.release_held("bybit", "BTC").await.unwrap();
+        assert!((released - 0.02).abs() < 1e-12);
+        assert!(restarted
+            .admit("binance", exposure(0.02), &limits())
+            .is_ok());
+        assert!(RiskGuard::new(Some(path.clone())).status().held.is_empty());
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,75 @@
+//! Request signatures of the venues orders can be sent to.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256, Sha512};
+
+fn hmac_sha256(secret: &str, payload: &str) -> Vec<u8> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(payload.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Binance and Bybit: lowercase hex HMAC-SHA256
+pub fn hmac_sha256_hex(secret: &str, payload: &str) -> String {
+    hex::encode(hmac_sha256(secret, payload))
+}
+
+/// KuCoin: base64 HMAC-SHA256, for both the request and the passphrase
+pub fn hmac_sha256_base64(secret: &str, payload: &str) -> String {
+    STANDARD.encode(hmac_sha256(secret, payload))
+}
+
+/// Gate v4: lowercase hex HMAC-SHA512
+pub fn hmac_sha512_hex(secret: &str, payload: &str) -> String {
+    let mut mac =
+        Hmac::<Sha512>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(payload.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Gate v4 signs the hex SHA-512 of the body, not the body itself
+pub fn sha512_hex(payload: &str) -> String {
+    hex::encode(Sha512::digest(payload.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binance_documented_example() {
+        let secret = "NhqPtmdSJYdKjVHjA7PZj4Mge3R5YNiP1e3UZjInClVN65XAbvqqM6A7H5fATj0j";
+        let query = "symbol=LTCBTC&side=BUY&type=LIMIT&timeInForce=GTC&quantity=1&price=0.1\
+                     &recvWindow=5000&timestamp=1499827319559";
+        assert_eq!(
+            hmac_sha256_hex(secret, query),
+            "c8db56825ae71d6d79447849e617115f4a920fa2acdcab2b053c4b2838bd6b71"
+        );
+    }
+
+    #[test]
+    fn rfc4231_vectors() {
+        // test case 2: key "Jefe"
+        let data = "what do ya want for nothing?";
+        assert_eq!(
+            hmac_sha256_hex("Jefe", data),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            hmac_sha512_hex("Jefe", data),
+            "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554\
+             9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737"
+        );
+        assert_eq!(
+            hmac_sha256_base64("Jefe", data),
+            "W9zBRr9gdU5qBCQmCJV1x1oAPwidJzmDnexYuWTsOEM="
+        );
+    }
+
+    #[test]
+    fn sha512_of_empty_body() {
+        assert!(sha512_hex("").starts_with("cf83e1357eefb8bdf1542850d66d8007"));
+    }
+}
@@ -0,0 +1,80 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A feed is reported down after this many failed fetches in a row
+pub const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+
+/// Last known state of one exchange feed
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FeedStatus {
+    /// Unix seconds of the last fetch that returned data
+    pub last_success: Option<u64>,
+    /// Unix seconds of the last failed fetch
+    pub last_failure: Option<u64>,
+    pub last_error: Option<String>,
+    pub consecutive_failures: u32,
+    /// Whether the last WebSocket session connected (`None` for REST-only venues)
+    pub ws_connected: Option<bool>,
+}
+
+impl FeedStatus {
+    /// Up once it has succeeded at least once and isn't failing repeatedly
+    pub fn is_up(&self) -> bool {
+        self.last_success.is_some() && self.consecutive_failures < MAX_CONSECUTIVE_FAILURES
+    }
+}
+
+/// Feed status for every exchange, updated by the fetch dispatcher
+#[derive(Debug, Default)]
+pub struct FeedRegistry {
+    feeds: Mutex<BTreeMap<String, FeedStatus>>,
+}
+
+impl FeedRegistry {
+    pub fn new(exchanges: &[&str]) -> Self {
+        let feeds = exchanges
+            .iter()
+            .map(|ex| (ex.to_string(), FeedStatus::default()))
+            .collect();
+        Self {
+            feeds: Mutex::new(feeds),
+        }
+    }
+
+    pub fn record_success(&self, exchange: &str) {
+        self.update(exchange, |f| {
+            f.last_success = Some(unix_now());
+            f.consecutive_failures = 0;
+        });
+    }
+
+    pub fn record_failure(&self, exchange: &str, error: &str) {
+        self.update(exchange, |f| {
+            f.last_failure = Some(unix_now());
+            f.last_error = Some(error.to_string());
+            f.consecutive_failures += 1;
+        });
+    }
+
+    pub fn set_ws_connected(&self, exchange: &str, connected: bool) {
+        self.update(exchange, |f| f.ws_connected = Some(connected));
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<String, FeedStatus> {
+        self.feeds.lock().expect("feed registry poisoned").clone()
+    }
+
+    fn update(&self, exchange: &str, f: impl FnOnce(&mut FeedStatus)) {
+        let mut feeds = self.feeds.lock().expect("feed registry poisoned");
+        f(feeds.entry(exchange.to_string()).or_default());
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -1,43 +1,193 @@
-use crate::models::{PairPrice, TriangularResult};
+use crate::models::{PairPrice, Side, TradeLeg, TriangularResult};
 use crate::utils::round2;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Counters from one scanner pass, exported as metrics by the caller
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScanStats {
+    /// Closed triangles whose cross rate was computed
+    pub evaluated: usize,
+    /// Unique triangles above `min_profit` after fees
+    pub passing: usize,
+    /// Best post-fee profit (percent) seen, even if below `min_profit`
+    pub best_profit: Option<f64>,
+}
+
+/// What the account holds on a venue, to size its opportunities by
+pub struct Holdings<'a> {
+    /// Available balance per canonical asset
+    pub balances: &'a BTreeMap<String, f64>,
+    /// `execution.start_amounts`: only these assets start a triangle, or
+    /// any asset when empty
+    pub start_amounts: &'a HashMap<String, f64>,
+    /// All three legs trade at once from held balances (`inventory` mode),
+    /// so every leg's balance caps the size, not just the start asset's
+    pub inventory: bool,
+}
+
+/// Which of a triangle's assets to start it from: of the `candidates`, the
+/// one whose balance is worth the most, or the first candidate when
+/// balances aren't known. `value[i]` is the balance of asset `i` in units
+/// of asset 0. `None` when no candidate is held. Scans and executions both
+/// pick with it, so a triangle is executed the way it was sized.
+pub fn start_asset(candidates: [bool; 3], value: Option<[f64; 3]>) -> Option<usize> {
+    let mut picks = (0..3).filter(|&i| candidates[i]);
+    let Some(value) = value else {
+        return picks.next();
+    };
+    picks
+        .filter(|&i| value[i] > 0.0)
+        .fold(None, |best, i| match best {
+            Some(b) if value[b] >= value[i] => Some(b),
+            _ => Some(i),
+        })
+}
+
+impl Holdings<'_> {
+    /// Rotation of a triangle to start it from and the most of that start
+    /// asset it can commit. `scale[i]` is the amount of asset `i` one unit
+    /// of asset 0 turns into along the path.
+    fn size(&self, assets: [&String; 3], scale: [f64; 3]) -> (usize, f64) {
+        let held = assets.map(|a| self.balances.get(a).copied().unwrap_or(0.0));
+        // each balance in units of asset 0, so they can be compared
+        let value = [0, 1, 2].map(|i| held[i] / scale[i]);
+        let candidates = if self.start_amounts.is_empty() {
+            [true; 3]
+        } else {
+            assets.map(|a| self.start_amounts.get(a).is_some_and(|x| *x > 0.0))
+        };
+        let Some(start) = start_asset(candidates, Some(value)) else {
+            return (0, 0.0);
+        };
+        let size = if self.inventory {
+            value.iter().cloned().fold(f64::INFINITY, f64::min) * scale[start]
+        } else {
+            held[start]
+        };
+        (start, size)
+    }
+}
+
+/// One venue's prices as fed to the scanner
+pub struct VenuePrices<'a> {
+    pub exchange: &'a str,
+    pub prices: &'a [PairPrice],
+    /// Taker fee (%) of the venue, charged on each of its legs
+    pub fee_per_leg: f64,
+    pub holdings: Option<Holdings<'a>>,
+}
+
+/// One direction of a market in the scanner graph
+struct Edge {
+    /// Index of the venue the market is listed on
+    venue: usize,
+    fee_mult: f64,
+    rate: f64,
+    liquidity: f64,
+    /// Pair as the venue names it, oriented along the edge
+    label: String,
+    /// Quoted bid/ask spread in basis points, if the venue reports a book
+    spread_bps: Option<f64>,
+    /// Market traded along the edge and the order that does it: selling
+    /// the base for the direct edge, buying it for the inverse
+    symbol: String,
+    side: Side,
+    /// Last price of the market, quote per base
+    price: f64,
+}
+
+impl Edge {
+    fn leg(&self, from: &str, to: &str) -> TradeLeg {
+        TradeLeg {
+            symbol: self.symbol.clone(),
+            side: self.side,
+            from: from.to_string(),
+            to: to.to_string(),
+            price: self.price,
+        }
+    }
+}
+
+fn spread_bps(p: &PairPrice) -> Option<f64> {
+    match (p.bid, p.ask) {
+        (Some(bid), Some(ask)) if bid > 0.0 && ask >= bid => {
+            Some((ask - bid) / ((ask + bid) / 2.0) * 10_000.0)
+        }
+        _ => None,
+    }
+}
 
 /// Scan triangles using given pair prices (spot only).
 /// Uses last price (not bid/ask) by design.
 /// Applies floating-point tolerance to avoid false zero-profit rejection.
+/// All venues share one graph; where several list the same pair, the last
+/// one wins, and a triangle may cross venues. Stats are kept per venue
+/// (aligned with `venues`) and count each triangle on every venue it uses.
+/// With holdings, each single-venue triangle starts where [`start_asset`]
+/// says and its size is capped by what is held. Results come best first.
 pub fn scan_triangles(
-    prices: &[PairPrice],
+    venues: &[VenuePrices],
     min_profit: f64,
-    fee_per_leg: f64,
-) -> Vec<TriangularResult> {
+) -> (Vec<TriangularResult>, Vec<ScanStats>) {
     const EPS: f64 = 1e-6;
+    let mut stats = vec![ScanStats::default(); venues.len()];
 
-    let mut rate: HashMap<(String, String), (f64, f64)> = HashMap::new(); // (price, liquidity)
+    let mut rate: HashMap<(String, String), Edge> = HashMap::new();
     let mut neighbors: HashMap<String, HashSet<String>> = HashMap::new();
 
-    for p in prices {
+    let listed = venues
+        .iter()
+        .enumerate()
+        .flat_map(|(i, v)| v.prices.iter().map(move |p| (i, v, p)));
+    for (venue, v, p) in listed {
+        let fee_mult = 1.0 - (v.fee_per_leg / 100.0);
         if !p.is_spot || !p.price.is_finite() || p.price <= 0.0 {
             continue;
         }
 
-        let a = p.base.to_uppercase();
-        let b = p.quote.to_uppercase();
+        // already canonical; the fetchers map venue aliases
+        let a = p.base.clone();
+        let b = p.quote.clone();
 
         // direct
-        rate.insert((a.clone(), b.clone()), (p.price, p.liquidity));
+        let spread = spread_bps(p);
+        rate.insert(
+            (a.clone(), b.clone()),
+            Edge {
+                venue,
+                fee_mult,
+                rate: p.price,
+                liquidity: p.liquidity,
+                label: format!("{}/{}", p.native_base, p.native_quote),
+                spread_bps: spread,
+                symbol: p.symbol.clone(),
+                side: Side::Sell,
+                price: p.price,
+            },
+        );
         neighbors.entry(a.clone()).or_default().insert(b.clone());
 
         // inverse (approximate, acceptable for signal scanning)
-        rate.insert((b.clone(), a.clone()), (1.0 / p.price, p.liquidity));
+        rate.insert(
+            (b.clone(), a.clone()),
+            Edge {
+                venue,
+                fee_mult,
+                rate: 1.0 / p.price,
+                liquidity: p.liquidity,
+                label: format!("{}/{}", p.native_quote, p.native_base),
+                spread_bps: spread,
+                symbol: p.symbol.clone(),
+                side: Side::Buy,
+                price: p.price,
+            },
+        );
         neighbors.entry(b.clone()).or_default().insert(a.clone());
     }
 
     let mut seen: HashSet<(String, String, String)> = HashSet::new();
     let mut out: Vec<TriangularResult> = Vec::new();
 
-    let fee_mult = 1.0 - (fee_per_leg / 100.0);
-    let total_fee_percent = 3.0 * fee_per_leg;
-
     for (a, bs) in &neighbors {
         for b in bs {
             if a == b {
@@ -50,32 +200,49 @@ pub fn scan_triangles(
                         continue;
                     }
 
-                    if !neighbors.get(c).map_or(false, |s| s.contains(a)) {
+                    if !neighbors.get(c).is_some_and(|s| s.contains(a)) {
                         continue;
                     }
 
-                    let (r1, l1) = match rate.get(&(a.clone(), b.clone())) {
-                        Some(v) => *v,
+                    let e1 = match rate.get(&(a.clone(), b.clone())) {
+                        Some(e) => e,
                         None => continue,
                     };
-                    let (r2, l2) = match rate.get(&(b.clone(), c.clone())) {
-                        Some(v) => *v,
+                    let e2 = match rate.get(&(b.clone(), c.clone())) {
+                        Some(e) => e,
                         None => continue,
                     };
-                    let (r3, l3) = match rate.get(&(c.clone(), a.clone())) {
-                        Some(v) => *v,
+                    let e3 = match rate.get(&(c.clone(), a.clone())) {
+                        Some(e) => e,
                         None => continue,
                     };
 
-                    let gross = r1 * r2 * r3;
+                    let gross = e1.rate * e2.rate * e3.rate;
                     if !gross.is_finite() {
                         continue;
                     }
 
                     // apply fees multiplicatively
-                    let net = gross * fee_mult.powi(3);
+                    let net = gross * e1.fee_mult * e2.fee_mult * e3.fee_mult;
                     let profit_after = (net - 1.0) * 100.0;
 
+                    // venues the triangle trades on, in path order
+                    let mut used = Vec::with_capacity(3);
+                    for v in [e1.venue, e2.venue, e3.venue] {
+                        if !used.contains(&v) {
+                            used.push(v);
+                        }
+                    }
+                    for &v in &used {
+                        let stats = &mut stats[v];
+                        stats.evaluated += 1;
+                        if profit_after.is_finite()
+                            && stats.best_profit.is_none_or(|best| profit_after > best)
+                        {
+                            stats.best_profit = Some(profit_after);
+                        }
+                    }
+
                     // 🔴 FIX: EPS-tolerant comparison on post-fee profit
                     if !profit_after.is_finite() || profit_after + EPS < min_profit {
                         continue;
@@ -83,7 +250,7 @@ pub fn scan_triangles(
 
                     let profit_before = (gross - 1.0) * 100.0;
 
-                    let reps = vec![
+                    let reps = [
                         (a.clone(), b.clone(), c.clone()),
                         (b.clone(), c.clone(), a.clone()),
                         (c.clone(), a.clone(), b.clone()),
@@ -93,17 +260,52 @@ pub fn scan_triangles(
                         continue;
                     }
 
-                    let leg_liqs = [l1, l2, l3];
+                    for &v in &used {
+                        stats[v].passing += 1;
+                    }
+                    let holdings = match used[..] {
+                        [v] => venues[v].holdings.as_ref(),
+                        _ => None,
+                    };
+                    let (start, max_size) = match holdings {
+                        Some(h) => {
+                            let scale = [1.0, e1.rate, e1.rate * e2.rate];
+                            let (start, size) = h.size([a, b, c], scale);
+                            (start, Some(size))
+                        }
+                        None => (0, None),
+                    };
+                    let mut assets = [a, b, c];
+                    let mut edges = [e1, e2, e3];
+                    assets.rotate_left(start);
+                    edges.rotate_left(start);
+                    let [a, b, c] = assets;
+                    let [e1, e2, e3] = edges;
+
+                    let leg_liqs = [e1.liquidity, e2.liquidity, e3.liquidity];
                     let min_liq = leg_liqs.iter().cloned().fold(f64::INFINITY, f64::min);
 
+                    let exchange = used
+                        .iter()
+                        .map(|&v| venues[v].exchange)
+                        .collect::<Vec<_>>()
+                        .join("+");
+                    let fees: f64 = [e1, e2, e3]
+                        .iter()
+                        .map(|e| venues[e.venue].fee_per_leg)
+                        .sum();
                     out.push(TriangularResult {
+                        exchange,
                         triangle: format!("{} → {} → {} → {}", a, b, c, a),
-                        pairs: format!("{}/{} | {}/{} | {}/{}", a, b, b, c, c, a),
+                        pairs: format!("{} | {} | {}", e1.label, e2.label, e3.label),
                         profit_before_fees: round2(profit_before),
-                        trade_fees: round2(total_fee_percent),
+                        trade_fees: round2(fees),
                         profit_after_fees: round2(profit_after),
                         leg_liquidities: leg_liqs,
+                        leg_spreads_bps: [e1, e2, e3].map(|e| e.spread_bps.map(round2)),
                         min_liquidity: min_liq,
+                        legs: [e1.leg(a, b), e2.leg(b, c), e3.leg(c, a)],
+                        max_size,
                     });
                 }
             }
@@ -116,5 +318,5 @@ pub fn scan_triangles(
             .unwrap_or(std::cmp::Ordering::Equal)
     });
 
-    out
-    }
+    (out, stats)
+}
@@ -1,52 +1,231 @@
-mod models;
+mod assets;
+mod auth;
+mod basis;
+mod config;
+mod error;
 mod exchanges;
+mod execution;
+mod health;
 mod logic;
+mod metrics;
+mod models;
+mod pair_cache;
+mod paper;
+mod ratelimit;
 mod routes;
+mod scanner;
 mod utils;
 
 use axum::{
+    http::{header, HeaderName, HeaderValue, Method},
+    middleware,
     routing::{get, post},
     Router,
 };
+use clap::Parser;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::sync::Mutex;
 use tokio::net::TcpListener;
-use tower_http::cors::{CorsLayer, Any};
+use tokio::sync::Mutex;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
 use tower_http::services::ServeDir;
 
-use crate::routes::{ui_handler, scan_handler};
+use crate::auth::{require_scope, Scope};
+use crate::config::{Cli, Config};
 use crate::models::AppState;
+use crate::routes::{
+    balances_handler, basis_scan_handler, credentials_handler, execute_handler, executions_handler,
+    healthz_handler, kill_switch_handler, metrics_handler, paper_portfolio_handler,
+    paper_trades_handler, readyz_handler, refresh_metadata_handler, release_held_handler,
+    results_handler, risk_handler, scan_handler, status_handler, ui_handler,
+};
 
 #[tokio::main]
 async fn main() {
     // init tracing for logs
     tracing_subscriber::fmt::init();
+    metrics::init();
+
+    let cli = Cli::parse();
+    if let Some(plain) = &cli.seal_credentials {
+        match Config::seal_credentials(&cli, plain) {
+            Ok(done) => {
+                println!("{}", done);
+                return;
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(2);
+            }
+        }
+    }
+    let config = match Config::load(&cli) {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
 
-    let shared_state = Arc::new(Mutex::new(AppState::default()));
+    let addr = config.server.bind;
+    let static_dir = config.server.static_dir.clone();
 
-    // CORS to allow the static frontend to call /scan
+    // CORS: the bundled UI is same-origin; other frontends must be listed
+    let origins = &config.server.cors_origins;
+    let allow_origin = if origins.iter().any(|o| o == "*") {
+        AllowOrigin::from(Any)
+    } else {
+        AllowOrigin::list(
+            origins
+                .iter()
+                .map(|o| HeaderValue::from_str(o).expect("cors origin validated at load")),
+        )
+    };
     let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+        .allow_origin(allow_origin)
+        .allow_methods([Method::GET, Method::POST])
+        .allow_headers([
+            header::CONTENT_TYPE,
+            header::AUTHORIZATION,
+            HeaderName::from_static("x-api-key"),
+        ]);
+
+    if config.auth.keys.is_empty() {
+        tracing::warn!(
+            "no auth.keys configured: every endpoint, admin routes included, is open to anyone"
+        );
+    }
+    if config.execution.enabled && !config.execution.verify_keys {
+        tracing::warn!("execution.verify_keys is off: trading keys are used unchecked");
+    }
+
+    let state = match AppState::new(config) {
+        Ok(state) => state,
+        Err(e) => {
+            eprintln!("cannot build HTTP client: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let shared_state = Arc::new(Mutex::new(state));
+    let scoped =
+        |scope: Scope| middleware::from_fn_with_state((shared_state.clone(), scope), require_scope);
+
+    tokio::spawn(reload_on_sighup(cli, shared_state.clone()));
+    tokio::spawn(scanner::background_scans(shared_state.clone()));
+    tokio::spawn(execution::background_refresh(shared_state.clone()));
+    tokio::spawn(execution::verify_keys(shared_state.clone()));
 
     let app = Router::new()
         .route("/api", get(ui_handler))
-        .route("/scan", post(scan_handler))
-        .nest_service("/", ServeDir::new("static"))
+        .route(
+            "/scan",
+            post(scan_handler)
+                .route_layer(middleware::from_fn_with_state(
+                    shared_state.clone(),
+                    ratelimit::limit_scans,
+                ))
+                .route_layer(scoped(Scope::Scan)),
+        )
+        .route(
+            "/scan/basis",
+            post(basis_scan_handler)
+                .route_layer(middleware::from_fn_with_state(
+                    shared_state.clone(),
+                    ratelimit::limit_scans,
+                ))
+                .route_layer(scoped(Scope::Scan)),
+        )
+        .route(
+            "/results",
+            get(results_handler).route_layer(scoped(Scope::Read)),
+        )
+        .route(
+            "/status",
+            get(status_handler).route_layer(scoped(Scope::Read)),
+        )
+        .route(
+            "/paper/portfolio",
+            get(paper_portfolio_handler).route_layer(scoped(Scope::Read)),
+        )
+        .route(
+            "/paper/trades",
+            get(paper_trades_handler).route_layer(scoped(Scope::Read)),
+        )
+        .route(
+            "/execute",
+            post(execute_handler).route_layer(scoped(Scope::Trade)),
+        )
+        .route(
+            "/executions",
+            get(executions_handler).route_layer(scoped(Scope::Trade)),
+        )
+        .route(
+            "/balances",
+            get(balances_handler).route_layer(scoped(Scope::Trade)),
+        )
+        .route("/risk", get(risk_handler).route_layer(scoped(Scope::Trade)))
+        .route(
+            "/admin/kill-switch",
+            post(kill_switch_handler).route_layer(scoped(Scope::Admin)),
+        )
+        .route(
+            "/admin/release-held",
+            post(release_held_handler).route_layer(scoped(Scope::Admin)),
+        )
+        .route(
+            "/admin/credentials",
+            get(credentials_handler).route_layer(scoped(Scope::Admin)),
+        )
+        .route(
+            "/admin/refresh-metadata",
+            post(refresh_metadata_handler).route_layer(scoped(Scope::Admin)),
+        )
+        .route("/metrics", get(metrics_handler))
+        .route("/healthz", get(healthz_handler))
+        .route("/readyz", get(readyz_handler))
+        .nest_service("/", ServeDir::new(static_dir))
         .layer(cors)
         .with_state(shared_state);
 
-    let port = std::env::var("PORT").unwrap_or_else(|_| "8080".to_string());
-    let addr: SocketAddr = format!("0.0.0.0:{}", port).parse().expect("invalid addr");
-    tracing::info!(%port, "starting server");
+    tracing::info!(%addr, "starting server");
 
     let listener = TcpListener::bind(addr)
         .await
         .expect("Failed to bind address");
 
-    axum::serve(listener, app)
-        .await
-        .expect("server error");
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .expect("server error");
 }
+
+/// Re-read the config file on SIGHUP and apply the non-structural settings.
+/// An invalid file is logged and the running config is kept.
+#[cfg(unix)]
+async fn reload_on_sighup(cli: Cli, state: Arc<Mutex<AppState>>) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hup = match signal(SignalKind::hangup()) {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::error!("cannot install SIGHUP handler: {}", e);
+            return;
+        }
+    };
+
+    while hup.recv().await.is_some() {
+        match Config::load(&cli) {
+            Ok(fresh) => {
+                state.lock().await.reload(fresh);
+                tracing::info!("configuration reloaded");
+                tokio::spawn(execution::verify_keys(state.clone()));
+            }
+            Err(e) => tracing::error!("config reload rejected, keeping current settings: {}", e),
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn reload_on_sighup(_cli: Cli, _state: Arc<Mutex<AppState>>) {}
@@ -0,0 +1,305 @@
+use prometheus::{
+    Encoder, GaugeVec, Histogram, HistogramOpts, HistogramVec, IntCounterVec, IntGauge,
+    IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use std::sync::LazyLock;
+
+/// Process-wide registry served on `GET /metrics`
+pub static REGISTRY: LazyLock<Registry> = LazyLock::new(Registry::new);
+
+/// Wall time of a full `/scan` request (all exchanges + triangle search)
+pub static SCAN_DURATION: LazyLock<Histogram> = LazyLock::new(|| {
+    let h = Histogram::with_opts(
+        HistogramOpts::new(
+            "arb_scan_duration_seconds",
+            "Duration of a full scan request",
+        )
+        .buckets(vec![
+            0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 30.0, 45.0, 60.0, 120.0,
+        ]),
+    )
+    .expect("metric can be created");
+    register(Box::new(h.clone()));
+    h
+});
+
+/// Per-exchange fetch latency
+pub static FETCH_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    let h = HistogramVec::new(
+        HistogramOpts::new(
+            "arb_exchange_fetch_duration_seconds",
+            "Duration of fetching market data from one exchange",
+        )
+        .buckets(vec![0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 30.0, 60.0]),
+        &["exchange"],
+    )
+    .expect("metric can be created");
+    register(Box::new(h.clone()));
+    h
+});
+
+/// Per-exchange failed fetches
+pub static FETCH_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    counter_vec(
+        "arb_exchange_fetch_errors_total",
+        "Failed market data fetches per exchange and error kind",
+        &["exchange", "kind"],
+    )
+});
+
+/// Symbols seen in exchange metadata (exchangeInfo, /symbols, ...) on the last fetch
+pub static SYMBOLS_TOTAL: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    gauge_vec(
+        "arb_exchange_symbols",
+        "Symbols listed in exchange metadata on the last fetch",
+        &["exchange"],
+    )
+});
+
+/// Exchange requests retried after a transient failure
+pub static HTTP_RETRIES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    counter_vec(
+        "arb_exchange_http_retries_total",
+        "Exchange HTTP requests retried per exchange and error kind",
+        &["exchange", "kind"],
+    )
+});
+
+/// Request weight each venue allows per window (from headers when reported)
+pub static RATE_LIMIT_LIMIT: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    gauge_vec(
+        "arb_exchange_rate_limit",
+        "Request weight allowed per rate-limit window",
+        &["exchange"],
+    )
+});
+
+/// Weight left in the current window
+pub static RATE_LIMIT_REMAINING: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    gauge_vec(
+        "arb_exchange_rate_limit_remaining",
+        "Request weight remaining in the current rate-limit window",
+        &["exchange"],
+    )
+});
+
+/// Requests held back because the budget was nearly spent
+pub static RATE_LIMIT_THROTTLED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    counter_vec(
+        "arb_exchange_rate_limit_throttled_total",
+        "Exchange requests delayed or refused to stay under the rate limit",
+        &["exchange"],
+    )
+});
+
+/// Metadata entries dropped (not trading, missing fields, unsupported quote)
+pub static SYMBOLS_SKIPPED: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    gauge_vec(
+        "arb_exchange_symbols_skipped",
+        "Metadata symbols skipped on the last fetch",
+        &["exchange"],
+    )
+});
+
+/// Ticker rows dropped (unknown symbol, zero price/volume, parse failure)
+pub static TICKERS_SKIPPED: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    gauge_vec(
+        "arb_exchange_tickers_skipped",
+        "Ticker entries skipped on the last fetch",
+        &["exchange"],
+    )
+});
+
+/// Pairs handed to the scanner, split by where they came from (`ws` / `rest`)
+pub static PAIRS_INGESTED: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    gauge_vec(
+        "arb_exchange_pairs_ingested",
+        "Spot pairs ingested on the last fetch",
+        &["exchange", "source"],
+    )
+});
+
+pub static PERPS_INGESTED: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    gauge_vec(
+        "arb_exchange_perps_ingested",
+        "Perpetual swaps ingested on the last fetch",
+        &["exchange"],
+    )
+});
+
+pub static BASIS_PASSING: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    counter_vec(
+        "arb_basis_passing_total",
+        "Spot-perp pairs above the requested minimum profit",
+        &["exchange"],
+    )
+});
+
+/// Raw WebSocket frames received; use `rate()` for messages per second
+pub static WS_MESSAGES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    counter_vec(
+        "arb_ws_messages_total",
+        "WebSocket messages received per exchange",
+        &["exchange"],
+    )
+});
+
+/// Candidate triangles whose cross rate was computed
+pub static TRIANGLES_EVALUATED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    counter_vec(
+        "arb_triangles_evaluated_total",
+        "Triangles evaluated by the scanner",
+        &["exchange"],
+    )
+});
+
+/// Triangles that cleared `min_profit` after fees
+pub static TRIANGLES_PASSING: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    counter_vec(
+        "arb_triangles_passing_total",
+        "Triangles above the requested minimum profit",
+        &["exchange"],
+    )
+});
+
+/// Simulated triangles by outcome (`filled`, `partial`, `failed`)
+pub static PAPER_TRADES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    counter_vec(
+        "arb_paper_trades_total",
+        "Paper-traded triangles per exchange and outcome",
+        &["exchange", "status"],
+    )
+});
+
+/// Triangles sent to a venue by outcome (`filled`, `partial`, `failed`)
+pub static EXECUTIONS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    counter_vec(
+        "arb_executions_total",
+        "Live-executed triangles per exchange and outcome",
+        &["exchange", "status"],
+    )
+});
+
+/// Recovery actions after a leg didn't fill (`retry`, `unwind`, `hold`)
+pub static EXECUTION_RECOVERIES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    counter_vec(
+        "arb_execution_recoveries_total",
+        "Leg-failure recovery actions per exchange",
+        &["exchange", "action"],
+    )
+});
+
+/// Triangles refused by a risk limit or the kill switch (`halted`,
+/// `open_attempts`, `daily_loss`, `exposure`)
+pub static RISK_REJECTIONS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    counter_vec(
+        "arb_risk_rejections_total",
+        "Executions refused by risk limits per exchange and limit",
+        &["exchange", "limit"],
+    )
+});
+
+/// 1 while the kill switch halts all execution
+pub static EXECUTION_HALTED: LazyLock<IntGauge> = LazyLock::new(|| {
+    let g = IntGauge::new("arb_execution_halted", "1 while the kill switch is engaged")
+        .expect("metric can be created");
+    register(Box::new(g.clone()));
+    g
+});
+
+/// Best post-fee profit (percent) seen on the last scan, regardless of
+/// `min_profit`; 0 when the scan found no triangle
+pub static BEST_PROFIT: LazyLock<GaugeVec> = LazyLock::new(|| {
+    let g = GaugeVec::new(
+        Opts::new(
+            "arb_best_profit_percent",
+            "Best post-fee triangle profit found on the last scan",
+        ),
+        &["exchange"],
+    )
+    .expect("metric can be created");
+    register(Box::new(g.clone()));
+    g
+});
+
+fn register(c: Box<dyn prometheus::core::Collector>) {
+    REGISTRY.register(c).expect("metric registered once");
+}
+
+fn counter_vec(name: &str, help: &str, labels: &[&str]) -> IntCounterVec {
+    let c = IntCounterVec::new(Opts::new(name, help), labels).expect("metric can be created");
+    register(Box::new(c.clone()));
+    c
+}
+
+fn gauge_vec(name: &str, help: &str, labels: &[&str]) -> IntGaugeVec {
+    let g = IntGaugeVec::new(Opts::new(name, help), labels).expect("metric can be created");
+    register(Box::new(g.clone()));
+    g
+}
+
+/// Record the metadata/ticker counters each fetcher already logs.
+pub fn record_ingest(
+    exchange: &str,
+    symbols_total: usize,
+    symbols_skipped: usize,
+    tickers_skipped: usize,
+) {
+    SYMBOLS_TOTAL
+        .with_label_values(&[exchange])
+        .set(symbols_total as i64);
+    SYMBOLS_SKIPPED
+        .with_label_values(&[exchange])
+        .set(symbols_skipped as i64);
+    TICKERS_SKIPPED
+        .with_label_values(&[exchange])
+        .set(tickers_skipped as i64);
+}
+
+pub fn record_pairs(exchange: &str, source: &str, count: usize) {
+    PAIRS_INGESTED
+        .with_label_values(&[exchange, source])
+        .set(count as i64);
+}
+
+pub fn record_perps(exchange: &str, count: usize) {
+    PERPS_INGESTED
+        .with_label_values(&[exchange])
+        .set(count as i64);
+}
+
+/// Force registration so every family shows up before the first scan.
+pub fn init() {
+    LazyLock::force(&SCAN_DURATION);
+    LazyLock::force(&FETCH_DURATION);
+    LazyLock::force(&FETCH_ERRORS);
+    LazyLock::force(&HTTP_RETRIES);
+    LazyLock::force(&RATE_LIMIT_LIMIT);
+    LazyLock::force(&RATE_LIMIT_REMAINING);
+    LazyLock::force(&RATE_LIMIT_THROTTLED);
+    LazyLock::force(&SYMBOLS_TOTAL);
+    LazyLock::force(&SYMBOLS_SKIPPED);
+    LazyLock::force(&TICKERS_SKIPPED);
+    LazyLock::force(&PAIRS_INGESTED);
+    LazyLock::force(&PERPS_INGESTED);
+    LazyLock::force(&WS_MESSAGES);
+    LazyLock::force(&TRIANGLES_EVALUATED);
+    LazyLock::force(&TRIANGLES_PASSING);
+    LazyLock::force(&BEST_PROFIT);
+    LazyLock::force(&BASIS_PASSING);
+    LazyLock::force(&PAPER_TRADES);
+    LazyLock::force(&EXECUTIONS);
+    LazyLock::force(&EXECUTION_RECOVERIES);
+    LazyLock::force(&RISK_REJECTIONS);
+    LazyLock::force(&EXECUTION_HALTED);
+}
+
+/// Render the registry in the Prometheus text exposition format
+pub fn render() -> String {
+    let mut buf = Vec::new();
+    let encoder = TextEncoder::new();
+    if let Err(e) = encoder.encode(&REGISTRY.gather(), &mut buf) {
+        tracing::error!("metrics encode error: {}", e);
+    }
+    String::from_utf8(buf).unwrap_or_default()
+}
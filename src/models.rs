@@ -1,18 +1,383 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::assets::AssetRegistry;
+use crate::auth::ApiKey;
+use crate::config::Config;
+use crate::error::ApiError;
+use crate::exchanges::{
+    ClientError, FetchContext, GenericVenues, HttpClient, Markets, MetadataCache, RateBudgets,
+    PERP_EXCHANGES,
+};
+use crate::execution::{
+    BalanceBook, Credentials, ExecutionContext, ExecutionEngine, KeyChecks, RiskGuard,
+    EXECUTION_EXCHANGES,
+};
+use crate::health::FeedRegistry;
+use crate::pair_cache::PairCache;
+use crate::paper::PaperEngine;
+use crate::ratelimit::RateLimiter;
 
 /// Shared app state (you can extend this if needed)
-#[derive(Default)]
 pub struct AppState {
     pub last_results: Option<Vec<TriangularResult>>,
+    /// Per-exchange feed health, shared with the fetchers
+    pub feeds: Arc<FeedRegistry>,
+    /// Active configuration; non-structural sections are swapped on SIGHUP
+    pub config: Config,
+    /// Resolved API keys; empty means auth is disabled
+    pub api_keys: Vec<ApiKey>,
+    /// Pooled, retrying client for every exchange REST call
+    pub http: Arc<HttpClient>,
+    /// Request-weight spent per exchange; outlives `http` across reloads
+    pub rate_budgets: Arc<RateBudgets>,
+    /// Symbol listings, refreshed per exchange TTL and persisted to disk
+    pub metadata: Arc<MetadataCache>,
+    /// Asset aliases applied to fetched pairs; rebuilt on reload
+    pub assets: Arc<AssetRegistry>,
+    /// Venues described in config; rebuilt on reload
+    pub generic: Arc<GenericVenues>,
+    /// Coalesced, briefly cached exchange snapshots
+    pub pair_cache: Arc<PairCache>,
+    /// Per-client `/scan` throttle
+    pub scan_limiter: Arc<RateLimiter>,
+    /// Virtual portfolio fed by `paper` simulations; survives reloads
+    pub paper: Arc<PaperEngine>,
+    /// Live orders sent by `/execute` and auto-execution; survives reloads
+    pub execution: Arc<ExecutionEngine>,
+    /// Loaded trading keys keyed by exchange; rebuilt on reload
+    pub credentials: Arc<HashMap<String, Credentials>>,
+    /// Permission checks of `credentials`; rebuilt with them
+    pub key_checks: Arc<KeyChecks>,
+    /// The keys their check cleared; the only ones orders are sent with
+    pub trading_credentials: Arc<HashMap<String, Credentials>>,
+    /// Account balances of the venues with trading keys
+    pub balances: Arc<BalanceBook>,
+    /// Risk limit bookkeeping and the kill switch; survives reloads
+    pub risk: Arc<RiskGuard>,
+}
+
+impl AppState {
+    pub fn new(config: Config) -> Result<Self, ClientError> {
+        let names = config.exchange_names();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        let rate_budgets = Arc::new(RateBudgets::new(&names));
+        let credentials = Arc::new(config.credentials());
+        let key_checks = Arc::new(KeyChecks::new(
+            &credentials,
+            config.execution.verify_keys,
+            &config.execution.unverifiable_keys,
+        ));
+        Ok(Self {
+            last_results: None,
+            feeds: Arc::new(FeedRegistry::new(&names)),
+            api_keys: config.api_keys(),
+            http: Arc::new(HttpClient::new(&config.http, rate_budgets.clone())?),
+            rate_budgets,
+            metadata: Arc::new(MetadataCache::new(config.metadata.cache_dir())),
+            assets: Arc::new(AssetRegistry::new(&config.assets)),
+            generic: Arc::new(config.generic_venues()),
+            pair_cache: Arc::new(PairCache::default()),
+            scan_limiter: Arc::new(RateLimiter::default()),
+            paper: Arc::new(PaperEngine::default()),
+            execution: Arc::new(ExecutionEngine::default()),
+            trading_credentials: Arc::new(key_checks.usable(&credentials)),
+            credentials,
+            key_checks,
+            balances: Arc::new(BalanceBook::default()),
+            risk: Arc::new(RiskGuard::new(Some(
+                config.execution.risk.state_file.clone(),
+            ))),
+            config,
+        })
+    }
+
+    pub fn fetch_context(&self) -> FetchContext {
+        FetchContext {
+            http: self.http.clone(),
+            feeds: self.feeds.clone(),
+            metadata: self.metadata.clone(),
+            assets: self.assets.clone(),
+            generic: self.generic.clone(),
+        }
+    }
+
+    /// Client, trading keys and settings for executing on `exchange`
+    pub fn execution_context(&self, exchange: &str) -> ExecutionContext {
+        ExecutionContext {
+            http: self.http.clone(),
+            credentials: self.trading_credentials.clone(),
+            config: self.config.execution.clone(),
+            fee_per_leg: self.config.fee_for(exchange),
+            assets: self.assets.clone(),
+            balances: self.balances.clone(),
+            risk: self.risk.clone(),
+            markets: self.metadata.listing(exchange),
+        }
+    }
+
+    /// Apply a freshly loaded config (SIGHUP)
+    pub fn reload(&mut self, fresh: Config) {
+        // in-flight fetches keep the old client until they finish
+        match HttpClient::new(&fresh.http, self.rate_budgets.clone()) {
+            Ok(http) => self.http = Arc::new(http),
+            Err(e) => tracing::error!(
+                "config reload: cannot rebuild HTTP client, keeping the old one: {}",
+                e
+            ),
+        }
+        self.api_keys = fresh.api_keys();
+        // keys are checked again by `verify_keys` before they trade
+        self.credentials = Arc::new(fresh.credentials());
+        self.key_checks = Arc::new(KeyChecks::new(
+            &self.credentials,
+            fresh.execution.verify_keys,
+            &fresh.execution.unverifiable_keys,
+        ));
+        self.trading_credentials = Arc::new(self.key_checks.usable(&self.credentials));
+        self.assets = Arc::new(AssetRegistry::new(&fresh.assets));
+        self.generic = Arc::new(fresh.generic_venues());
+        self.config.reload_from(fresh);
+    }
 }
 
 /// Input payload when user hits "Scan"
 #[derive(Debug, Deserialize)]
 pub struct ScanRequest {
+    pub exchanges: Vec<String>,
+    /// Falls back to `scan.default_min_profit` from the config
+    #[serde(default)]
+    pub min_profit: Option<f64>,
+}
+
+/// A `ScanRequest` that passed validation
+#[derive(Debug)]
+pub struct ValidScan {
+    /// Canonical, de-duplicated exchange names
     pub exchanges: Vec<String>,
     pub min_profit: f64,
 }
 
+impl ScanRequest {
+    /// Well above the built-in venues plus any generic ones a deployment
+    /// describes
+    pub const MAX_EXCHANGES: usize = 32;
+    /// Post-fee profit (%) bounds; a triangle can't lose more than 100%
+    pub const MIN_PROFIT_RANGE: (f64, f64) = (-100.0, 100.0);
+
+    pub fn validate(self, config: &Config) -> Result<ValidScan, ApiError> {
+        if self.exchanges.is_empty() {
+            return Err(ApiError::EmptyExchangeList);
+        }
+        if self.exchanges.len() > Self::MAX_EXCHANGES {
+            return Err(ApiError::TooManyExchanges {
+                max: Self::MAX_EXCHANGES,
+            });
+        }
+
+        let mut exchanges = Vec::new();
+        for ex in &self.exchanges {
+            let name = config
+                .canonical_exchange(ex.trim())
+                .ok_or_else(|| ApiError::UnknownExchange(ex.clone()))?;
+            if !config.exchanges.enabled.contains(&name) {
+                return Err(ApiError::ExchangeDisabled(name));
+            }
+            if !exchanges.contains(&name) {
+                exchanges.push(name);
+            }
+        }
+
+        let (min, max) = Self::MIN_PROFIT_RANGE;
+        let min_profit = self.min_profit.unwrap_or(config.scan.default_min_profit);
+        if !min_profit.is_finite() || !(min..=max).contains(&min_profit) {
+            return Err(ApiError::OutOfRange {
+                field: "min_profit",
+                min,
+                max,
+            });
+        }
+
+        Ok(ValidScan {
+            exchanges,
+            min_profit,
+        })
+    }
+}
+
+/// Input payload of `POST /scan/basis`
+#[derive(Debug, Deserialize)]
+pub struct BasisRequest {
+    pub exchanges: Vec<String>,
+    /// Falls back to `scan.default_min_profit` from the config
+    #[serde(default)]
+    pub min_profit: Option<f64>,
+    /// Falls back to `basis.horizon_hours` from the config
+    #[serde(default)]
+    pub horizon_hours: Option<f64>,
+}
+
+/// A `BasisRequest` that passed validation
+#[derive(Debug)]
+pub struct ValidBasisScan {
+    /// Canonical, de-duplicated exchange names, all with perp ingestion
+    pub exchanges: Vec<String>,
+    pub min_profit: f64,
+    pub horizon_hours: f64,
+}
+
+impl BasisRequest {
+    /// Holding period bounds: one hour up to a quarter
+    pub const HORIZON_RANGE: (f64, f64) = (1.0, 2160.0);
+
+    pub fn validate(self, config: &Config) -> Result<ValidBasisScan, ApiError> {
+        let scan = ScanRequest {
+            exchanges: self.exchanges,
+            min_profit: self.min_profit,
+        }
+        .validate(config)?;
+        if let Some(ex) = scan
+            .exchanges
+            .iter()
+            .find(|ex| !PERP_EXCHANGES.contains(&ex.as_str()))
+        {
+            return Err(ApiError::PerpsUnsupported(ex.clone()));
+        }
+
+        let (min, max) = Self::HORIZON_RANGE;
+        let horizon_hours = self.horizon_hours.unwrap_or(config.basis.horizon_hours);
+        if !horizon_hours.is_finite() || !(min..=max).contains(&horizon_hours) {
+            return Err(ApiError::OutOfRange {
+                field: "horizon_hours",
+                min,
+                max,
+            });
+        }
+
+        Ok(ValidBasisScan {
+            exchanges: scan.exchanges,
+            min_profit: scan.min_profit,
+            horizon_hours,
+        })
+    }
+}
+
+/// Input payload of `POST /execute`; a result from `/scan` can be posted
+/// as-is
+#[derive(Debug, Deserialize)]
+pub struct ExecuteRequest {
+    pub exchange: String,
+    pub legs: [TradeLeg; 3],
+    /// Post-fee profit (%) the scan reported, kept with the execution
+    #[serde(default, alias = "profit_after_fees")]
+    pub expected_profit: Option<f64>,
+}
+
+impl ExecuteRequest {
+    /// Canonical name of the exchange, which must accept orders and be
+    /// enabled; the legs must chain into a closed triangle
+    pub fn validate(&self, config: &Config) -> Result<String, ApiError> {
+        if !config.execution.enabled {
+            return Err(ApiError::ExecutionRefused(
+                "execution is disabled (execution.enabled = false)".to_string(),
+            ));
+        }
+        let name = config
+            .canonical_exchange(self.exchange.trim())
+            .ok_or_else(|| ApiError::UnknownExchange(self.exchange.clone()))?;
+        if !config.exchanges.enabled.contains(&name) {
+            return Err(ApiError::ExchangeDisabled(name));
+        }
+        if !EXECUTION_EXCHANGES.contains(&name.as_str()) {
+            return Err(ApiError::ExecutionRefused(format!(
+                "orders can't be sent to `{}`",
+                name
+            )));
+        }
+        let closed = (0..3).all(|i| self.legs[i].to == self.legs[(i + 1) % 3].from);
+        let priced = self
+            .legs
+            .iter()
+            .all(|l| l.price.is_finite() && l.price > 0.0 && !l.symbol.is_empty());
+        if !closed || !priced {
+            return Err(ApiError::MalformedBody(
+                "legs must be three priced orders whose assets chain back to the start".to_string(),
+            ));
+        }
+        Ok(name)
+    }
+
+    /// Every leg must be a market `exchange` lists, on the side its assets
+    /// imply: a buy spends the quote asset, a sell the base. Checked before
+    /// any order is sent, as the venue would only refuse a leg after the
+    /// ones before it filled.
+    pub fn check_listing(
+        &self,
+        exchange: &str,
+        markets: &Markets,
+        assets: &AssetRegistry,
+    ) -> Result<(), ApiError> {
+        for (i, leg) in self.legs.iter().enumerate() {
+            let Some((base, quote)) = markets.symbols.get(&leg.symbol) else {
+                return Err(ApiError::ExecutionRefused(format!(
+                    "leg {}: `{}` is not listed on {}",
+                    i + 1,
+                    leg.symbol,
+                    exchange
+                )));
+            };
+            let (base, quote) = (
+                assets.canonical(exchange, base),
+                assets.canonical(exchange, quote),
+            );
+            let (from, to) = match leg.side {
+                Side::Buy => (&quote, &base),
+                Side::Sell => (&base, &quote),
+            };
+            if leg.from != *from || leg.to != *to {
+                return Err(ApiError::ExecutionRefused(format!(
+                    "leg {}: {:?} on `{}` trades {} for {}, not {} for {}",
+                    i + 1,
+                    leg.side,
+                    leg.symbol,
+                    from,
+                    to,
+                    leg.from,
+                    leg.to
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Input payload of `POST /admin/kill-switch`
+#[derive(Debug, Deserialize)]
+pub struct KillSwitchRequest {
+    /// `true` halts all execution, `false` resumes it
+    pub halted: bool,
+    /// Recorded with the halt
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Input payload of `POST /admin/release-held`
+#[derive(Debug, Deserialize)]
+pub struct ReleaseHeldRequest {
+    pub exchange: String,
+    /// Canonical asset recovery left held
+    pub asset: String,
+}
+
+/// Output payload of `POST /scan/basis`
+#[derive(Debug, Serialize)]
+pub struct BasisResponse {
+    pub status: String,
+    pub count: usize,
+    pub results: Vec<BasisResult>,
+}
+
 /// Output payload for UI
 #[derive(Debug, Serialize)]
 pub struct ScanResponse {
@@ -24,20 +389,126 @@ pub struct ScanResponse {
 /// Individual spot trading pair price
 #[derive(Debug, Clone)]
 pub struct PairPrice {
+    /// Market as the venue's REST API names it (`BTCUSDT`, `BTC-USDT`,
+    /// `XXBTZUSD`), used to look up its order book
+    pub symbol: String,
+    /// Canonical asset names (see `AssetRegistry`)
     pub base: String,
     pub quote: String,
+    /// The venue's own spelling, shown in results
+    pub native_base: String,
+    pub native_quote: String,
     pub price: f64,
+    /// Best bid / ask, when the venue's ticker carries them
+    pub bid: Option<f64>,
+    pub ask: Option<f64>,
     pub is_spot: bool,
     /// Reported liquidity (normalized to USD) based on **24h high quote volume**
     pub liquidity: f64,
 }
 
+/// Linear (quote-margined) perpetual swap
+#[derive(Debug, Clone)]
+pub struct PerpQuote {
+    /// Canonical asset names (see `AssetRegistry`)
+    pub base: String,
+    pub quote: String,
+    /// Contract as the venue names it (`BTCUSDT`, `BTC-USDT-SWAP`)
+    pub symbol: String,
+    pub mark_price: f64,
+    pub index_price: Option<f64>,
+    /// Rate charged at the next settlement, as a fraction (0.0001 = 0.01%);
+    /// longs pay shorts when positive
+    pub funding_rate: f64,
+    /// Unix milliseconds of the next settlement
+    pub next_funding_time: Option<u64>,
+    pub funding_interval_hours: f64,
+    /// 24h turnover in the quote asset
+    pub liquidity: f64,
+}
+
+/// Spot-perp pair whose basis and/or funding pay more than the fees
+#[derive(Debug, Clone, Serialize)]
+pub struct BasisResult {
+    pub exchange: String,
+    /// Spot market as the venue names it
+    pub spot_pair: String,
+    pub perp_symbol: String,
+    /// `long_spot_short_perp` (cash and carry) or `short_spot_long_perp`
+    /// (needs the spot asset borrowed)
+    pub direction: &'static str,
+    pub spot_price: f64,
+    pub mark_price: f64,
+    pub index_price: Option<f64>,
+    /// Perp premium over spot in percent; negative is a discount
+    pub basis: f64,
+    /// Next funding rate in percent
+    pub funding_rate: f64,
+    /// Funding rate in percent per year at the current rate
+    pub funding_annualized: f64,
+    pub next_funding_time: Option<u64>,
+    /// Funding received over the horizon in this direction, in percent
+    pub expected_funding: f64,
+    /// Two spot and two perp trades, in percent
+    pub trade_fees: f64,
+    /// Basis captured plus expected funding minus fees, in percent
+    pub profit_after_fees: f64,
+    pub horizon_hours: f64,
+    /// Lower of the spot and perp 24h turnover
+    pub min_liquidity: f64,
+}
+
+/// Order side of one triangle leg
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+/// One order of a triangle as it would be placed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeLeg {
+    /// Market as the venue's API names it
+    pub symbol: String,
+    pub side: Side,
+    /// Canonical assets given up and received
+    pub from: String,
+    pub to: String,
+    /// Last price of the market (quote per base) when scanned
+    pub price: f64,
+}
+
+/// Outcome of a triangle traded on paper or live
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TradeStatus {
+    /// All three legs filled completely
+    Filled,
+    /// Every leg traded but one only partly; what it couldn't trade stays
+    /// in that leg's asset
+    Partial,
+    /// A leg could not trade at all; holdings stay in the asset reached
+    Failed,
+}
+
+impl TradeStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            TradeStatus::Filled => "filled",
+            TradeStatus::Partial => "partial",
+            TradeStatus::Failed => "failed",
+        }
+    }
+}
+
 /// Single triangular arbitrage opportunity
 #[derive(Debug, Clone, Serialize)]
 pub struct TriangularResult {
+    pub exchange: String,
     /// Triangle path like `BTC → ETH → USDT → BTC`
     pub triangle: String,
-    /// The actual tradable pairs in that path
+    /// The actual tradable pairs in that path, as the venue names them
     pub pairs: String,
     /// Profit margin before fees
     pub profit_before_fees: f64,
@@ -47,6 +518,175 @@ pub struct TriangularResult {
     pub profit_after_fees: f64,
     /// Liquidity (24h high) for each leg of the triangle
     pub leg_liquidities: [f64; 3],
+    /// Quoted bid/ask spread per leg in basis points; `null` where the
+    /// venue's ticker has no book
+    pub leg_spreads_bps: [Option<f64>; 3],
     /// Minimum liquidity across all 3 legs (24h high)
     pub min_liquidity: f64,
+    /// The three orders, in path order
+    pub legs: [TradeLeg; 3],
+    /// Most of the start asset (`legs[0].from`) the trade can commit: its
+    /// balance, or in inventory mode the size every leg's balance covers.
+    /// `null` while the venue's balances aren't known
+    pub max_size: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::response::IntoResponse;
+
+    fn scan(exchanges: &[&str], min_profit: Option<f64>) -> ScanRequest {
+        ScanRequest {
+            exchanges: exchanges.iter().map(|s| s.to_string()).collect(),
+            min_profit,
+        }
+    }
+
+    /// Status and JSON body as a client would see them
+    async fn respond(err: ApiError) -> (u16, serde_json::Value) {
+        let response = err.into_response();
+        let status = response.status().as_u16();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[test]
+    fn min_profit_must_be_a_finite_percentage() {
+        let config = Config::default();
+        for bad in [f64::NAN, f64::INFINITY, -150.0, 100.5] {
+            let err = scan(&["binance"], Some(bad)).validate(&config).unwrap_err();
+            assert!(
+                matches!(
+                    err,
+                    ApiError::OutOfRange {
+                        field: "min_profit",
+                        ..
+                    }
+                ),
+                "{}: {:?}",
+                bad,
+                err
+            );
+        }
+
+        // small losses are a valid floor; the default fills in when absent
+        let valid = scan(&["binance"], Some(-0.5)).validate(&config).unwrap();
+        assert_eq!(valid.min_profit, -0.5);
+        let valid = scan(&["binance"], None).validate(&config).unwrap();
+        assert_eq!(valid.min_profit, config.scan.default_min_profit);
+    }
+
+    #[test]
+    fn exchange_lists_are_checked_and_normalized() {
+        let mut config = Config::default();
+        assert!(matches!(
+            scan(&[], None).validate(&config),
+            Err(ApiError::EmptyExchangeList)
+        ));
+        let many = vec!["binance"; ScanRequest::MAX_EXCHANGES + 1];
+        assert!(matches!(
+            scan(&many, None).validate(&config),
+            Err(ApiError::TooManyExchanges { .. })
+        ));
+        assert!(matches!(
+            scan(&["binance", "nowhere"], None).validate(&config),
+            Err(ApiError::UnknownExchange(ex)) if ex == "nowhere"
+        ));
+
+        let valid = scan(&[" Binance", "binance", "kraken"], None)
+            .validate(&config)
+            .unwrap();
+        assert_eq!(valid.exchanges, vec!["binance", "kraken"]);
+
+        config.exchanges.enabled = vec!["binance".to_string()];
+        assert!(matches!(
+            scan(&["kraken"], None).validate(&config),
+            Err(ApiError::ExchangeDisabled(ex)) if ex == "kraken"
+        ));
+    }
+
+    #[test]
+    fn execute_legs_must_match_the_listing() {
+        let leg = |symbol: &str, side, from: &str, to: &str| TradeLeg {
+            symbol: symbol.to_string(),
+            side,
+            from: from.to_string(),
+            to: to.to_string(),
+            price: 1.0,
+        };
+        let markets = Markets::new(
+            3,
+            [
+                ("BTCUSDT", "BTC", "USDT"),
+                ("ETHBTC", "ETH", "BTC"),
+                ("ETHUSDT", "ETH", "USDT"),
+            ]
+            .into_iter()
+            .map(|(s, b, q)| (s.to_string(), (b.to_string(), q.to_string())))
+            .collect(),
+        );
+        let assets = AssetRegistry::new(&Config::default().assets);
+        let mut request = ExecuteRequest {
+            exchange: "binance".to_string(),
+            legs: [
+                leg("BTCUSDT", Side::Buy, "USDT", "BTC"),
+                leg("ETHBTC", Side::Buy, "BTC", "ETH"),
+                leg("ETHUSDT", Side::Sell, "ETH", "USDT"),
+            ],
+            expected_profit: None,
+        };
+        assert!(request.check_listing("binance", &markets, &assets).is_ok());
+
+        request.legs[1].side = Side::Sell;
+        let err = request
+            .check_listing("binance", &markets, &assets)
+            .unwrap_err();
+        assert!(matches!(err, ApiError::ExecutionRefused(_)), "{:?}", err);
+        assert_eq!(err.status().as_u16(), 422);
+        assert!(err.to_string().contains("leg 2"), "{}", err);
+
+        request.legs[1] = leg("ETHXBT", Side::Buy, "BTC", "ETH");
+        let err = request
+            .check_listing("binance", &markets, &assets)
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("`ETHXBT` is not listed"),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn errors_have_a_stable_json_shape() {
+        let (status, body) = respond(ApiError::MalformedBody("expected a list".into())).await;
+        assert_eq!(status, 400);
+        assert_eq!(body["status"], "error");
+        assert_eq!(body["code"], "malformed_body");
+        assert_eq!(body["error"], "malformed request body: expected a list");
+
+        let (status, body) = respond(ApiError::UnknownExchange("nowhere".into())).await;
+        assert_eq!(status, 422);
+        assert_eq!(body["code"], "unknown_exchange");
+        assert_eq!(body["exchange"], "nowhere");
+
+        let err = scan(&["binance"], Some(f64::NAN))
+            .validate(&Config::default())
+            .unwrap_err();
+        let (status, body) = respond(err).await;
+        assert_eq!(status, 422);
+        assert_eq!(body["code"], "out_of_range");
+        assert_eq!(body["field"], "min_profit");
+        assert_eq!(
+            body["error"],
+            "min_profit must be a number between -100 and 100"
+        );
+
+        let (status, body) = respond(ApiError::EmptyExchangeList).await;
+        assert_eq!(status, 422);
+        assert_eq!(body["code"], "empty_exchange_list");
+        assert!(body.get("field").is_none());
+    }
 }
@@ -0,0 +1,310 @@
+use futures::future::{BoxFuture, FutureExt, Shared};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::time::{Duration, Instant};
+
+use crate::exchanges::{
+    fetch_exchange_data, ErrorKind, ExchangeError, FetchContext, FetchSettings,
+};
+use crate::models::PairPrice;
+
+type FetchResult = Result<Snapshot, ExchangeError>;
+type InFlight = Shared<BoxFuture<'static, FetchResult>>;
+
+/// One download of an exchange's pairs
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub pairs: Arc<Vec<PairPrice>>,
+    /// Distinct for every download; later downloads have higher ids
+    pub id: u64,
+}
+
+/// What a snapshot may be acted on for, once
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SnapshotUse {
+    Paper,
+    Execution,
+}
+
+enum Slot {
+    Ready {
+        fetched_at: Instant,
+        snapshot: Snapshot,
+    },
+    Fetching(InFlight),
+}
+
+/// Per-exchange pair snapshots shared by every scan.
+///
+/// Concurrent scans of the same exchange join a single in-flight fetch, and
+/// a snapshot younger than the freshness window is served without touching
+/// the exchange. Scanning itself is cheap, so callers still apply their own
+/// `min_profit` to the shared pairs. Trading on a snapshot is claimed, so
+/// every scan served from the cache doesn't trade the same prices again.
+#[derive(Default)]
+pub struct PairCache {
+    slots: Mutex<HashMap<String, Slot>>,
+    next_id: AtomicU64,
+    claimed: Mutex<HashMap<(SnapshotUse, String), u64>>,
+}
+
+impl PairCache {
+    /// Pairs for `exchange` (canonical name), fetched at most once per
+    /// `max_age` no matter how many callers ask.
+    pub async fn get(
+        self: &Arc<Self>,
+        exchange: &str,
+        max_age: Duration,
+        ctx: &FetchContext,
+        settings: &FetchSettings,
+    ) -> FetchResult {
+        let (ctx, settings, name) = (ctx.clone(), *settings, exchange.to_string());
+        self.get_or_fetch(exchange, max_age, move || async move {
+            fetch_exchange_data(&name, &ctx, &settings).await
+        })
+        .await
+    }
+
+    /// Serve `exchange` from the cache or a running fetch, else start `fetch`
+    async fn get_or_fetch<F, Fut>(
+        self: &Arc<Self>,
+        exchange: &str,
+        max_age: Duration,
+        fetch: F,
+    ) -> FetchResult
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Vec<PairPrice>, ExchangeError>> + Send + 'static,
+    {
+        let in_flight = {
+            let mut slots = self.slots.lock().expect("pair cache poisoned");
+            match slots.get(exchange) {
+                Some(Slot::Ready {
+                    fetched_at,
+                    snapshot,
+                }) if fetched_at.elapsed() < max_age => {
+                    tracing::info!(
+                        "{}: serving cached pairs ({}s old)",
+                        exchange,
+                        fetched_at.elapsed().as_secs()
+                    );
+                    return Ok(snapshot.clone());
+                }
+                Some(Slot::Fetching(fut)) => {
+                    tracing::info!("{}: joining in-flight fetch", exchange);
+                    fut.clone()
+                }
+                _ => {
+                    let fut = self.spawn_fetch(exchange, fetch());
+                    slots.insert(exchange.to_string(), Slot::Fetching(fut.clone()));
+                    fut
+                }
+            }
+        };
+
+        in_flight.await
+    }
+
+    /// `true` the first time `snapshot` of `exchange` is claimed for
+    /// `purpose`; snapshots older than one already claimed never are
+    pub fn claim(&self, purpose: SnapshotUse, exchange: &str, snapshot: &Snapshot) -> bool {
+        let mut claimed = self.claimed.lock().expect("pair cache poisoned");
+        let last = claimed.entry((purpose, exchange.to_string())).or_insert(0);
+        if snapshot.id > *last {
+            *last = snapshot.id;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Run the fetch on its own task so it completes (and fills the cache)
+    /// even if every waiting HTTP client disconnects.
+    fn spawn_fetch<F>(self: &Arc<Self>, exchange: &str, fetch: F) -> InFlight
+    where
+        F: Future<Output = Result<Vec<PairPrice>, ExchangeError>> + Send + 'static,
+    {
+        let cache = self.clone();
+        let exchange = exchange.to_string();
+        let label = exchange.clone();
+        let handle = tokio::spawn(async move {
+            let result = fetch.await.map(|pairs| Snapshot {
+                pairs: Arc::new(pairs),
+                id: cache.next_id.fetch_add(1, Ordering::Relaxed) + 1,
+            });
+
+            let mut slots = cache.slots.lock().expect("pair cache poisoned");
+            match &result {
+                Ok(snapshot) => {
+                    slots.insert(
+                        exchange,
+                        Slot::Ready {
+                            fetched_at: Instant::now(),
+                            snapshot: snapshot.clone(),
+                        },
+                    );
+                }
+                Err(_) => {
+                    slots.remove(&exchange);
+                }
+            }
+            result
+        });
+
+        async move {
+            handle.await.unwrap_or_else(|e| {
+                Err(ExchangeError::new(
+                    &label,
+                    "",
+                    ErrorKind::Network {
+                        message: format!("fetch task failed: {}", e),
+                    },
+                ))
+            })
+        }
+        .boxed()
+        .shared()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const MAX_AGE: Duration = Duration::from_millis(300);
+
+    fn pair(symbol: &str) -> PairPrice {
+        PairPrice {
+            symbol: symbol.to_string(),
+            base: "BTC".to_string(),
+            quote: "USDT".to_string(),
+            native_base: "BTC".to_string(),
+            native_quote: "USDT".to_string(),
+            price: 50_000.0,
+            bid: None,
+            ask: None,
+            is_spot: true,
+            liquidity: 1_000_000.0,
+        }
+    }
+
+    /// A fetch that counts its calls and takes a moment to answer
+    fn counted(
+        calls: &Arc<AtomicUsize>,
+    ) -> impl FnOnce() -> BoxFuture<'static, Result<Vec<PairPrice>, ExchangeError>> {
+        let calls = calls.clone();
+        move || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                Ok(vec![pair("BTCUSDT")])
+            }
+            .boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn concurrent_gets_share_one_fetch() {
+        let cache = Arc::new(PairCache::default());
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let (a, b, c) = tokio::join!(
+            cache.get_or_fetch("binance", MAX_AGE, counted(&calls)),
+            cache.get_or_fetch("binance", MAX_AGE, counted(&calls)),
+            cache.get_or_fetch("binance", MAX_AGE, counted(&calls)),
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        let (a, b, c) = (a.unwrap(), b.unwrap(), c.unwrap());
+        assert!(Arc::ptr_eq(&a.pairs, &b.pairs) && Arc::ptr_eq(&b.pairs, &c.pairs));
+        assert!(a.id == b.id && b.id == c.id);
+
+        // another exchange is its own slot
+        cache
+            .get_or_fetch("kraken", MAX_AGE, counted(&calls))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn snapshots_are_reused_until_they_age_out() {
+        let cache = Arc::new(PairCache::default());
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let first = cache
+            .get_or_fetch("binance", MAX_AGE, counted(&calls))
+            .await;
+        tokio::time::sleep(MAX_AGE / 3).await;
+        let cached = cache
+            .get_or_fetch("binance", MAX_AGE, counted(&calls))
+            .await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        let (first, cached) = (first.unwrap(), cached.unwrap());
+        assert!(Arc::ptr_eq(&first.pairs, &cached.pairs));
+        assert_eq!(first.id, cached.id);
+
+        tokio::time::sleep(MAX_AGE).await;
+        let next = cache
+            .get_or_fetch("binance", MAX_AGE, counted(&calls))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(next.id > first.id);
+    }
+
+    #[tokio::test]
+    async fn snapshots_are_claimed_once_per_use() {
+        let cache = Arc::new(PairCache::default());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let first = cache
+            .get_or_fetch("binance", MAX_AGE, counted(&calls))
+            .await
+            .unwrap();
+        let cached = cache
+            .get_or_fetch("binance", MAX_AGE, counted(&calls))
+            .await
+            .unwrap();
+
+        assert!(cache.claim(SnapshotUse::Execution, "binance", &first));
+        assert!(!cache.claim(SnapshotUse::Execution, "binance", &cached));
+        // paper trading and other venues claim separately
+        assert!(cache.claim(SnapshotUse::Paper, "binance", &cached));
+        assert!(cache.claim(SnapshotUse::Execution, "kraken", &first));
+
+        tokio::time::sleep(MAX_AGE).await;
+        let next = cache
+            .get_or_fetch("binance", MAX_AGE, counted(&calls))
+            .await
+            .unwrap();
+        assert!(cache.claim(SnapshotUse::Execution, "binance", &next));
+        assert!(!cache.claim(SnapshotUse::Execution, "binance", &first));
+    }
+
+    #[tokio::test]
+    async fn failed_fetches_are_not_cached() {
+        let cache = Arc::new(PairCache::default());
+        let failing = || async {
+            Err(ExchangeError::new(
+                "binance",
+                "",
+                ErrorKind::Network {
+                    message: "connection reset".to_string(),
+                },
+            ))
+        };
+        assert!(cache
+            .get_or_fetch("binance", MAX_AGE, failing)
+            .await
+            .is_err());
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        cache
+            .get_or_fetch("binance", MAX_AGE, counted(&calls))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
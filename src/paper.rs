@@ -0,0 +1,513 @@
+//! Paper trading: replays detected triangles against live order books.
+//!
+//! Each qualifying triangle is traded with a slice of a virtual balance.
+//! Every leg waits the configured latency, fetches a fresh book and walks it
+//! as a market order, so the fill reflects what the venue offered after the
+//! opportunity was seen rather than the ticker that produced it. Balances
+//! are kept per exchange and seeded from `paper.initial_balances`.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::time::{sleep, Duration};
+
+use crate::config::PaperConfig;
+use crate::exchanges::{fetch_order_book, ExchangeError, HttpClient, OrderBook};
+use crate::metrics;
+use crate::models::{Side, TradeLeg, TradeStatus, TriangularResult};
+use crate::utils::{round2, round4};
+
+/// Simulations running at once; later triangles are skipped until one ends
+const MAX_IN_FLIGHT: usize = 16;
+
+/// One simulated market order
+#[derive(Debug, Clone, Serialize)]
+pub struct LegFill {
+    pub symbol: String,
+    pub side: Side,
+    pub from: String,
+    pub to: String,
+    /// Amount of `from` given up
+    pub spent: f64,
+    /// Amount of `to` obtained, net of the taker fee
+    pub received: f64,
+    /// Last price the scanner saw
+    pub expected_price: f64,
+    /// Volume-weighted fill price
+    pub avg_price: f64,
+    /// Fill price against the expected one in basis points; positive is worse
+    pub slippage_bps: f64,
+    /// Filled entirely within the fetched depth
+    pub complete: bool,
+}
+
+/// A simulated triangle, as listed by `GET /paper/trades`
+#[derive(Debug, Clone, Serialize)]
+pub struct PaperTrade {
+    pub id: u64,
+    pub exchange: String,
+    /// Path as traded, rotated to start at the asset held
+    pub triangle: String,
+    /// Unix seconds
+    pub started_at: u64,
+    pub finished_at: u64,
+    /// Post-fee profit (%) the scanner reported
+    pub expected_profit: f64,
+    pub status: TradeStatus,
+    pub start_asset: String,
+    /// Amount of `start_asset` the first leg actually sold
+    pub start_amount: f64,
+    /// Amount of `start_asset` back after the last leg; 0 when failed
+    pub end_amount: f64,
+    /// `end_amount - start_amount`; intermediate assets left behind by a
+    /// partial fill are not valued
+    pub realized_pnl: f64,
+    pub realized_pnl_pct: f64,
+    pub legs: Vec<LegFill>,
+    pub error: Option<String>,
+}
+
+/// Output payload of `GET /paper/portfolio`
+#[derive(Debug, Serialize)]
+pub struct Portfolio {
+    /// Balance per exchange and asset
+    pub balances: BTreeMap<String, BTreeMap<String, f64>>,
+    /// Realized PnL per start asset over all filled and partial triangles
+    pub realized_pnl: BTreeMap<String, f64>,
+    pub trades: TradeCounts,
+    pub in_flight: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct TradeCounts {
+    pub filled: usize,
+    pub partial: usize,
+    pub failed: usize,
+}
+
+#[derive(Debug, Default)]
+struct Ledger {
+    balances: BTreeMap<String, BTreeMap<String, f64>>,
+    realized_pnl: BTreeMap<String, f64>,
+    counts: TradeCounts,
+    /// Newest last
+    trades: VecDeque<PaperTrade>,
+    next_id: u64,
+    /// `(exchange, triangle)` being simulated, so repeated scans of the same
+    /// opportunity don't stack up
+    in_flight: HashSet<(String, String)>,
+}
+
+impl Ledger {
+    fn wallet(
+        &mut self,
+        exchange: &str,
+        initial: &HashMap<String, f64>,
+    ) -> &mut BTreeMap<String, f64> {
+        self.balances
+            .entry(exchange.to_string())
+            .or_insert_with(|| initial.iter().map(|(a, v)| (a.clone(), *v)).collect())
+    }
+}
+
+/// The triangle rotated to start at an asset held, and the amount committed
+struct Opening {
+    legs: Vec<TradeLeg>,
+    amount: f64,
+}
+
+/// Virtual portfolio shared by every simulation; cheap to lock, never held
+/// across an await
+#[derive(Debug, Default)]
+pub struct PaperEngine {
+    ledger: Mutex<Ledger>,
+}
+
+impl PaperEngine {
+    /// Trade `result` against the venue's live books. Returns `None` when
+    /// the triangle was skipped: already in flight, too many simulations,
+    /// or no balance in any of its assets.
+    pub async fn simulate(
+        &self,
+        result: &TriangularResult,
+        http: &HttpClient,
+        cfg: &PaperConfig,
+        fee_per_leg: f64,
+    ) -> Option<PaperTrade> {
+        let exchange = result.exchange.as_str();
+        self.run(result, cfg, fee_per_leg, |symbol| async move {
+            fetch_order_book(exchange, &symbol, http).await
+        })
+        .await
+    }
+
+    async fn run<F, Fut>(
+        &self,
+        result: &TriangularResult,
+        cfg: &PaperConfig,
+        fee_per_leg: f64,
+        fetch_book: F,
+    ) -> Option<PaperTrade>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<OrderBook, ExchangeError>>,
+    {
+        let exchange = result.exchange.as_str();
+        let key = (result.exchange.clone(), result.triangle.clone());
+        let Opening { legs, amount } = self.open(&key, &result.legs, cfg)?;
+        let started_at = unix_now();
+        let fee_mult = 1.0 - fee_per_leg / 100.0;
+
+        let mut in_hand = amount;
+        let mut fills: Vec<LegFill> = Vec::new();
+        let mut error = None;
+        for leg in &legs {
+            if cfg.latency_ms > 0 {
+                sleep(Duration::from_millis(cfg.latency_ms)).await;
+            }
+            let book = match fetch_book(leg.symbol.clone()).await {
+                Ok(book) => book,
+                Err(e) => {
+                    error = Some(e.to_string());
+                    break;
+                }
+            };
+            let fill = match leg.side {
+                Side::Sell => book.sell(in_hand),
+                Side::Buy => book.buy_with(in_hand),
+            };
+            if fill.spent <= 0.0 {
+                error = Some(format!(
+                    "{}: no liquidity on the {} side",
+                    leg.symbol,
+                    side_of_book(leg.side)
+                ));
+                break;
+            }
+            // what the book couldn't absorb stays in the leg's asset
+            self.credit(exchange, &leg.from, in_hand - fill.spent);
+            let slippage = match leg.side {
+                Side::Sell => (leg.price - fill.avg_price) / leg.price,
+                Side::Buy => (fill.avg_price - leg.price) / leg.price,
+            };
+            fills.push(LegFill {
+                symbol: leg.symbol.clone(),
+                side: leg.side,
+                from: leg.from.clone(),
+                to: leg.to.clone(),
+                spent: fill.spent,
+                received: fill.received * fee_mult,
+                expected_price: leg.price,
+                avg_price: fill.avg_price,
+                slippage_bps: round2(slippage * 10_000.0),
+                complete: fill.complete,
+            });
+            in_hand = fill.received * fee_mult;
+        }
+
+        // after the last leg this is the start asset again
+        let reached = legs.get(fills.len()).map_or(&legs[0].from, |l| &l.from);
+        self.credit(exchange, reached, in_hand);
+
+        let start_asset = legs[0].from.clone();
+        let status = if error.is_some() {
+            TradeStatus::Failed
+        } else if fills.iter().all(|f| f.complete) {
+            TradeStatus::Filled
+        } else {
+            TradeStatus::Partial
+        };
+        let start_amount = fills.first().map_or(0.0, |f| f.spent);
+        let end_amount = if status == TradeStatus::Failed {
+            0.0
+        } else {
+            in_hand
+        };
+        let realized_pnl = if status == TradeStatus::Failed {
+            0.0
+        } else {
+            end_amount - start_amount
+        };
+        let path: Vec<&str> = legs.iter().map(|l| l.from.as_str()).collect();
+        let trade = PaperTrade {
+            id: 0,
+            exchange: exchange.to_string(),
+            triangle: format!("{} → {}", path.join(" → "), start_asset),
+            started_at,
+            finished_at: unix_now(),
+            expected_profit: result.profit_after_fees,
+            status,
+            realized_pnl_pct: if start_amount > 0.0 {
+                round4(realized_pnl / start_amount * 100.0)
+            } else {
+                0.0
+            },
+            start_asset,
+            start_amount,
+            end_amount,
+            realized_pnl,
+            legs: fills,
+            error,
+        };
+        Some(self.close(&key, trade, cfg.max_trades))
+    }
+
+    /// Reserve the triangle and take `trade_fraction` of the first asset
+    /// along its path that has a balance
+    fn open(
+        &self,
+        key: &(String, String),
+        legs: &[TradeLeg; 3],
+        cfg: &PaperConfig,
+    ) -> Option<Opening> {
+        let mut ledger = self.ledger.lock().expect("paper ledger poisoned");
+        if ledger.in_flight.len() >= MAX_IN_FLIGHT || ledger.in_flight.contains(key) {
+            return None;
+        }
+        let wallet = ledger.wallet(&key.0, &cfg.initial_balances);
+        let start =
+            (0..legs.len()).find(|&i| wallet.get(&legs[i].from).is_some_and(|b| *b > 0.0))?;
+        let balance = wallet.get_mut(&legs[start].from)?;
+        let amount = *balance * cfg.trade_fraction;
+        *balance -= amount;
+        ledger.in_flight.insert(key.clone());
+
+        let mut rotated = legs.to_vec();
+        rotated.rotate_left(start);
+        Some(Opening {
+            legs: rotated,
+            amount,
+        })
+    }
+
+    fn credit(&self, exchange: &str, asset: &str, amount: f64) {
+        if amount <= 0.0 {
+            return;
+        }
+        let mut ledger = self.ledger.lock().expect("paper ledger poisoned");
+        // the wallet was seeded when the triangle was opened
+        if let Some(wallet) = ledger.balances.get_mut(exchange) {
+            *wallet.entry(asset.to_string()).or_insert(0.0) += amount;
+        }
+    }
+
+    fn close(
+        &self,
+        key: &(String, String),
+        mut trade: PaperTrade,
+        max_trades: usize,
+    ) -> PaperTrade {
+        let mut ledger = self.ledger.lock().expect("paper ledger poisoned");
+        ledger.in_flight.remove(key);
+        ledger.next_id += 1;
+        trade.id = ledger.next_id;
+        match trade.status {
+            TradeStatus::Filled => ledger.counts.filled += 1,
+            TradeStatus::Partial => ledger.counts.partial += 1,
+            TradeStatus::Failed => ledger.counts.failed += 1,
+        }
+        if trade.status != TradeStatus::Failed {
+            *ledger
+                .realized_pnl
+                .entry(trade.start_asset.clone())
+                .or_insert(0.0) += trade.realized_pnl;
+        }
+        ledger.trades.push_back(trade.clone());
+        while ledger.trades.len() > max_trades {
+            ledger.trades.pop_front();
+        }
+        metrics::PAPER_TRADES
+            .with_label_values(&[&trade.exchange, trade.status.label()])
+            .inc();
+        trade
+    }
+
+    pub fn portfolio(&self) -> Portfolio {
+        let ledger = self.ledger.lock().expect("paper ledger poisoned");
+        Portfolio {
+            balances: ledger.balances.clone(),
+            realized_pnl: ledger.realized_pnl.clone(),
+            trades: ledger.counts,
+            in_flight: ledger.in_flight.len(),
+        }
+    }
+
+    /// Most recent trades first
+    pub fn trades(&self, limit: usize) -> Vec<PaperTrade> {
+        let ledger = self.ledger.lock().expect("paper ledger poisoned");
+        ledger.trades.iter().rev().take(limit).cloned().collect()
+    }
+}
+
+fn side_of_book(side: Side) -> &'static str {
+    match side {
+        Side::Sell => "bid",
+        Side::Buy => "ask",
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leg(symbol: &str, side: Side, from: &str, to: &str, price: f64) -> TradeLeg {
+        TradeLeg {
+            symbol: symbol.to_string(),
+            side,
+            from: from.to_string(),
+            to: to.to_string(),
+            price,
+        }
+    }
+
+    /// USDT → BTC → ETH → USDT: buy BTC, buy ETH with BTC, sell ETH
+    fn triangle() -> TriangularResult {
+        TriangularResult {
+            exchange: "binance".to_string(),
+            triangle: "USDT → BTC → ETH → USDT".to_string(),
+            pairs: "USDT/BTC | BTC/ETH | ETH/USDT".to_string(),
+            profit_before_fees: 1.0,
+            trade_fees: 0.3,
+            profit_after_fees: 0.7,
+            leg_liquidities: [1e9; 3],
+            leg_spreads_bps: [None; 3],
+            min_liquidity: 1e9,
+            legs: [
+                leg("BTCUSDT", Side::Buy, "USDT", "BTC", 100_000.0),
+                leg("ETHBTC", Side::Buy, "BTC", "ETH", 0.05),
+                leg("ETHUSDT", Side::Sell, "ETH", "USDT", 5_050.0),
+            ],
+            max_size: None,
+        }
+    }
+
+    fn config(balances: &[(&str, f64)]) -> PaperConfig {
+        PaperConfig {
+            enabled: true,
+            latency_ms: 0,
+            trade_fraction: 0.5,
+            initial_balances: balances.iter().map(|(a, v)| (a.to_string(), *v)).collect(),
+            ..PaperConfig::default()
+        }
+    }
+
+    fn books(symbol: &str) -> Result<OrderBook, ExchangeError> {
+        match symbol {
+            "BTCUSDT" => Ok(OrderBook::new(vec![], vec![(100_000.0, 1.0)])),
+            "ETHBTC" => Ok(OrderBook::new(vec![], vec![(0.05, 100.0)])),
+            "ETHUSDT" => Ok(OrderBook::new(
+                vec![(5_050.0, 0.5), (5_000.0, 100.0)],
+                vec![],
+            )),
+            _ => Err(ExchangeError::unsupported("binance")),
+        }
+    }
+
+    #[tokio::test]
+    async fn walks_each_book_and_books_the_pnl() {
+        let engine = PaperEngine::default();
+        let trade = engine
+            .run(
+                &triangle(),
+                &config(&[("USDT", 20_000.0)]),
+                0.0,
+                |s| async move { books(&s) },
+            )
+            .await
+            .unwrap();
+
+        // 10k USDT -> 0.1 BTC -> 2 ETH -> 0.5 @ 5050 + 1.5 @ 5000
+        assert_eq!(trade.status, TradeStatus::Filled);
+        assert_eq!(trade.start_amount, 10_000.0);
+        assert_eq!(trade.end_amount, 10_025.0);
+        assert_eq!(trade.realized_pnl, 25.0);
+        assert_eq!(trade.legs[2].avg_price, 5_012.5);
+        assert_eq!(trade.legs[2].slippage_bps, 74.26);
+
+        let portfolio = engine.portfolio();
+        assert_eq!(portfolio.balances["binance"]["USDT"], 20_025.0);
+        assert_eq!(portfolio.realized_pnl["USDT"], 25.0);
+        assert_eq!(engine.trades(10)[0].id, 1);
+    }
+
+    #[tokio::test]
+    async fn starts_at_the_asset_held() {
+        let engine = PaperEngine::default();
+        let trade = engine
+            .run(&triangle(), &config(&[("BTC", 0.2)]), 0.0, |s| async move {
+                books(&s)
+            })
+            .await
+            .unwrap();
+        assert_eq!(trade.start_asset, "BTC");
+        assert_eq!(trade.triangle, "BTC → ETH → USDT → BTC");
+        assert_eq!(trade.legs[0].symbol, "ETHBTC");
+    }
+
+    #[tokio::test]
+    async fn thin_book_is_partial_and_keeps_the_rest() {
+        let engine = PaperEngine::default();
+        // 200k USDT only buys the 1 BTC on offer
+        let trade = engine
+            .run(
+                &triangle(),
+                &config(&[("USDT", 400_000.0)]),
+                0.0,
+                |s| async move { books(&s) },
+            )
+            .await
+            .unwrap();
+        assert_eq!(trade.status, TradeStatus::Partial);
+        assert_eq!(trade.start_amount, 100_000.0);
+        assert!(!trade.legs[0].complete);
+        // the unfilled 100k USDT went back to the wallet
+        assert_eq!(trade.realized_pnl, 25.0);
+        assert_eq!(engine.portfolio().balances["binance"]["USDT"], 400_025.0);
+    }
+
+    #[tokio::test]
+    async fn failed_leg_leaves_holdings_in_the_asset_reached() {
+        let engine = PaperEngine::default();
+        let trade = engine
+            .run(
+                &triangle(),
+                &config(&[("USDT", 20_000.0)]),
+                0.0,
+                |s| async move {
+                    if s == "ETHBTC" {
+                        Err(ExchangeError::unsupported("binance"))
+                    } else {
+                        books(&s)
+                    }
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(trade.status, TradeStatus::Failed);
+        assert_eq!(trade.legs.len(), 1);
+        let wallet = &engine.portfolio().balances["binance"];
+        assert_eq!(wallet["USDT"], 10_000.0);
+        assert_eq!(wallet["BTC"], 0.1);
+    }
+
+    #[tokio::test]
+    async fn skips_triangles_without_a_funded_asset() {
+        let engine = PaperEngine::default();
+        let trade = engine
+            .run(&triangle(), &config(&[("SOL", 5.0)]), 0.0, |s| async move {
+                books(&s)
+            })
+            .await;
+        assert!(trade.is_none());
+        assert_eq!(engine.portfolio().in_flight, 0);
+    }
+}
@@ -0,0 +1,166 @@
+use axum::{
+    extract::{Extension, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Mutex as AsyncMutex;
+use tokio::time::{Duration, Instant};
+
+use crate::auth::Principal;
+use crate::error::ApiError;
+use crate::models::AppState;
+
+/// Entries idle longer than this are dropped when the table is pruned
+const IDLE_EVICT: Duration = Duration::from_secs(600);
+const PRUNE_ABOVE: usize = 10_000;
+
+struct Bucket {
+    tokens: f64,
+    last: Instant,
+}
+
+/// In-memory token bucket per client key (API key name or IP).
+/// Limits are passed on every call so a config reload applies immediately.
+#[derive(Default)]
+pub struct RateLimiter {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    /// Take one token for `client`. `Err` carries how long until one is free.
+    pub fn check(&self, client: &str, per_minute: u32, burst: u32) -> Result<(), Duration> {
+        if per_minute == 0 {
+            return Ok(());
+        }
+        let capacity = burst.max(1) as f64;
+        let refill_per_sec = per_minute as f64 / 60.0;
+        let now = Instant::now();
+
+        let mut buckets = self.buckets.lock().expect("rate limiter poisoned");
+        if buckets.len() > PRUNE_ABOVE {
+            buckets.retain(|_, b| now.duration_since(b.last) < IDLE_EVICT);
+        }
+
+        let bucket = buckets.entry(client.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            last: now,
+        });
+        let elapsed = now.duration_since(bucket.last).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * refill_per_sec).min(capacity);
+        bucket.last = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let wait = (1.0 - bucket.tokens) / refill_per_sec;
+            Err(Duration::from_secs_f64(wait))
+        }
+    }
+}
+
+/// Route middleware for `/scan`; runs inside `require_scope` so the caller is
+/// known. Authenticated callers are limited per key, anonymous ones per IP.
+pub async fn limit_scans(
+    State(state): State<Arc<AsyncMutex<AppState>>>,
+    Extension(principal): Extension<Principal>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let client = if principal.name == "anonymous" {
+        format!("ip:{}", principal.client)
+    } else {
+        format!("key:{}", principal.name)
+    };
+
+    let verdict = {
+        let shared_state = state.lock().await;
+        let limits = &shared_state.config.rate_limit;
+        shared_state
+            .scan_limiter
+            .check(&client, limits.scan_per_minute, limits.scan_burst)
+    };
+
+    match verdict {
+        Ok(()) => next.run(req).await,
+        Err(wait) => {
+            let retry_after = (wait.as_secs_f64().ceil() as u64).max(1);
+            tracing::warn!(target: "audit", %client, retry_after, "scan rate limited");
+            ApiError::RateLimited {
+                retry_after_secs: retry_after,
+            }
+            .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::get, Router};
+    use reqwest::{header, StatusCode};
+    use tokio::net::TcpListener;
+
+    use crate::config::Config;
+
+    #[tokio::test]
+    async fn buckets_refill_at_the_configured_rate() {
+        let limiter = RateLimiter::default();
+        // 600 a minute: one token every 100ms, two at once
+        assert!(limiter.check("a", 600, 2).is_ok());
+        assert!(limiter.check("a", 600, 2).is_ok());
+        let wait = limiter.check("a", 600, 2).unwrap_err();
+        assert!(wait <= Duration::from_millis(100), "{:?}", wait);
+        assert!(wait > Duration::from_millis(50), "{:?}", wait);
+
+        // other clients have their own bucket
+        assert!(limiter.check("b", 600, 2).is_ok());
+
+        tokio::time::sleep(wait + Duration::from_millis(20)).await;
+        assert!(limiter.check("a", 600, 2).is_ok());
+        assert!(limiter.check("a", 600, 2).is_err());
+
+        // a zero rate disables the limit
+        for _ in 0..10 {
+            assert!(limiter.check("a", 0, 2).is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn limited_scans_get_429_with_retry_after() {
+        let mut config = Config::default();
+        config.rate_limit.scan_per_minute = 6;
+        config.rate_limit.scan_burst = 2;
+        let state = Arc::new(AsyncMutex::new(AppState::new(config).unwrap()));
+        let principal = Principal {
+            name: "anonymous".to_string(),
+            client: "127.0.0.1".to_string(),
+        };
+        let app = Router::new()
+            .route(
+                "/scan",
+                get(|| async { "ok" })
+                    .route_layer(middleware::from_fn_with_state(state, limit_scans)),
+            )
+            .layer(Extension(principal));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/scan", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let client = reqwest::Client::new();
+        for _ in 0..2 {
+            let response = client.get(&url).send().await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let response = client.get(&url).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        // one token every 10s, rounded up to whole seconds
+        assert_eq!(response.headers()[header::RETRY_AFTER], "10");
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["code"], "rate_limited");
+        assert_eq!(body["retry_after_secs"], 10);
+    }
+}
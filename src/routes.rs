@@ -1,15 +1,24 @@
 use axum::{
-    extract::State,
-    response::Json,
-    http::StatusCode,
+    extract::{rejection::JsonRejection, Extension, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json},
 };
+use futures::future::join_all;
+use serde::Deserialize;
 use serde_json::json;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-use crate::models::{AppState, ScanRequest, ScanResponse, TriangularResult};
-use crate::exchanges::fetch_exchange_data;
-use crate::logic::scan_triangles;
+use crate::auth::Principal;
+use crate::error::ApiError;
+use crate::execution::Execution;
+use crate::metrics;
+use crate::models::{
+    AppState, BasisRequest, BasisResponse, ExecuteRequest, KillSwitchRequest, ReleaseHeldRequest,
+    ScanRequest, ScanResponse,
+};
+use crate::paper::{PaperTrade, Portfolio};
+use crate::scanner::{run_basis_scan, run_scan};
 
 /// Root endpoint
 pub async fn ui_handler() -> (StatusCode, Json<serde_json::Value>) {
@@ -25,34 +34,418 @@ pub async fn ui_handler() -> (StatusCode, Json<serde_json::Value>) {
 /// Main scan endpoint
 pub async fn scan_handler(
     State(state): State<Arc<Mutex<AppState>>>,
-    Json(payload): Json<ScanRequest>,
-) -> (StatusCode, Json<serde_json::Value>) {
-    let mut all_pairs = Vec::new();
+    Extension(principal): Extension<Principal>,
+    payload: Result<Json<ScanRequest>, JsonRejection>,
+) -> Result<Json<ScanResponse>, ApiError> {
+    let Json(payload) = payload?;
+    let scan = {
+        let shared_state = state.lock().await;
+        payload.validate(&shared_state.config)?
+    };
+    tracing::info!(
+        target: "audit",
+        key = %principal.name,
+        client = %principal.client,
+        exchanges = ?scan.exchanges,
+        min_profit = scan.min_profit,
+        "scan triggered"
+    );
 
-    for ex in &payload.exchanges {
-        match fetch_exchange_data(ex).await {
-            Ok(mut pairs) => {
-                tracing::info!("✅ {} returned {} spot pairs", ex, pairs.len());
-                all_pairs.append(&mut pairs);
-            }
-            Err(e) => {
-                tracing::error!("❌ Error fetching {}: {:?}", ex, e);
+    let names: Vec<&str> = scan.exchanges.iter().map(String::as_str).collect();
+    let (results, failures) = run_scan(&state, &names, scan.min_profit).await;
+    if failures.len() == scan.exchanges.len() {
+        return Err(ApiError::ExchangesUnavailable(failures));
+    }
+
+    Ok(Json(ScanResponse {
+        status: "success".to_string(),
+        count: results.len(),
+        results,
+    }))
+}
+
+/// Spot-perp basis and funding scan of the requested exchanges
+pub async fn basis_scan_handler(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Extension(principal): Extension<Principal>,
+    payload: Result<Json<BasisRequest>, JsonRejection>,
+) -> Result<Json<BasisResponse>, ApiError> {
+    let Json(payload) = payload?;
+    let scan = {
+        let shared_state = state.lock().await;
+        payload.validate(&shared_state.config)?
+    };
+    tracing::info!(
+        target: "audit",
+        key = %principal.name,
+        client = %principal.client,
+        exchanges = ?scan.exchanges,
+        min_profit = scan.min_profit,
+        horizon_hours = scan.horizon_hours,
+        "basis scan triggered"
+    );
+
+    let (results, failures) = run_basis_scan(&state, &scan).await;
+    if failures.len() == scan.exchanges.len() {
+        return Err(ApiError::ExchangesUnavailable(failures));
+    }
+
+    Ok(Json(BasisResponse {
+        status: "success".to_string(),
+        count: results.len(),
+        results,
+    }))
+}
+
+/// Re-download every enabled exchange's symbol listing now, ignoring TTLs
+pub async fn refresh_metadata_handler(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Extension(principal): Extension<Principal>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let (metadata, http, generic, exchanges) = {
+        let shared_state = state.lock().await;
+        (
+            shared_state.metadata.clone(),
+            shared_state.http.clone(),
+            shared_state.generic.clone(),
+            shared_state.config.exchanges.enabled.clone(),
+        )
+    };
+    tracing::info!(
+        target: "audit",
+        key = %principal.name,
+        client = %principal.client,
+        exchanges = ?exchanges,
+        "metadata refresh triggered"
+    );
+
+    let outcomes = join_all(
+        exchanges
+            .iter()
+            .map(|ex| metadata.refresh(ex, &http, &generic)),
+    )
+    .await;
+    let mut refreshed = serde_json::Map::new();
+    let mut failures = Vec::new();
+    for (ex, outcome) in exchanges.iter().zip(outcomes) {
+        match outcome {
+            Ok(markets) => {
+                refreshed.insert(
+                    ex.clone(),
+                    json!({
+                        "symbols": markets.symbols.len(),
+                        "listed": markets.listed,
+                        "fetched_at": markets.fetched_at,
+                    }),
+                );
             }
+            Err(e) => failures.push(e),
         }
     }
+    if refreshed.is_empty() {
+        return Err(ApiError::ExchangesUnavailable(failures));
+    }
+
+    Ok(Json(json!({
+        "status": "success",
+        "refreshed": refreshed,
+        "failures": failures,
+    })))
+}
+
+/// Results of the most recent scan (UI or background), without hitting exchanges
+pub async fn results_handler(State(state): State<Arc<Mutex<AppState>>>) -> Json<ScanResponse> {
+    let results = state.lock().await.last_results.clone().unwrap_or_default();
+
+    Json(ScanResponse {
+        status: "success".to_string(),
+        count: results.len(),
+        results,
+    })
+}
+
+/// Balances, realized PnL and trade counts of the paper-trading portfolio
+pub async fn paper_portfolio_handler(State(state): State<Arc<Mutex<AppState>>>) -> Json<Portfolio> {
+    let paper = state.lock().await.paper.clone();
+    Json(paper.portfolio())
+}
+
+/// `?limit=` of the history endpoints
+#[derive(Debug, Deserialize)]
+pub struct LimitQuery {
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// Most recent paper trades first; `?limit=` defaults to 50
+pub async fn paper_trades_handler(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Query(query): Query<LimitQuery>,
+) -> Json<serde_json::Value> {
+    let paper = state.lock().await.paper.clone();
+    let trades: Vec<PaperTrade> = paper.trades(query.limit.unwrap_or(50));
+    Json(json!({
+        "status": "success",
+        "count": trades.len(),
+        "trades": trades,
+    }))
+}
+
+/// Send the three orders of a triangle to its venue and report the fills
+pub async fn execute_handler(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Extension(principal): Extension<Principal>,
+    payload: Result<Json<ExecuteRequest>, JsonRejection>,
+) -> Result<Json<Execution>, ApiError> {
+    let Json(payload) = payload?;
+    let (exchange, engine, mut ctx, metadata, generic, ttl) = {
+        let shared_state = state.lock().await;
+        let exchange = payload.validate(&shared_state.config)?;
+        let ctx = shared_state.execution_context(&exchange);
+        let ttl = shared_state.config.metadata_ttl(&exchange);
+        (
+            exchange,
+            shared_state.execution.clone(),
+            ctx,
+            shared_state.metadata.clone(),
+            shared_state.generic.clone(),
+            ttl,
+        )
+    };
+    let markets = metadata
+        .get(&exchange, ttl, &ctx.http, &generic)
+        .await
+        .map_err(|e| ApiError::ExchangesUnavailable(vec![e]))?;
+    payload.check_listing(&exchange, &markets, &ctx.assets)?;
+    ctx.markets = Some(markets);
+    tracing::info!(
+        target: "audit",
+        key = %principal.name,
+        client = %principal.client,
+        exchange = %exchange,
+        legs = ?payload.legs.iter().map(|l| l.symbol.as_str()).collect::<Vec<_>>(),
+        "execution requested"
+    );
+
+    // spawned so a client that goes away doesn't cut the triangle short
+    let attempt = tokio::spawn(async move {
+        engine
+            .execute(&exchange, &payload.legs, payload.expected_profit, &ctx)
+            .await
+    });
+    let execution = match attempt.await {
+        Ok(outcome) => outcome.map_err(|e| ApiError::ExecutionRefused(e.to_string()))?,
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
+    };
+    Ok(Json(execution))
+}
+
+/// Most recent live executions first; `?limit=` defaults to 50
+pub async fn executions_handler(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Query(query): Query<LimitQuery>,
+) -> Json<serde_json::Value> {
+    let engine = state.lock().await.execution.clone();
+    let executions = engine.executions(query.limit.unwrap_or(50));
+    Json(json!({
+        "status": "success",
+        "count": executions.len(),
+        "executions": executions,
+    }))
+}
+
+/// Last fetched account balances of every venue with trading keys
+pub async fn balances_handler(
+    State(state): State<Arc<Mutex<AppState>>>,
+) -> Json<serde_json::Value> {
+    let book = state.lock().await.balances.clone();
+    Json(json!({
+        "status": "success",
+        "balances": book.snapshot(),
+    }))
+}
+
+/// Risk limit usage and the kill switch
+pub async fn risk_handler(State(state): State<Arc<Mutex<AppState>>>) -> Json<serde_json::Value> {
+    let (risk, limits) = {
+        let shared_state = state.lock().await;
+        (
+            shared_state.risk.clone(),
+            shared_state.config.execution.risk.clone(),
+        )
+    };
+    let status = risk.status();
+    Json(json!({
+        "status": "success",
+        "halted": status.halt.is_some(),
+        "halt": status.halt,
+        "open_attempts": status.open_attempts,
+        "max_open_attempts": limits.max_open_attempts,
+        "exposure": status.exposure,
+        "max_exposure": limits.max_exposure,
+        "daily_pnl": status.daily_pnl,
+        "held": status.held,
+        "max_daily_loss": limits.max_daily_loss,
+        "max_notional": limits.max_notional,
+    }))
+}
+
+/// Trading keys per venue with the outcome of their permission check; no key
+/// material
+pub async fn credentials_handler(
+    State(state): State<Arc<Mutex<AppState>>>,
+) -> Json<serde_json::Value> {
+    let (checks, verify) = {
+        let shared_state = state.lock().await;
+        (
+            shared_state.key_checks.clone(),
+            shared_state.config.execution.verify_keys,
+        )
+    };
+    Json(json!({
+        "status": "success",
+        "verify_keys": verify,
+        "keys": checks.snapshot(),
+    }))
+}
+
+/// Halt or resume all execution; the state is saved and survives restarts.
+/// A halt takes effect even when saving fails, a resume doesn't.
+pub async fn kill_switch_handler(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Extension(principal): Extension<Principal>,
+    payload: Result<Json<KillSwitchRequest>, JsonRejection>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let Json(payload) = payload?;
+    let risk = state.lock().await.risk.clone();
+    tracing::info!(
+        target: "audit",
+        key = %principal.name,
+        client = %principal.client,
+        halted = payload.halted,
+        reason = ?payload.reason,
+        "kill switch set"
+    );
+
+    if payload.halted {
+        let reason = payload
+            .reason
+            .unwrap_or_else(|| "halted via the API".to_string());
+        risk.halt(reason, principal.name.clone())
+            .await
+            .map_err(|e| {
+                ApiError::StateNotSaved(format!(
+                    "execution is halted, but the halt won't survive a restart: {}",
+                    e
+                ))
+            })?;
+    } else {
+        risk.resume()
+            .await
+            .map_err(|e| ApiError::StateNotSaved(format!("execution stays halted: {}", e)))?;
+    }
+    Ok(Json(json!({
+        "status": "success",
+        "halted": risk.is_halted(),
+        "halt": risk.halt_state(),
+    })))
+}
 
-    let results: Vec<TriangularResult> =
-        scan_triangles(&all_pairs, payload.min_profit, 0.10);
+/// Stop counting a held leftover toward `max_exposure` once it has been
+/// unwound by hand; saved like the kill switch
+pub async fn release_held_handler(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Extension(principal): Extension<Principal>,
+    payload: Result<Json<ReleaseHeldRequest>, JsonRejection>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let Json(payload) = payload?;
+    let risk = state.lock().await.risk.clone();
+    tracing::info!(
+        target: "audit",
+        key = %principal.name,
+        client = %principal.client,
+        exchange = %payload.exchange,
+        asset = %payload.asset,
+        "held leftover released"
+    );
+
+    let released = risk
+        .release_held(&payload.exchange, &payload.asset)
+        .await
+        .map_err(|e| {
+            ApiError::StateNotSaved(format!(
+                "released, but it will count as held again after a restart: {}",
+                e
+            ))
+        })?;
+    Ok(Json(json!({
+        "status": "success",
+        "released": released,
+        "held": risk.status().held,
+    })))
+}
 
-    let mut shared_state = state.lock().await;
-    shared_state.last_results = Some(results.clone());
+/// Liveness probe: the process is up and serving requests
+pub async fn healthz_handler() -> (StatusCode, Json<serde_json::Value>) {
+    (StatusCode::OK, Json(json!({ "status": "ok" })))
+}
+
+/// Readiness probe: 503 while any required exchange feed is down
+pub async fn readyz_handler(
+    State(state): State<Arc<Mutex<AppState>>>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let (feeds, required) = {
+        let shared_state = state.lock().await;
+        (
+            shared_state.feeds.snapshot(),
+            shared_state.config.exchanges.required.clone(),
+        )
+    };
+
+    let down: Vec<&String> = required
+        .iter()
+        .filter(|ex| !feeds.get(ex.as_str()).is_some_and(|f| f.is_up()))
+        .collect();
+
+    let code = if down.is_empty() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
 
     (
-        StatusCode::OK,
-        Json(json!(ScanResponse {
-            status: "success".to_string(),
-            count: results.len(),
-            results,
+        code,
+        Json(json!({
+            "status": if down.is_empty() { "ready" } else { "not_ready" },
+            "required": required,
+            "down": down,
+            "feeds": feeds,
         })),
     )
-            }
+}
+
+/// Operator view: feed health plus each venue's remaining request budget
+pub async fn status_handler(State(state): State<Arc<Mutex<AppState>>>) -> Json<serde_json::Value> {
+    let (feeds, budgets) = {
+        let shared_state = state.lock().await;
+        (
+            shared_state.feeds.clone(),
+            shared_state.rate_budgets.clone(),
+        )
+    };
+
+    Json(json!({
+        "status": "ok",
+        "feeds": feeds.snapshot(),
+        "rate_limits": budgets.snapshot(),
+    }))
+}
+
+/// Prometheus scrape endpoint
+pub async fn metrics_handler() -> impl IntoResponse {
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics::render(),
+    )
+}
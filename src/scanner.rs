@@ -0,0 +1,269 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::{Duration, Instant};
+
+use crate::basis::{scan_basis, BasisParams};
+use crate::exchanges::{fetch_perp_data, ExchangeError, SUPPORTED_EXCHANGES};
+use crate::execution::{ExecutionContext, ExecutionMode, EXECUTION_EXCHANGES};
+use crate::logic::{scan_triangles, Holdings, ScanStats, VenuePrices};
+use crate::metrics;
+use crate::models::{AppState, BasisResult, TriangularResult, ValidBasisScan};
+use crate::pair_cache::SnapshotUse;
+use crate::utils::round4;
+
+/// Fetch every requested exchange and scan their pairs as one graph.
+/// Shared by `POST /scan` and the background scan loop; fetches go through
+/// the pair cache, so concurrent callers share one download per exchange.
+/// `exchanges` must be canonical names; failed venues are returned alongside
+/// the results.
+pub async fn run_scan(
+    state: &Arc<Mutex<AppState>>,
+    exchanges: &[&str],
+    min_profit: f64,
+) -> (Vec<TriangularResult>, Vec<ExchangeError>) {
+    let started = Instant::now();
+    let (ctx, cache, config, paper, execution, credentials, balances, risk) = {
+        let shared_state = state.lock().await;
+        (
+            shared_state.fetch_context(),
+            shared_state.pair_cache.clone(),
+            shared_state.config.clone(),
+            shared_state.paper.clone(),
+            shared_state.execution.clone(),
+            shared_state.trading_credentials.clone(),
+            shared_state.balances.clone(),
+            shared_state.risk.clone(),
+        )
+    };
+    let mut fetched = Vec::new();
+    let mut failures = Vec::new();
+
+    for &name in exchanges {
+        let settings = config.fetch_settings(name);
+        match cache.get(name, config.cache_ttl(), &ctx, &settings).await {
+            Ok(snapshot) => {
+                tracing::info!("✅ {} returned {} spot pairs", name, snapshot.pairs.len());
+                fetched.push((name, snapshot, balances.get(name)));
+            }
+            Err(e) => {
+                tracing::error!("❌ Error fetching {}: {}", name, e);
+                failures.push(e);
+            }
+        }
+    }
+
+    let venues: Vec<VenuePrices> = fetched
+        .iter()
+        .map(|(name, snapshot, held)| VenuePrices {
+            exchange: name,
+            prices: &snapshot.pairs,
+            fee_per_leg: config.fee_for(name),
+            holdings: held.as_ref().map(|b| Holdings {
+                balances: &b.assets,
+                start_amounts: &config.execution.start_amounts,
+                inventory: config.execution.mode == ExecutionMode::Inventory,
+            }),
+        })
+        .collect();
+    // best first, as `scan_triangles` sorts them
+    let (results, stats) = scan_triangles(&venues, min_profit);
+    for (venue, stats) in venues.iter().zip(&stats) {
+        record_scan_stats(venue.exchange, stats);
+    }
+    metrics::SCAN_DURATION.observe(started.elapsed().as_secs_f64());
+
+    // scans served from the cache see the same prices again; each venue's
+    // snapshot is paper traded and auto-executed at most once
+    let claim = |purpose, exchange: &str| {
+        fetched
+            .iter()
+            .find(|(name, ..)| *name == exchange)
+            .is_some_and(|(_, snapshot, _)| cache.claim(purpose, exchange, snapshot))
+    };
+
+    if config.paper.enabled {
+        let mut claimed = HashMap::new();
+        // generic venues have no order book to trade against
+        for result in results.iter().filter(|r| {
+            r.profit_after_fees >= config.paper.min_profit
+                && SUPPORTED_EXCHANGES.contains(&r.exchange.as_str())
+        }) {
+            if !*claimed
+                .entry(result.exchange.as_str())
+                .or_insert_with(|| claim(SnapshotUse::Paper, &result.exchange))
+            {
+                continue;
+            }
+            let (paper, http, result) = (paper.clone(), ctx.http.clone(), result.clone());
+            let (cfg, fee) = (config.paper.clone(), config.fee_for(&result.exchange));
+            tokio::spawn(async move {
+                if let Some(trade) = paper.simulate(&result, &http, &cfg, fee).await {
+                    tracing::info!(
+                        "📝 paper {} {}: {:?}, pnl {} {}",
+                        trade.exchange,
+                        trade.triangle,
+                        trade.status,
+                        trade.realized_pnl,
+                        trade.start_asset
+                    );
+                }
+            });
+        }
+    }
+
+    if config.execution.enabled && config.execution.auto && !risk.is_halted() {
+        // results are sorted, so this sends each venue's best triangle that
+        // something is held for; the engine runs one at a time per venue
+        let mut sent = HashSet::new();
+        for result in results.iter().filter(|r| {
+            r.profit_after_fees >= config.execution.min_profit
+                && EXECUTION_EXCHANGES.contains(&r.exchange.as_str())
+                && credentials.contains_key(&r.exchange)
+                && r.max_size.is_none_or(|size| size > 0.0)
+        }) {
+            if !sent.insert(result.exchange.clone())
+                || !claim(SnapshotUse::Execution, &result.exchange)
+            {
+                continue;
+            }
+            let execution_ctx = ExecutionContext {
+                http: ctx.http.clone(),
+                credentials: credentials.clone(),
+                config: config.execution.clone(),
+                fee_per_leg: config.fee_for(&result.exchange),
+                assets: ctx.assets.clone(),
+                balances: balances.clone(),
+                risk: risk.clone(),
+                markets: ctx.metadata.listing(&result.exchange),
+            };
+            let (execution, result) = (execution.clone(), result.clone());
+            tokio::spawn(async move {
+                let outcome = execution
+                    .execute(
+                        &result.exchange,
+                        &result.legs,
+                        Some(result.profit_after_fees),
+                        &execution_ctx,
+                    )
+                    .await;
+                match outcome {
+                    Ok(done) => tracing::info!(
+                        "💸 executed {} {}: {:?}, pnl {} {}",
+                        done.exchange,
+                        done.triangle,
+                        done.status,
+                        done.realized_pnl,
+                        done.start_asset
+                    ),
+                    Err(e) => tracing::warn!("auto-execution skipped: {}", e),
+                }
+            });
+        }
+    }
+
+    // keep the previous results if every venue failed
+    if failures.len() < exchanges.len() {
+        state.lock().await.last_results = Some(results.clone());
+    }
+
+    (results, failures)
+}
+
+/// Fetch spot pairs (through the pair cache) and perpetuals of every
+/// requested exchange and look for basis and funding trades. Failed venues
+/// are returned alongside the results.
+pub async fn run_basis_scan(
+    state: &Arc<Mutex<AppState>>,
+    scan: &ValidBasisScan,
+) -> (Vec<BasisResult>, Vec<ExchangeError>) {
+    let (ctx, cache, config) = {
+        let shared_state = state.lock().await;
+        (
+            shared_state.fetch_context(),
+            shared_state.pair_cache.clone(),
+            shared_state.config.clone(),
+        )
+    };
+    let mut results: Vec<BasisResult> = Vec::new();
+    let mut failures = Vec::new();
+
+    for name in &scan.exchanges {
+        let settings = config.fetch_settings(name);
+        let (spot, perps) = tokio::join!(
+            cache.get(name, config.cache_ttl(), &ctx, &settings),
+            fetch_perp_data(name, &ctx)
+        );
+        let (spot, perps) = match (spot, perps) {
+            (Ok(spot), Ok(perps)) => (spot, perps),
+            (Err(e), _) | (_, Err(e)) => {
+                tracing::error!("❌ Error fetching {} for the basis scan: {}", name, e);
+                failures.push(e);
+                continue;
+            }
+        };
+        let params = BasisParams {
+            min_profit: scan.min_profit,
+            horizon_hours: scan.horizon_hours,
+            spot_fee: config.fee_for(name),
+            perp_fee: config.perp_fee_for(name),
+            allow_spot_short: config.basis.allow_spot_short,
+        };
+        let (mut found, stats) = scan_basis(name, &spot.pairs, &perps, &params);
+        tracing::info!(
+            "✅ {}: {} perps, {} matched to spot, {} above min_profit",
+            name,
+            perps.len(),
+            stats.evaluated,
+            stats.passing
+        );
+        metrics::BASIS_PASSING
+            .with_label_values(&[name])
+            .inc_by(stats.passing as u64);
+        results.append(&mut found);
+    }
+
+    results.sort_by(|x, y| y.profit_after_fees.total_cmp(&x.profit_after_fees));
+    (results, failures)
+}
+
+/// Periodically scan all enabled exchanges so feed health and metrics stay
+/// current without UI traffic. Interval and exchange list are re-read each
+/// round, so a SIGHUP reload takes effect on the next tick.
+pub async fn background_scans(state: Arc<Mutex<AppState>>) {
+    loop {
+        let (interval, exchanges, min_profit) = {
+            let shared_state = state.lock().await;
+            let cfg = &shared_state.config;
+            (
+                cfg.scan.interval_secs,
+                cfg.exchanges.enabled.clone(),
+                cfg.scan.default_min_profit,
+            )
+        };
+
+        if interval == 0 {
+            // disabled; poll again later in case a reload turns it on
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            continue;
+        }
+
+        let names: Vec<&str> = exchanges.iter().map(String::as_str).collect();
+        let (results, _) = run_scan(&state, &names, min_profit).await;
+        tracing::info!("background scan found {} opportunities", results.len());
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+    }
+}
+
+fn record_scan_stats(exchange: &str, stats: &ScanStats) {
+    metrics::TRIANGLES_EVALUATED
+        .with_label_values(&[exchange])
+        .inc_by(stats.evaluated as u64);
+    metrics::TRIANGLES_PASSING
+        .with_label_values(&[exchange])
+        .inc_by(stats.passing as u64);
+    // a scan without a triangle resets it, so a closed gap stops showing
+    metrics::BEST_PROFIT
+        .with_label_values(&[exchange])
+        .set(stats.best_profit.map_or(0.0, round4));
+}